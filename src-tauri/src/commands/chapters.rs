@@ -32,38 +32,120 @@ pub async fn save_chapter(
     let (root_path, metadata_arc) = state.projects.get_context(project_id).await?;
     let mut metadata = metadata_arc.lock().await;
 
-    // 1. Resolve filename to ensure chapter exists
-    let filename = storage::resolve_chapter_path(&root_path, &metadata, &chapter_id)?
-        .file_name()
-        .ok_or_else(|| crate::errors::Error::InvalidStructure {
-            path: root_path.clone(),
-            reason: "Invalid chapter path".to_string(),
-        })?
-        .to_string_lossy()
-        .to_string();
+    // 1. Resolve path to ensure chapter exists
+    let chapter_path = storage::resolve_chapter_path(&root_path, &metadata, &chapter_id)?;
 
-    // 2. Write content
-    let repo = storage::LocalFileRepository;
-
-    // Create snapshot
-    storage::create_snapshot(&repo, &root_path, &chapter_id, &content).await?;
+    // 2. Update word count on a copy, so a failed write leaves the loaded state alone
+    let mut updated = metadata.clone();
+    let chapter = updated.manifest.chapter_mut(&chapter_id).ok_or_else(|| {
+        crate::errors::Error::ChapterNotFound {
+            id: chapter_id.clone(),
+        }
+    })?;
+    chapter.word_count = crate::models::count_words(&content);
+    updated.updated_at = chrono::Utc::now();
 
-    storage::write_chapter_file(&repo, &root_path, &filename, &content).await?;
+    // 3. Write snapshot, chapter and project.json together
+    let repo = storage::LocalFileRepository;
+    let snapshot = storage::plan_snapshot(
+        &repo,
+        &root_path,
+        &chapter_id,
+        &content,
+        None,
+        Some(&metadata.settings.snapshots),
+    )
+    .await?;
 
-    // 3. Update word count
-    if let Some(chapter) = metadata
-        .manifest
-        .chapters
-        .iter_mut()
-        .find(|c| c.id == chapter_id)
-    {
-        chapter.word_count = crate::models::count_words(&content);
-    } else {
-        return Err(crate::errors::Error::ChapterNotFound { id: chapter_id });
+    let mut transaction = storage::Transaction::new(&root_path);
+    if let Some(planned) = &snapshot {
+        for (path, bytes) in &planned.writes {
+            transaction.write(path, bytes.clone());
+        }
     }
+    transaction.write(chapter_path, content);
+    transaction.write(
+        root_path.join(storage::METADATA_FILENAME),
+        updated.to_project_file()?,
+    );
+    transaction.commit().await?;
+    *metadata = updated;
 
-    metadata.updated_at = chrono::Utc::now();
-    storage::save_project_metadata(&root_path, &metadata).await?;
+    // The save is done; a failed compaction is retried after the next snapshot
+    if snapshot.is_some() {
+        if let Err(e) =
+            storage::prune_snapshots(&repo, &root_path, &chapter_id, &metadata.settings.snapshots)
+                .await
+        {
+            log::warn!("Failed to compact snapshots of {}: {}", chapter_id, e);
+        }
+    }
 
     Ok(metadata.clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tauri::Manager;
+
+    #[tokio::test]
+    async fn test_save_chapter_survives_damaged_snapshot_index() {
+        let app = tauri::test::mock_app();
+        app.manage(AppState::new());
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("book");
+        let projects = &app.state::<AppState>().projects;
+        let project_id = projects
+            .create_project(path.clone(), "Book".to_string(), "Tester".to_string())
+            .await
+            .unwrap()
+            .id;
+        let chapter_id = projects
+            .mutate_project(project_id, |metadata| {
+                let chapter = metadata.manifest.create_chapter(None, "One".to_string());
+                metadata.manifest.push(chapter);
+                Ok(())
+            })
+            .await
+            .unwrap()
+            .manifest
+            .chapters[0]
+            .id
+            .clone();
+
+        save_chapter(
+            app.state(),
+            project_id,
+            chapter_id.clone(),
+            "First".to_string(),
+        )
+        .await
+        .unwrap();
+        // Cut short by a crash
+        let index = path
+            .join(storage::MANUSCRIPT_DIR)
+            .join(".snapshots")
+            .join(&chapter_id)
+            .join("index.json");
+        std::fs::write(&index, "{\"snapshots\":[").unwrap();
+
+        let metadata = save_chapter(
+            app.state(),
+            project_id,
+            chapter_id.clone(),
+            "Second try".to_string(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            metadata.manifest.chapter(&chapter_id).unwrap().word_count,
+            2
+        );
+        let repo = storage::LocalFileRepository;
+        let snapshots = storage::list_snapshots(&repo, &path, &chapter_id)
+            .await
+            .unwrap();
+        assert_eq!(snapshots.len(), 1);
+    }
+}
@@ -1,4 +1,7 @@
+use crate::export::characters::{build_characters, CharacterFormat};
+use crate::import::characters::{apply_records, parse_records};
 use crate::models::{Character, ProjectMetadata};
+use crate::validation;
 use crate::AppState;
 use tauri::State;
 use uuid::Uuid;
@@ -33,3 +36,46 @@ pub async fn delete_character(
         })
         .await
 }
+
+/// Write every character to a CSV or JSON file.
+#[tauri::command]
+pub async fn export_characters(
+    state: State<'_, AppState>,
+    project_id: Uuid,
+    format: CharacterFormat,
+    output_path: String,
+) -> crate::errors::Result<()> {
+    validation::validate_path(&output_path)?;
+
+    let (_, metadata_arc) = state.projects.get_context(project_id).await?;
+    let characters = {
+        let guard = metadata_arc.lock().await;
+        guard.characters.clone()
+    };
+
+    tokio::fs::write(&output_path, build_characters(&characters, format)?).await?;
+    Ok(())
+}
+
+/// Add characters from a CSV or JSON file. With `merge`, rows matching an
+/// existing character by id or name update it instead of adding a new one.
+#[tauri::command]
+pub async fn import_characters(
+    state: State<'_, AppState>,
+    project_id: Uuid,
+    format: CharacterFormat,
+    source_path: String,
+    merge: bool,
+) -> crate::errors::Result<ProjectMetadata> {
+    validation::validate_path(&source_path)?;
+
+    let text = tokio::fs::read_to_string(&source_path).await?;
+    let records = parse_records(&text, format)?;
+
+    state
+        .projects
+        .mutate_project(project_id, |metadata| {
+            apply_records(metadata, records, merge).map(|_| ())
+        })
+        .await
+}
@@ -0,0 +1,131 @@
+use crate::errors::Error;
+use crate::export::bible::{self, BibleFormat};
+use crate::export::outline::{self, OutlineFormat};
+use crate::export::timeline::{self, TimelineFormat};
+use crate::export::{self, CompileFormat, CompileOutput};
+use crate::models::CompilePreset;
+use crate::storage;
+use crate::validation;
+use crate::AppState;
+use std::path::Path;
+use tauri::State;
+use uuid::Uuid;
+
+/// Compile the manuscript, in manifest order. Single-file formats are written to
+/// `output_path`; multi-file formats (LaTeX, HTML site) use it as the output folder.
+/// Without a preset id the whole manifest is compiled with default settings.
+#[tauri::command]
+pub async fn compile_project(
+    state: State<'_, AppState>,
+    project_id: Uuid,
+    format: CompileFormat,
+    output_path: String,
+    preset_id: Option<String>,
+) -> crate::errors::Result<()> {
+    validation::validate_path(&output_path)?;
+
+    let (root_path, metadata_arc) = state.projects.get_context(project_id).await?;
+
+    // Clone metadata and release the lock before reading every chapter
+    let metadata = {
+        let guard = metadata_arc.lock().await;
+        guard.clone()
+    };
+
+    let preset = match preset_id {
+        Some(id) => metadata
+            .compile_preset(&id)
+            .cloned()
+            .ok_or(Error::PresetNotFound { id })?,
+        None => CompilePreset::default(),
+    };
+
+    let repo = storage::LocalFileRepository;
+    let manuscript = export::load_manuscript(&repo, &root_path, &metadata, &preset).await?;
+
+    match export::render(&manuscript, &preset, format)? {
+        CompileOutput::File(bytes) => tokio::fs::write(&output_path, bytes).await?,
+        CompileOutput::Directory(files) => {
+            let base = Path::new(&output_path);
+            for (name, bytes) in files {
+                let path = base.join(name);
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                tokio::fs::write(&path, bytes).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write a character bible for the project. Mention counts and first appearances
+/// come from the same scan as the character graph.
+#[tauri::command]
+pub async fn export_character_bible(
+    state: State<'_, AppState>,
+    project_id: Uuid,
+    format: BibleFormat,
+    output_path: String,
+) -> crate::errors::Result<()> {
+    validation::validate_path(&output_path)?;
+
+    let (root_path, metadata_arc) = state.projects.get_context(project_id).await?;
+    let metadata = {
+        let guard = metadata_arc.lock().await;
+        guard.clone()
+    };
+
+    let mentions = state
+        .intelligence
+        .chapter_mentions(project_id, &root_path, &metadata)
+        .await?;
+    let appearances = bible::appearances(&metadata, &mentions);
+
+    tokio::fs::write(
+        &output_path,
+        bible::build_bible(&metadata, &appearances, format),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Write the scene chronology as an iCalendar file or a CSV table.
+#[tauri::command]
+pub async fn export_timeline(
+    state: State<'_, AppState>,
+    project_id: Uuid,
+    format: TimelineFormat,
+    output_path: String,
+) -> crate::errors::Result<()> {
+    validation::validate_path(&output_path)?;
+
+    let (_, metadata_arc) = state.projects.get_context(project_id).await?;
+    let content = {
+        let guard = metadata_arc.lock().await;
+        timeline::build_timeline(&guard, format)
+    };
+
+    tokio::fs::write(&output_path, content).await?;
+    Ok(())
+}
+
+/// Write an outline of the manifest tree, in reading order.
+#[tauri::command]
+pub async fn export_outline(
+    state: State<'_, AppState>,
+    project_id: Uuid,
+    format: OutlineFormat,
+    output_path: String,
+) -> crate::errors::Result<()> {
+    validation::validate_path(&output_path)?;
+
+    let (_, metadata_arc) = state.projects.get_context(project_id).await?;
+    let content = {
+        let guard = metadata_arc.lock().await;
+        outline::build_outline(&guard, format)
+    };
+
+    tokio::fs::write(&output_path, content).await?;
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use crate::import::text::{self, TextSplitOptions};
+use crate::import::{self, docx, epub, markdown, scrivener, PreviewNode};
+use crate::integrations;
+use crate::models::ProjectMetadata;
+use crate::validation;
+use crate::AppState;
+
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// Create a project at `path` from a Scrivener `.scriv` bundle. The Draft folder
+/// becomes the manifest, synopses are kept and labels become plotlines.
+#[tauri::command]
+pub async fn import_scrivener(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    source_path: String,
+    path: String,
+    author: String,
+) -> crate::errors::Result<ProjectMetadata> {
+    validation::validate_path(&source_path)?;
+    validation::validate_path(&path)?;
+    validation::validate_name(&author)?;
+
+    let imported = scrivener::read_bundle(Path::new(&source_path)).await?;
+
+    let root_path = PathBuf::from(&path);
+    let metadata =
+        import::create_project(&state.projects, root_path.clone(), author, imported).await?;
+
+    integrations::research_watcher::init_research_watcher(&app, root_path);
+
+    Ok(metadata)
+}
+
+/// Create a project at `path` from a Word `.docx` manuscript. Heading 1–3
+/// paragraphs start nodes at the matching depth.
+#[tauri::command]
+pub async fn import_docx(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    source_path: String,
+    path: String,
+    author: String,
+) -> crate::errors::Result<ProjectMetadata> {
+    validation::validate_path(&source_path)?;
+    validation::validate_path(&path)?;
+    validation::validate_name(&author)?;
+
+    let source = Path::new(&source_path);
+    let fallback_title = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Imported Project".to_string());
+    let imported = docx::read_docx(tokio::fs::read(source).await?, &fallback_title)?;
+
+    let root_path = PathBuf::from(&path);
+    let metadata =
+        import::create_project(&state.projects, root_path.clone(), author, imported).await?;
+
+    integrations::research_watcher::init_research_watcher(&app, root_path);
+
+    Ok(metadata)
+}
+
+/// Create a project at `path` from a folder of Markdown files, such as an
+/// Obsidian vault. Files under `research_folder` go to the research vault.
+#[tauri::command]
+pub async fn import_markdown_folder(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    source_path: String,
+    path: String,
+    author: String,
+    research_folder: Option<String>,
+) -> crate::errors::Result<ProjectMetadata> {
+    validation::validate_path(&source_path)?;
+    validation::validate_path(&path)?;
+    validation::validate_name(&author)?;
+    if let Some(folder) = &research_folder {
+        validation::validate_path(folder)?;
+    }
+
+    let imported = markdown::read_folder(
+        Path::new(&source_path),
+        research_folder.as_deref().map(Path::new),
+    )
+    .await?;
+
+    let root_path = PathBuf::from(&path);
+    let metadata =
+        import::create_project(&state.projects, root_path.clone(), author, imported).await?;
+
+    integrations::research_watcher::init_research_watcher(&app, root_path);
+
+    Ok(metadata)
+}
+
+/// Split a `.txt` or `.md` manuscript without writing anything, returning the
+/// tree `import_text` would create.
+#[tauri::command]
+pub async fn preview_text_import(
+    source_path: String,
+    options: TextSplitOptions,
+) -> crate::errors::Result<Vec<PreviewNode>> {
+    validation::validate_path(&source_path)?;
+
+    let imported = text::read_text_file(Path::new(&source_path), &options).await?;
+    Ok(imported.nodes.iter().map(|node| node.preview()).collect())
+}
+
+/// Create a project at `path` from a single `.txt` or `.md` manuscript, split
+/// into chapters (and optionally scenes) by `options`.
+#[tauri::command]
+pub async fn import_text(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    source_path: String,
+    path: String,
+    author: String,
+    options: TextSplitOptions,
+) -> crate::errors::Result<ProjectMetadata> {
+    validation::validate_path(&source_path)?;
+    validation::validate_path(&path)?;
+    validation::validate_name(&author)?;
+
+    let imported = text::read_text_file(Path::new(&source_path), &options).await?;
+
+    let root_path = PathBuf::from(&path);
+    let metadata =
+        import::create_project(&state.projects, root_path.clone(), author, imported).await?;
+
+    integrations::research_watcher::init_research_watcher(&app, root_path);
+
+    Ok(metadata)
+}
+
+/// Create a project at `path` from an EPUB, one node per spine document, nested
+/// following the book's table of contents.
+#[tauri::command]
+pub async fn import_epub(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    source_path: String,
+    path: String,
+    author: String,
+) -> crate::errors::Result<ProjectMetadata> {
+    validation::validate_path(&source_path)?;
+    validation::validate_path(&path)?;
+    validation::validate_name(&author)?;
+
+    let source = Path::new(&source_path);
+    let fallback_title = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Imported Project".to_string());
+    let imported = epub::read_epub(tokio::fs::read(source).await?, &fallback_title)?;
+
+    let root_path = PathBuf::from(&path);
+    let metadata =
+        import::create_project(&state.projects, root_path.clone(), author, imported).await?;
+
+    integrations::research_watcher::init_research_watcher(&app, root_path);
+
+    Ok(metadata)
+}
@@ -62,7 +62,7 @@ mod tests {
     use super::*;
     use crate::intelligence::graph::build_character_graph_cached;
     use crate::intelligence::scanner::CharacterScanner;
-    use crate::models::{Character, CharacterRole, Manifest, ProjectMetadata};
+    use crate::models::{Character, CharacterRole, ProjectMetadata};
     use std::collections::HashMap;
 
     use crate::models::utils::WordIndexer;
@@ -113,17 +113,12 @@ mod tests {
     }
 
     fn make_test_metadata(characters: Vec<Character>) -> ProjectMetadata {
-        ProjectMetadata {
-            id: Uuid::new_v4(),
-            title: "Test".to_string(),
-            author: "Author".to_string(),
-            created_at: chrono::Utc::now(),
-            updated_at: chrono::Utc::now(),
-            settings: Default::default(),
-            manifest: Manifest { chapters: vec![] },
-            characters,
-            plotlines: vec![],
-        }
+        let mut metadata = ProjectMetadata::new("Test".to_string(), "Author".to_string());
+        metadata.characters = characters;
+        metadata.plotlines = vec![];
+        metadata.compile_presets = vec![];
+        metadata.reindex();
+        metadata
     }
 
     #[tokio::test]
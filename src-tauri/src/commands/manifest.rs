@@ -29,12 +29,7 @@ pub async fn update_node_metadata(
     state
         .projects
         .mutate_project(project_id, |metadata| {
-            if let Some(node) = metadata
-                .manifest
-                .chapters
-                .iter_mut()
-                .find(|c| c.id == node_id)
-            {
+            if let Some(node) = metadata.manifest.chapter_mut(&node_id) {
                 if let Some(t) = update.title {
                     node.title = t;
                 }
@@ -82,7 +77,7 @@ pub async fn create_node(
     storage::write_chapter_file(&repo, &root_path, &new_chapter.filename, "").await?;
 
     // 3. Save Metadata
-    metadata.manifest.chapters.push(new_chapter);
+    metadata.manifest.push(new_chapter);
 
     metadata.updated_at = chrono::Utc::now();
     storage::save_project_metadata(&root_path, &metadata).await?;
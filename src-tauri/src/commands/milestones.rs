@@ -0,0 +1,146 @@
+use crate::integrations;
+use crate::models::ProjectMetadata;
+use crate::storage::{self, Milestone};
+use crate::validation;
+use crate::AppState;
+use tauri::State;
+use uuid::Uuid;
+
+#[tauri::command]
+pub async fn create_milestone(
+    state: State<'_, AppState>,
+    project_id: Uuid,
+    name: String,
+    include_research: bool,
+) -> crate::errors::Result<Milestone> {
+    validation::validate_name(&name)?;
+
+    let (root_path, metadata_arc) = state.projects.get_context(project_id).await?;
+    let metadata = metadata_arc.lock().await;
+    let repo = storage::LocalFileRepository;
+    storage::create_milestone(&repo, &root_path, &metadata, &name, include_research).await
+}
+
+#[tauri::command]
+pub async fn list_milestones(
+    state: State<'_, AppState>,
+    project_id: Uuid,
+) -> crate::errors::Result<Vec<Milestone>> {
+    let (root_path, _metadata) = state.projects.get_context(project_id).await?;
+    let repo = storage::LocalFileRepository;
+    storage::list_milestones(&repo, &root_path).await
+}
+
+/// Puts the whole project back as the milestone recorded it, after saving the
+/// current state as another milestone.
+#[tauri::command]
+pub async fn restore_milestone(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    project_id: Uuid,
+    milestone_id: Uuid,
+) -> crate::errors::Result<ProjectMetadata> {
+    let (root_path, metadata_arc) = state.projects.get_context(project_id).await?;
+    let mut metadata = metadata_arc.lock().await;
+    let repo = storage::LocalFileRepository;
+
+    let milestone = storage::load_milestone(&repo, &root_path, milestone_id).await?;
+    let includes_research = milestone
+        .files
+        .keys()
+        .any(|key| key.starts_with(storage::RESEARCH_DIR));
+
+    let safety_name = format!("Before restoring {}", milestone.name);
+    storage::create_milestone(
+        &repo,
+        &root_path,
+        &metadata,
+        &safety_name,
+        includes_research,
+    )
+    .await?;
+    storage::restore_milestone(&repo, &root_path, &metadata, &milestone).await?;
+    state
+        .projects
+        .reload_metadata(project_id, &mut metadata)
+        .await?;
+
+    if includes_research {
+        // Rescan so the research panel picks up the restored index
+        integrations::research_watcher::init_research_watcher(&app, root_path);
+    }
+
+    Ok(metadata.clone())
+}
+
+/// Puts one chapter back as the milestone recorded it, recreating it if it was
+/// deleted since.
+#[tauri::command]
+pub async fn restore_milestone_chapter(
+    state: State<'_, AppState>,
+    project_id: Uuid,
+    milestone_id: Uuid,
+    chapter_id: String,
+) -> crate::errors::Result<ProjectMetadata> {
+    let (root_path, metadata_arc) = state.projects.get_context(project_id).await?;
+    let mut metadata = metadata_arc.lock().await;
+    let repo = storage::LocalFileRepository;
+
+    let milestone = storage::load_milestone(&repo, &root_path, milestone_id).await?;
+    let (mut chapter, content) =
+        storage::read_milestone_chapter(&repo, &root_path, &milestone, &chapter_id).await?;
+    chapter.word_count = crate::models::count_words(&content);
+
+    // Changes go to a copy, so a failed write leaves the loaded state alone
+    let mut updated = metadata.clone();
+    let mut transaction = storage::Transaction::new(&root_path);
+    let mut snapshot = None;
+    let chapter_path = if let Some(existing) = updated.manifest.chapter_mut(&chapter_id) {
+        existing.word_count = chapter.word_count;
+
+        // Keep the current text in the chapter's history
+        let current =
+            storage::read_chapter_content(&repo, &root_path, &metadata, &chapter_id).await?;
+        snapshot =
+            storage::plan_snapshot(&repo, &root_path, &chapter_id, &current, None, None).await?;
+        if let Some(planned) = &snapshot {
+            for (path, bytes) in &planned.writes {
+                transaction.write(path, bytes.clone());
+            }
+        }
+        storage::resolve_chapter_path(&root_path, &metadata, &chapter_id)?
+    } else {
+        // Its parent may be gone too; put it at the top level then
+        if chapter
+            .parent_id
+            .as_deref()
+            .is_some_and(|parent| updated.manifest.chapter(parent).is_none())
+        {
+            chapter.parent_id = None;
+        }
+        let path = storage::resolve_chapter_path_from_filename(&root_path, &chapter.filename)?;
+        updated.manifest.push(chapter);
+        path
+    };
+    updated.updated_at = chrono::Utc::now();
+
+    transaction.write(chapter_path, content);
+    transaction.write(
+        root_path.join(storage::METADATA_FILENAME),
+        updated.to_project_file()?,
+    );
+    transaction.commit().await?;
+    *metadata = updated;
+
+    // The restore is done; a failed compaction is retried after the next snapshot
+    if snapshot.is_some() {
+        if let Err(e) =
+            storage::prune_snapshots(&repo, &root_path, &chapter_id, &metadata.settings.snapshots)
+                .await
+        {
+            log::warn!("Failed to compact snapshots of {}: {}", chapter_id, e);
+        }
+    }
+
+    Ok(metadata.clone())
+}
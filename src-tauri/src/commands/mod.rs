@@ -1,15 +1,21 @@
 pub mod chapters;
 pub mod characters;
+pub mod export;
+pub mod import;
 pub mod intelligence;
 pub mod manifest;
+pub mod milestones;
 pub mod project;
 pub mod research;
 pub mod snapshots;
 
 pub use chapters::*;
 pub use characters::*;
+pub use export::*;
+pub use import::*;
 pub use intelligence::*;
 pub use manifest::*;
+pub use milestones::*;
 pub use project::*;
 pub use research::*;
 pub use snapshots::*;
@@ -1,8 +1,11 @@
+use crate::errors::Error;
 use crate::integrations;
-use crate::models::{Plotline, ProjectMetadata, ProjectSettings};
+use crate::models::{CompilePreset, Plotline, ProjectMetadata, ProjectSettings};
+use crate::storage;
 use crate::validation;
 use crate::AppState;
 
+use std::collections::HashSet;
 use std::path::PathBuf;
 use tauri::State;
 use uuid::Uuid;
@@ -47,6 +50,22 @@ pub async fn load_project(
     Ok(metadata)
 }
 
+/// Writes the project's pending changes and unloads it. Called when the user
+/// closes the project or switches to another one.
+#[tauri::command]
+pub async fn close_project(
+    state: State<'_, AppState>,
+    project_id: Uuid,
+) -> crate::errors::Result<()> {
+    if let Ok((root_path, _metadata)) = state.projects.get_context(project_id).await {
+        state
+            .research
+            .stop_watching(&root_path.join(storage::RESEARCH_DIR))
+            .await;
+    }
+    state.projects.unload_project(project_id).await
+}
+
 #[tauri::command]
 pub async fn update_project_settings(
     state: State<'_, AppState>,
@@ -76,3 +95,60 @@ pub async fn update_plotlines(
         })
         .await
 }
+
+#[tauri::command]
+pub async fn update_compile_presets(
+    state: State<'_, AppState>,
+    project_id: Uuid,
+    presets: Vec<CompilePreset>,
+) -> crate::errors::Result<ProjectMetadata> {
+    let mut ids = HashSet::new();
+    for preset in &presets {
+        validation::validate_name(&preset.name)?;
+        if preset.id.trim().is_empty() || !ids.insert(preset.id.as_str()) {
+            return Err(Error::Validation(format!(
+                "Invalid or duplicate preset id `{}`",
+                preset.id
+            )));
+        }
+    }
+
+    state
+        .projects
+        .mutate_project(project_id, |metadata| {
+            metadata.compile_presets = presets;
+            Ok(())
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tauri::Manager;
+
+    #[tokio::test]
+    async fn test_close_project_writes_pending_changes() {
+        let app = tauri::test::mock_app();
+        app.manage(AppState::new());
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("book");
+        let project_id = app
+            .state::<AppState>()
+            .projects
+            .create_project(path.clone(), "Book".to_string(), "Tester".to_string())
+            .await
+            .unwrap()
+            .id;
+
+        // Held in memory for a moment before it is written
+        update_plotlines(app.state(), project_id, Vec::new())
+            .await
+            .unwrap();
+        close_project(app.state(), project_id).await.unwrap();
+
+        let on_disk = crate::storage::load_project_metadata(&path).await.unwrap();
+        assert!(on_disk.plotlines.is_empty());
+        assert!(!app.state::<AppState>().projects.is_loaded(project_id).await);
+    }
+}
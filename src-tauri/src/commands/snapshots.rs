@@ -1,4 +1,4 @@
-use crate::storage;
+use crate::storage::{self, SnapshotEntry};
 use crate::AppState;
 use tauri::State;
 use uuid::Uuid;
@@ -8,7 +8,7 @@ pub async fn list_snapshots(
     state: State<'_, AppState>,
     project_id: Uuid,
     chapter_id: String,
-) -> crate::errors::Result<Vec<String>> {
+) -> crate::errors::Result<Vec<SnapshotEntry>> {
     let (root_path, _metadata) = state.projects.get_context(project_id).await?;
     let repo = storage::LocalFileRepository;
     storage::list_snapshots(&repo, &root_path, &chapter_id).await
@@ -19,11 +19,11 @@ pub async fn load_snapshot_content(
     state: State<'_, AppState>,
     project_id: Uuid,
     chapter_id: String,
-    filename: String,
+    snapshot_id: String,
 ) -> crate::errors::Result<String> {
     let (root_path, _metadata) = state.projects.get_context(project_id).await?;
     let repo = storage::LocalFileRepository;
-    storage::read_snapshot_content(&repo, &root_path, &chapter_id, &filename).await
+    storage::read_snapshot_content(&repo, &root_path, &chapter_id, &snapshot_id).await
 }
 
 #[tauri::command]
@@ -32,63 +32,78 @@ pub async fn create_snapshot(
     project_id: Uuid,
     chapter_id: String,
     content: String,
+    label: Option<String>,
 ) -> crate::errors::Result<()> {
-    let (root_path, _metadata) = state.projects.get_context(project_id).await?;
+    let (root_path, metadata_arc) = state.projects.get_context(project_id).await?;
+    // Held throughout: saves and compaction also write to the snapshot store
+    let metadata = metadata_arc.lock().await;
     let repo = storage::LocalFileRepository;
-    storage::create_snapshot(&repo, &root_path, &chapter_id, &content).await?;
+    storage::create_snapshot(
+        &repo,
+        &root_path,
+        &chapter_id,
+        &content,
+        label.as_deref(),
+        &metadata.settings.snapshots,
+    )
+    .await?;
     Ok(())
 }
 
+/// Puts a chapter back as the snapshot recorded it, after taking a snapshot of
+/// its current text.
 #[tauri::command]
 pub async fn restore_snapshot(
     state: State<'_, AppState>,
     project_id: Uuid,
     chapter_id: String,
-    snapshot_filename: String,
+    snapshot_id: String,
 ) -> crate::errors::Result<String> {
     let (root_path, metadata_arc) = state.projects.get_context(project_id).await?;
     let mut metadata = metadata_arc.lock().await;
-
-    // We need current content to create a safety snapshot
     let repo = storage::LocalFileRepository;
 
-    // Get chapter filename from metadata
-    let chapter_filename = storage::resolve_chapter_path(&root_path, &metadata, &chapter_id)?
-        .file_name()
-        .ok_or_else(|| crate::errors::Error::InvalidStructure {
-            path: root_path.clone(),
-            reason: "Invalid chapter path".to_string(),
-        })?
-        .to_string_lossy()
-        .to_string();
-
-    let current_content =
-        storage::read_chapter_content(&repo, &root_path, &metadata, &chapter_id).await?;
-
-    let new_content = storage::restore_snapshot(
-        &repo,
-        &root_path,
-        &chapter_id,
-        &snapshot_filename,
-        &current_content,
-        &chapter_filename,
-    )
-    .await?;
+    let chapter_path = storage::resolve_chapter_path(&root_path, &metadata, &chapter_id)?;
+    let content =
+        storage::read_snapshot_content(&repo, &root_path, &chapter_id, &snapshot_id).await?;
 
-    // Update word count
-    if let Some(chapter) = metadata
-        .manifest
-        .chapters
-        .iter_mut()
-        .find(|c| c.id == chapter_id)
-    {
-        chapter.word_count = crate::models::count_words(&new_content);
+    // Changes go to a copy, so a failed write leaves the loaded state alone
+    let mut updated = metadata.clone();
+    if let Some(chapter) = updated.manifest.chapter_mut(&chapter_id) {
+        chapter.word_count = crate::models::count_words(&content);
+    }
+    updated.updated_at = chrono::Utc::now();
+
+    // Keep the current text in the chapter's history
+    let current = storage::read_chapter_content(&repo, &root_path, &metadata, &chapter_id).await?;
+    let snapshot =
+        storage::plan_snapshot(&repo, &root_path, &chapter_id, &current, None, None).await?;
+
+    let mut transaction = storage::Transaction::new(&root_path);
+    if let Some(planned) = &snapshot {
+        for (path, bytes) in &planned.writes {
+            transaction.write(path, bytes.clone());
+        }
+    }
+    transaction.write(chapter_path, content.clone());
+    transaction.write(
+        root_path.join(storage::METADATA_FILENAME),
+        updated.to_project_file()?,
+    );
+    transaction.commit().await?;
+    *metadata = updated;
+
+    // The restore is done; a failed compaction is retried after the next snapshot
+    if snapshot.is_some() {
+        if let Err(e) =
+            storage::prune_snapshots(&repo, &root_path, &chapter_id, &metadata.settings.snapshots)
+                .await
+        {
+            log::warn!("Failed to compact snapshots of {}: {}", chapter_id, e);
+        }
     }
 
-    metadata.updated_at = chrono::Utc::now();
-    storage::save_project_metadata(&root_path, &metadata).await?;
-
-    Ok(new_content)
+    Ok(content)
 }
 
 #[tauri::command]
@@ -96,7 +111,7 @@ pub async fn branch_snapshot(
     state: State<'_, AppState>,
     project_id: Uuid,
     snapshot_chapter_id: String,
-    snapshot_filename: String,
+    snapshot_id: String,
 ) -> crate::errors::Result<crate::models::ProjectMetadata> {
     let (root_path, metadata_arc) = state.projects.get_context(project_id).await?;
     let mut metadata = metadata_arc.lock().await;
@@ -104,12 +119,12 @@ pub async fn branch_snapshot(
 
     // 1. Read snapshot content
     let content =
-        storage::read_snapshot_content(&repo, &root_path, &snapshot_chapter_id, &snapshot_filename)
+        storage::read_snapshot_content(&repo, &root_path, &snapshot_chapter_id, &snapshot_id)
             .await?;
 
     // 2. Create new chapter in manifest (Branching off)
     // We can name it "Branch from <date>" or similar.
-    let timestamp = snapshot_filename
+    let timestamp = snapshot_id
         .split('_')
         .next()
         .filter(|s| !s.is_empty())
@@ -119,9 +134,7 @@ pub async fn branch_snapshot(
     // Find parent_id of the original chapter to place the branch as a sibling
     let parent_id = metadata
         .manifest
-        .chapters
-        .iter()
-        .find(|c| c.id == snapshot_chapter_id)
+        .chapter(&snapshot_chapter_id)
         .and_then(|c| c.parent_id.clone());
 
     let new_chapter = metadata.manifest.create_chapter(parent_id, name);
@@ -134,7 +147,7 @@ pub async fn branch_snapshot(
     let mut chapter_to_update = new_chapter; // It was returned by value
     chapter_to_update.word_count = crate::models::count_words(&content);
 
-    metadata.manifest.chapters.push(chapter_to_update);
+    metadata.manifest.push(chapter_to_update);
     metadata.updated_at = chrono::Utc::now();
     storage::save_project_metadata(&root_path, &metadata).await?;
 
@@ -41,6 +41,24 @@ pub enum Error {
 
     #[error("Lock poisoned: {0}")]
     LockPoisoned(String),
+
+    #[error("Snapshot `{id}` not found")]
+    SnapshotNotFound { id: String },
+
+    #[error("Milestone `{id}` not found")]
+    MilestoneNotFound { id: uuid::Uuid },
+
+    #[error("Compile preset `{id}` not found")]
+    PresetNotFound { id: String },
+
+    #[error("Export error: {0}")]
+    Export(String),
+
+    #[error("Import error: {0}")]
+    Import(String),
+
+    #[error("Project format version {found} is newer than this app supports ({supported}); please update BrutWrite")]
+    UnsupportedSchema { found: u64, supported: u64 },
 }
 
 impl serde::Serialize for Error {
@@ -71,6 +89,12 @@ impl Error {
             Error::ArtifactNotFound(_) => "ARTIFACT_NOT_FOUND",
             Error::Intelligence(_) => "INTELLIGENCE_ERROR",
             Error::LockPoisoned(_) => "LOCK_POISONED",
+            Error::SnapshotNotFound { .. } => "SNAPSHOT_NOT_FOUND",
+            Error::MilestoneNotFound { .. } => "MILESTONE_NOT_FOUND",
+            Error::PresetNotFound { .. } => "PRESET_NOT_FOUND",
+            Error::Export(_) => "EXPORT_ERROR",
+            Error::Import(_) => "IMPORT_ERROR",
+            Error::UnsupportedSchema { .. } => "UNSUPPORTED_SCHEMA",
         }
     }
 }
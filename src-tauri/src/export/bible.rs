@@ -0,0 +1,458 @@
+//! Character bible writer.
+//!
+//! Lists every character of the project with their profile and `CharacterEngine`,
+//! grouped by role. Mention counts come from the intelligence scanner; the first
+//! appearance is the earliest chapter in reading order that mentions the
+//! character. Empty fields are left out so short entries stay short.
+
+use super::html_book::document;
+use super::manuscript::reading_order;
+use super::markdown::{escape_line, escape_text};
+use crate::intelligence::graph::CachedMentions;
+use crate::models::{Character, CharacterRole, ProjectMetadata};
+use html_escape::encode_text;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Output formats supported by `export_character_bible`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BibleFormat {
+    Markdown,
+    Html,
+}
+
+/// Where a character shows up in the manuscript.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Appearance {
+    pub mention_count: u32,
+    /// Title of the first chapter, in reading order, that mentions the character.
+    pub first_chapter: Option<String>,
+}
+
+/// Folds the scanner's per-chapter mentions into one `Appearance` per character.
+pub fn appearances(
+    metadata: &ProjectMetadata,
+    chapter_mentions: &HashMap<String, CachedMentions>,
+) -> HashMap<Uuid, Appearance> {
+    let mut result: HashMap<Uuid, Appearance> = HashMap::new();
+
+    for mentions in chapter_mentions.values() {
+        for (_, _, id) in mentions.iter() {
+            result.entry(*id).or_default().mention_count += 1;
+        }
+    }
+
+    for entry in reading_order(&metadata.manifest) {
+        let Some(mentions) = chapter_mentions.get(&entry.chapter.id) else {
+            continue;
+        };
+        for (_, _, id) in mentions.iter() {
+            if let Some(appearance) = result.get_mut(id) {
+                appearance
+                    .first_chapter
+                    .get_or_insert_with(|| entry.chapter.title.clone());
+            }
+        }
+    }
+
+    result
+}
+
+/// Renders the bible in the requested format.
+pub fn build_bible(
+    metadata: &ProjectMetadata,
+    appearances: &HashMap<Uuid, Appearance>,
+    format: BibleFormat,
+) -> String {
+    match format {
+        BibleFormat::Markdown => build_markdown(metadata, appearances),
+        BibleFormat::Html => build_html(metadata, appearances),
+    }
+}
+
+fn build_markdown(metadata: &ProjectMetadata, appearances: &HashMap<Uuid, Appearance>) -> String {
+    let mut sections = vec![format!(
+        "# {}\n\n{}",
+        escape_line(&escape_text(&bible_title(metadata))),
+        escape_line(&escape_text(&metadata.author))
+    )];
+
+    for character in by_role(&metadata.characters) {
+        let mut parts = vec![format!("## {}", escape_line(&escape_text(&character.name)))];
+
+        let fields: Vec<String> = fields(character, appearances.get(&character.id))
+            .into_iter()
+            .map(|(label, value)| format!("**{}:** {}", label, escape_text(&value)))
+            .collect();
+        parts.push(fields.join("  \n"));
+
+        for (label, text) in prose(character) {
+            parts.push(format!("### {}", label));
+            parts.push(markdown_paragraphs(text));
+        }
+
+        let traits: Vec<&String> = character
+            .traits
+            .iter()
+            .filter(|t| !t.trim().is_empty())
+            .collect();
+        if !traits.is_empty() {
+            parts.push("### Traits".to_string());
+            parts.push(
+                traits
+                    .iter()
+                    .map(|t| format!("- {}", escape_text(t.trim())))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+        }
+
+        let engine = engine(character);
+        if !engine.is_empty() {
+            parts.push("### Engine".to_string());
+            parts.push(
+                engine
+                    .iter()
+                    .map(|(label, value)| {
+                        format!("- **{}:** {}", label, escape_text(&one_line(value)))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+        }
+
+        sections.push(parts.join("\n\n"));
+    }
+
+    let mut out = sections.join("\n\n");
+    out.push('\n');
+    out
+}
+
+fn build_html(metadata: &ProjectMetadata, appearances: &HashMap<Uuid, Appearance>) -> String {
+    let title = bible_title(metadata);
+    let mut body = format!(
+        "<header class=\"title-page\">\n<h1>{}</h1>\n<p>{}</p>\n</header>\n<main>\n",
+        encode_text(&title),
+        encode_text(&metadata.author)
+    );
+
+    for character in by_role(&metadata.characters) {
+        body.push_str("<section class=\"character\">\n");
+        body.push_str(&format!("<h2>{}</h2>\n", encode_text(&character.name)));
+        body.push_str(&definition_list(
+            fields(character, appearances.get(&character.id))
+                .iter()
+                .map(|(label, value)| (*label, value.as_str())),
+        ));
+
+        for (label, text) in prose(character) {
+            body.push_str(&format!("<h3>{}</h3>\n", label));
+            for paragraph in paragraphs(text) {
+                body.push_str(&format!(
+                    "<p>{}</p>\n",
+                    paragraph
+                        .lines()
+                        .map(|line| encode_text(line.trim()).into_owned())
+                        .collect::<Vec<_>>()
+                        .join("<br />")
+                ));
+            }
+        }
+
+        let traits: Vec<&String> = character
+            .traits
+            .iter()
+            .filter(|t| !t.trim().is_empty())
+            .collect();
+        if !traits.is_empty() {
+            body.push_str("<h3>Traits</h3>\n<ul>\n");
+            for t in traits {
+                body.push_str(&format!("<li>{}</li>\n", encode_text(t.trim())));
+            }
+            body.push_str("</ul>\n");
+        }
+
+        let engine = engine(character);
+        if !engine.is_empty() {
+            body.push_str("<h3>Engine</h3>\n");
+            body.push_str(&definition_list(engine.iter().copied()));
+        }
+
+        body.push_str("</section>\n");
+    }
+    body.push_str("</main>\n");
+
+    document(&title, &body)
+}
+
+fn bible_title(metadata: &ProjectMetadata) -> String {
+    format!("{} - Character Bible", metadata.title)
+}
+
+/// Characters grouped by role, keeping project order within a role.
+fn by_role(characters: &[Character]) -> Vec<&Character> {
+    let mut sorted: Vec<&Character> = characters.iter().collect();
+    sorted.sort_by_key(|c| role_rank(&c.role));
+    sorted
+}
+
+fn role_rank(role: &CharacterRole) -> u8 {
+    match role {
+        CharacterRole::Protagonist => 0,
+        CharacterRole::Antagonist => 1,
+        CharacterRole::Secondary => 2,
+        CharacterRole::Extra => 3,
+    }
+}
+
+fn role_label(role: &CharacterRole) -> &'static str {
+    match role {
+        CharacterRole::Protagonist => "Protagonist",
+        CharacterRole::Antagonist => "Antagonist",
+        CharacterRole::Secondary => "Secondary",
+        CharacterRole::Extra => "Extra",
+    }
+}
+
+/// Short one-line fields shown under the character's name.
+fn fields(character: &Character, appearance: Option<&Appearance>) -> Vec<(&'static str, String)> {
+    let mut fields = vec![("Role", role_label(&character.role).to_string())];
+    if !character.archetype.trim().is_empty() {
+        fields.push(("Archetype", one_line(&character.archetype)));
+    }
+    let aliases: Vec<&str> = character
+        .aliases
+        .iter()
+        .map(|a| a.trim())
+        .filter(|a| !a.is_empty())
+        .collect();
+    if !aliases.is_empty() {
+        fields.push(("Aliases", aliases.join(", ")));
+    }
+
+    let appearance = appearance.cloned().unwrap_or_default();
+    fields.push((
+        "First appearance",
+        appearance
+            .first_chapter
+            .unwrap_or_else(|| "Not mentioned yet".to_string()),
+    ));
+    fields.push(("Mentions", appearance.mention_count.to_string()));
+    fields
+}
+
+/// Free-text sections, in the order they appear in the character sheet.
+fn prose(character: &Character) -> Vec<(&'static str, &str)> {
+    [
+        ("Description", character.description.as_str()),
+        ("Physical features", character.physical_features.as_str()),
+        ("Arc", character.arc.as_str()),
+    ]
+    .into_iter()
+    .filter(|(_, text)| !text.trim().is_empty())
+    .collect()
+}
+
+fn engine(character: &Character) -> Vec<(&'static str, &str)> {
+    let engine = &character.engine;
+    [
+        ("Desire", engine.desire.as_str()),
+        ("Fear", engine.fear.as_str()),
+        ("Wound", engine.wound.as_str()),
+        ("Secret", engine.secret.as_str()),
+    ]
+    .into_iter()
+    .filter(|(_, text)| !text.trim().is_empty())
+    .collect()
+}
+
+/// Splits free text on blank lines.
+fn paragraphs(text: &str) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(current.join("\n"));
+                current.clear();
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current.join("\n"));
+    }
+    paragraphs
+}
+
+fn markdown_paragraphs(text: &str) -> String {
+    paragraphs(text)
+        .iter()
+        .map(|p| {
+            p.lines()
+                .map(|line| escape_line(&escape_text(line.trim())))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn definition_list<'a>(items: impl Iterator<Item = (&'a str, &'a str)>) -> String {
+    let mut out = String::from("<dl class=\"fields\">\n");
+    for (label, value) in items {
+        out.push_str(&format!(
+            "<dt>{}</dt>\n<dd>{}</dd>\n",
+            encode_text(label),
+            encode_text(&one_line(value))
+        ));
+    }
+    out.push_str("</dl>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Chapter, CharacterEngine, Manifest};
+    use std::sync::Arc;
+
+    fn chapter(id: &str, title: &str, order: u32) -> Chapter {
+        Chapter {
+            id: id.to_string(),
+            parent_id: None,
+            title: title.to_string(),
+            filename: format!("{}.md", id),
+            word_count: 0,
+            order,
+            chronological_date: None,
+            abstract_timeframe: None,
+            duration: None,
+            plotline_tag: None,
+            depends_on: None,
+            pov_character_id: None,
+            synopsis: None,
+        }
+    }
+
+    fn character(id: u128, name: &str, role: CharacterRole) -> Character {
+        Character {
+            id: Uuid::from_u128(id),
+            name: name.to_string(),
+            role,
+            archetype: String::new(),
+            description: String::new(),
+            engine: CharacterEngine::default(),
+            physical_features: String::new(),
+            traits: vec![],
+            arc: String::new(),
+            notes: String::new(),
+            aliases: vec![],
+        }
+    }
+
+    fn sample() -> ProjectMetadata {
+        let mut metadata = ProjectMetadata::new("Tales".to_string(), "Jane Doe".to_string());
+        // Manifest order differs from reading order
+        metadata.manifest =
+            Manifest::new(vec![chapter("c2", "Second", 2), chapter("c1", "First", 1)]);
+
+        let mut alice = character(1, "Alice", CharacterRole::Protagonist);
+        alice.archetype = "The Seeker".to_string();
+        alice.aliases = vec!["Ally".to_string(), " ".to_string()];
+        alice.description = "Curious.\n\n# Not a heading".to_string();
+        alice.traits = vec!["brave".to_string()];
+        alice.engine = CharacterEngine {
+            desire: "Find the door".to_string(),
+            fear: "Water".to_string(),
+            wound: String::new(),
+            secret: "She *is* the queen".to_string(),
+        };
+
+        metadata.characters = vec![
+            character(3, "Cook", CharacterRole::Extra),
+            alice,
+            character(2, "Bob <Jr>", CharacterRole::Antagonist),
+        ];
+        metadata
+    }
+
+    fn mentions() -> HashMap<String, CachedMentions> {
+        let alice = Uuid::from_u128(1);
+        let bob = Uuid::from_u128(2);
+        HashMap::from([
+            (
+                "c2".to_string(),
+                Arc::new(vec![(0, 0, alice), (10, 2, bob)]),
+            ),
+            ("c1".to_string(), Arc::new(vec![(5, 1, alice)])),
+        ])
+    }
+
+    #[test]
+    fn test_appearances_follow_reading_order() {
+        let found = appearances(&sample(), &mentions());
+
+        let alice = &found[&Uuid::from_u128(1)];
+        assert_eq!(alice.mention_count, 2);
+        assert_eq!(alice.first_chapter.as_deref(), Some("First"));
+
+        let bob = &found[&Uuid::from_u128(2)];
+        assert_eq!(bob.mention_count, 1);
+        assert_eq!(bob.first_chapter.as_deref(), Some("Second"));
+
+        assert!(!found.contains_key(&Uuid::from_u128(3)));
+    }
+
+    #[test]
+    fn test_markdown_bible() {
+        let metadata = sample();
+        let md = build_bible(
+            &metadata,
+            &appearances(&metadata, &mentions()),
+            BibleFormat::Markdown,
+        );
+
+        assert!(md.starts_with("# Tales - Character Bible\n\nJane Doe\n\n## Alice\n\n"));
+        assert!(md.contains(
+            "**Role:** Protagonist  \n**Archetype:** The Seeker  \n**Aliases:** Ally  \n**First appearance:** First  \n**Mentions:** 2"
+        ));
+        assert!(md.contains("### Description\n\nCurious.\n\n\\# Not a heading"));
+        assert!(md.contains("### Traits\n\n- brave"));
+        assert!(md.contains("- **Secret:** She \\*is\\* the queen"));
+        assert!(!md.contains("**Wound:**"));
+
+        // Grouped by role
+        let alice = md.find("## Alice").unwrap();
+        let bob = md.find("## Bob \\<Jr\\>").unwrap();
+        let cook = md.find("## Cook").unwrap();
+        assert!(alice < bob && bob < cook);
+        assert!(md.contains("**First appearance:** Not mentioned yet  \n**Mentions:** 0"));
+    }
+
+    #[test]
+    fn test_html_bible() {
+        let metadata = sample();
+        let html = build_bible(
+            &metadata,
+            &appearances(&metadata, &mentions()),
+            BibleFormat::Html,
+        );
+
+        assert!(html.contains("<title>Tales - Character Bible</title>"));
+        assert!(html.contains("<h2>Bob &lt;Jr&gt;</h2>"));
+        assert!(html.contains("<dt>First appearance</dt>\n<dd>Second</dd>"));
+        assert!(html.contains(
+            "<h3>Engine</h3>\n<dl class=\"fields\">\n<dt>Desire</dt>\n<dd>Find the door</dd>"
+        ));
+        assert!(html.contains("<p># Not a heading</p>"));
+    }
+}
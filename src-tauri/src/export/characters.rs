@@ -0,0 +1,128 @@
+//! Character table writers.
+//!
+//! Writes every `Character` record for editing in a spreadsheet (CSV) or another
+//! tool (JSON), in a shape `import::characters` reads back. In CSV the engine is
+//! flattened into `desire`, `fear`, `wound` and `secret` columns and list fields
+//! are joined with `LIST_SEPARATOR`; JSON keeps the project file's layout.
+
+use super::csv;
+use crate::errors::Result;
+use crate::models::{Character, CharacterRole};
+use serde::{Deserialize, Serialize};
+
+/// Formats supported by `export_characters` and `import_characters`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CharacterFormat {
+    Csv,
+    Json,
+}
+
+/// CSV header, in column order.
+pub const COLUMNS: &[&str] = &[
+    "id",
+    "name",
+    "role",
+    "archetype",
+    "description",
+    "physical_features",
+    "traits",
+    "arc",
+    "notes",
+    "aliases",
+    "desire",
+    "fear",
+    "wound",
+    "secret",
+];
+
+/// Joins list items (traits, aliases) inside one CSV cell.
+pub const LIST_SEPARATOR: &str = "; ";
+
+pub fn build_characters(characters: &[Character], format: CharacterFormat) -> Result<String> {
+    match format {
+        CharacterFormat::Csv => Ok(build_csv(characters)),
+        CharacterFormat::Json => Ok(serde_json::to_string_pretty(characters)?),
+    }
+}
+
+/// Name of a role as written in files, matching its serde name.
+pub fn role_name(role: &CharacterRole) -> &'static str {
+    match role {
+        CharacterRole::Protagonist => "protagonist",
+        CharacterRole::Antagonist => "antagonist",
+        CharacterRole::Secondary => "secondary",
+        CharacterRole::Extra => "extra",
+    }
+}
+
+fn build_csv(characters: &[Character]) -> String {
+    let mut out = csv::record(COLUMNS);
+    for c in characters {
+        out.push_str(&csv::record(&[
+            c.id.to_string(),
+            c.name.clone(),
+            role_name(&c.role).to_string(),
+            c.archetype.clone(),
+            c.description.clone(),
+            c.physical_features.clone(),
+            c.traits.join(LIST_SEPARATOR),
+            c.arc.clone(),
+            c.notes.clone(),
+            c.aliases.join(LIST_SEPARATOR),
+            c.engine.desire.clone(),
+            c.engine.fear.clone(),
+            c.engine.wound.clone(),
+            c.engine.secret.clone(),
+        ]));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CharacterEngine;
+    use uuid::Uuid;
+
+    fn alice() -> Character {
+        Character {
+            id: Uuid::from_u128(1),
+            name: "Alice".to_string(),
+            role: CharacterRole::Protagonist,
+            archetype: "Hero".to_string(),
+            description: "Curious, \"brave\"".to_string(),
+            engine: CharacterEngine {
+                desire: "Home".to_string(),
+                ..Default::default()
+            },
+            physical_features: String::new(),
+            traits: vec!["bold".to_string(), "kind".to_string()],
+            arc: String::new(),
+            notes: String::new(),
+            aliases: vec!["Al".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let csv = build_characters(&[alice()], CharacterFormat::Csv).unwrap();
+        let rows: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(
+            rows[0],
+            "id,name,role,archetype,description,physical_features,traits,arc,notes,aliases,desire,fear,wound,secret"
+        );
+        assert_eq!(
+            rows[1],
+            "00000000-0000-0000-0000-000000000001,Alice,protagonist,Hero,\"Curious, \"\"brave\"\"\",,bold; kind,,,Al,Home,,,"
+        );
+    }
+
+    #[test]
+    fn test_json_export_lists() {
+        let json = build_characters(&[alice()], CharacterFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0]["traits"], serde_json::json!(["bold", "kind"]));
+        assert_eq!(value[0]["role"], "protagonist");
+    }
+}
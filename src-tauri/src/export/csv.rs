@@ -0,0 +1,35 @@
+//! Minimal RFC 4180 CSV writer shared by the tabular exports.
+
+/// Formats one record, quoting fields that contain separators, quotes or line
+/// breaks. Records end with CRLF as the RFC asks; spreadsheets accept both.
+pub fn record<S: AsRef<str>>(fields: &[S]) -> String {
+    let mut out = fields
+        .iter()
+        .map(|f| field(f.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",");
+    out.push_str("\r\n");
+    out
+}
+
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_quoting() {
+        assert_eq!(record(&["a", "b c", ""]), "a,b c,\r\n");
+        assert_eq!(
+            record(&["x,y", "say \"hi\"", "two\nlines"]),
+            "\"x,y\",\"say \"\"hi\"\"\",\"two\nlines\"\r\n"
+        );
+    }
+}
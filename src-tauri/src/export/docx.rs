@@ -0,0 +1,468 @@
+//! DOCX writer producing Standard Manuscript Format.
+//!
+//! Layout: 12pt Courier or Times, double spacing, one-inch margins, half-inch
+//! first-line indents, a title page with contact line and approximate word count,
+//! a `Surname / TITLE / page` running header and centred scene separators. The
+//! package is written by hand (WordprocessingML), so no office suite is needed.
+
+use super::html::{Block, Inline};
+use super::manuscript::Manuscript;
+use super::package::Package;
+use crate::errors::Result;
+use crate::models::{CompilePreset, ManuscriptFont};
+use html_escape::{encode_double_quoted_attribute, encode_text};
+
+const NS_W: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const NS_R: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+/// Letter paper with one-inch margins, in twentieths of a point.
+const PAGE_SETUP: &str = r#"<w:pgSz w:w="12240" w:h="15840"/><w:pgMar w:top="1440" w:right="1440" w:bottom="1440" w:left="1440" w:header="720" w:footer="720" w:gutter="0"/>"#;
+
+fn font_family(font: ManuscriptFont) -> &'static str {
+    match font {
+        ManuscriptFont::Courier => "Courier New",
+        ManuscriptFont::Times => "Times New Roman",
+    }
+}
+
+/// Builds the `.docx` package for a manuscript.
+pub fn build_docx(manuscript: &Manuscript, preset: &CompilePreset) -> Result<Vec<u8>> {
+    let mut package = Package::new();
+
+    package.add_deflated("[Content_Types].xml", CONTENT_TYPES.as_bytes())?;
+    package.add_deflated("_rels/.rels", ROOT_RELS.as_bytes())?;
+    package.add_deflated("docProps/core.xml", build_core(manuscript).as_bytes())?;
+    package.add_deflated("word/_rels/document.xml.rels", DOCUMENT_RELS.as_bytes())?;
+    package.add_deflated(
+        "word/styles.xml",
+        build_styles(preset.manuscript_font).as_bytes(),
+    )?;
+    package.add_deflated("word/header1.xml", build_header(manuscript).as_bytes())?;
+    package.add_deflated(
+        "word/document.xml",
+        build_document(manuscript, preset).as_bytes(),
+    )?;
+
+    package.finish()
+}
+
+/// Rounds a word count the way manuscripts report it: to the nearest hundred for
+/// short works and to the nearest thousand for anything longer.
+pub fn approximate_word_count(words: u32) -> u32 {
+    if words == 0 {
+        return 0;
+    }
+    let step = if words < 10_000 { 100 } else { 1_000 };
+    ((words + step / 2) / step * step).max(step)
+}
+
+pub fn format_thousands(n: u32) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Last word of the author name, used in the running header.
+fn surname(author: &str) -> &str {
+    author.split_whitespace().last().unwrap_or(author)
+}
+
+// =============================================================================
+// Static parts
+// =============================================================================
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+<Default Extension="xml" ContentType="application/xml"/>
+<Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
+<Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>
+<Override PartName="/word/header1.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.header+xml"/>
+<Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/>
+</Types>
+"#;
+
+const ROOT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/>
+</Relationships>
+"#;
+
+const DOCUMENT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
+<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/header" Target="header1.xml"/>
+</Relationships>
+"#;
+
+fn build_core(manuscript: &Manuscript) -> String {
+    let modified = manuscript.updated_at.format("%Y-%m-%dT%H:%M:%SZ");
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+<dc:title>{title}</dc:title>
+<dc:creator>{author}</dc:creator>
+<dcterms:modified xsi:type="dcterms:W3CDTF">{modified}</dcterms:modified>
+</cp:coreProperties>
+"#,
+        title = encode_text(&manuscript.title),
+        author = encode_text(&manuscript.author),
+    )
+}
+
+fn build_styles(font: ManuscriptFont) -> String {
+    let family = encode_double_quoted_attribute(font_family(font));
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="{ns}">
+<w:docDefaults>
+<w:rPrDefault><w:rPr><w:rFonts w:ascii="{family}" w:hAnsi="{family}" w:cs="{family}" w:eastAsia="{family}"/><w:sz w:val="24"/><w:szCs w:val="24"/><w:lang w:val="en-US"/></w:rPr></w:rPrDefault>
+<w:pPrDefault><w:pPr><w:spacing w:before="0" w:after="0" w:line="480" w:lineRule="auto"/></w:pPr></w:pPrDefault>
+</w:docDefaults>
+<w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/><w:qFormat/></w:style>
+<w:style w:type="paragraph" w:styleId="BodyText"><w:name w:val="Body Text"/><w:basedOn w:val="Normal"/><w:qFormat/><w:pPr><w:ind w:firstLine="720"/></w:pPr></w:style>
+<w:style w:type="paragraph" w:styleId="Title"><w:name w:val="Title"/><w:basedOn w:val="Normal"/><w:qFormat/><w:pPr><w:jc w:val="center"/></w:pPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="BodyText"/><w:qFormat/><w:pPr><w:keepNext/><w:pageBreakBefore/><w:spacing w:before="3600" w:after="480"/><w:jc w:val="center"/><w:outlineLvl w:val="0"/></w:pPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/><w:next w:val="BodyText"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="480" w:after="240"/><w:jc w:val="center"/><w:outlineLvl w:val="1"/></w:pPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading3"><w:name w:val="heading 3"/><w:basedOn w:val="Normal"/><w:next w:val="BodyText"/><w:qFormat/><w:pPr><w:keepNext/><w:jc w:val="center"/><w:outlineLvl w:val="2"/></w:pPr><w:rPr><w:i/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="SceneBreak"><w:name w:val="Scene Break"/><w:basedOn w:val="Normal"/><w:next w:val="BodyText"/><w:qFormat/><w:pPr><w:jc w:val="center"/></w:pPr></w:style>
+<w:style w:type="paragraph" w:styleId="Quote"><w:name w:val="Quote"/><w:basedOn w:val="Normal"/><w:qFormat/><w:pPr><w:ind w:left="720" w:right="720"/></w:pPr></w:style>
+<w:style w:type="paragraph" w:styleId="Header"><w:name w:val="header"/><w:basedOn w:val="Normal"/><w:pPr><w:spacing w:line="240" w:lineRule="auto"/><w:jc w:val="right"/></w:pPr></w:style>
+</w:styles>
+"#,
+        ns = NS_W,
+    )
+}
+
+fn build_header(manuscript: &Manuscript) -> String {
+    let slug = format!(
+        "{} / {} / ",
+        surname(&manuscript.author),
+        manuscript.title.to_uppercase()
+    );
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:hdr xmlns:w="{ns}"><w:p><w:pPr><w:pStyle w:val="Header"/></w:pPr>{slug}<w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText xml:space="preserve"> PAGE </w:instrText></w:r><w:r><w:fldChar w:fldCharType="separate"/></w:r><w:r><w:t>1</w:t></w:r><w:r><w:fldChar w:fldCharType="end"/></w:r></w:p></w:hdr>
+"#,
+        ns = NS_W,
+        slug = text_run(&slug, RunStyle::default()),
+    )
+}
+
+// =============================================================================
+// Document body
+// =============================================================================
+
+fn build_document(manuscript: &Manuscript, preset: &CompilePreset) -> String {
+    let mut body = String::new();
+
+    if preset.front_matter {
+        // Title page (own section, no running header)
+        let words = format!(
+            "about {} words",
+            format_thousands(approximate_word_count(manuscript.word_count()))
+        );
+        body.push_str(&format!(
+            r#"<w:p><w:pPr><w:tabs><w:tab w:val="right" w:pos="9360"/></w:tabs><w:spacing w:line="240" w:lineRule="auto"/></w:pPr>{}<w:r><w:tab/></w:r>{}</w:p>"#,
+            text_run(&manuscript.author, RunStyle::default()),
+            text_run(&words, RunStyle::default())
+        ));
+        body.push_str(r#"<w:p><w:pPr><w:spacing w:before="5760"/></w:pPr></w:p>"#);
+        body.push_str(&paragraph(
+            "Title",
+            &text_run(&manuscript.title.to_uppercase(), RunStyle::default()),
+        ));
+        body.push_str(&paragraph(
+            "Title",
+            &text_run(&format!("by {}", manuscript.author), RunStyle::default()),
+        ));
+        body.push_str(&format!(
+            r#"<w:p><w:pPr><w:sectPr>{}</w:sectPr></w:pPr></w:p>"#,
+            PAGE_SETUP
+        ));
+    }
+
+    // Manuscript pages
+    for chapter in &manuscript.chapters {
+        if let Some(level) = chapter.heading_level {
+            body.push_str(&paragraph(
+                heading_style(level),
+                &text_run(&chapter.title, RunStyle::default()),
+            ));
+        }
+        for block in &chapter.blocks {
+            render_block(block, "BodyText", &preset.scene_separator, &mut body);
+        }
+    }
+    if preset.back_matter {
+        body.push_str(&paragraph(
+            "SceneBreak",
+            &text_run("END", RunStyle::default()),
+        ));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="{ns_w}" xmlns:r="{ns_r}"><w:body>{body}<w:sectPr><w:headerReference w:type="default" r:id="rId2"/>{page}<w:pgNumType w:start="1"/></w:sectPr></w:body></w:document>
+"#,
+        ns_w = NS_W,
+        ns_r = NS_R,
+        page = PAGE_SETUP,
+    )
+}
+
+fn heading_style(level: u8) -> &'static str {
+    match level {
+        0 | 1 => "Heading1",
+        2 => "Heading2",
+        _ => "Heading3",
+    }
+}
+
+fn paragraph(style: &str, runs: &str) -> String {
+    format!(
+        r#"<w:p><w:pPr><w:pStyle w:val="{}"/></w:pPr>{}</w:p>"#,
+        style, runs
+    )
+}
+
+/// Renders one block; `body_style` is the paragraph style for running text
+/// (block quotes switch it to `Quote`).
+fn render_block(block: &Block, body_style: &str, scene_separator: &str, out: &mut String) {
+    match block {
+        // Headings inside chapter content never outrank a chapter title
+        Block::Heading { level, content } => {
+            let style = heading_style((*level).max(2));
+            out.push_str(&paragraph(style, &runs(content, RunStyle::default())));
+        }
+        Block::Paragraph(content) => {
+            out.push_str(&paragraph(body_style, &runs(content, RunStyle::default())));
+        }
+        Block::BlockQuote(inner) => {
+            for b in inner {
+                render_block(b, "Quote", scene_separator, out);
+            }
+        }
+        Block::List { ordered, items } => {
+            for (i, item) in items.iter().enumerate() {
+                let marker = if *ordered {
+                    format!("{}.\t", i + 1)
+                } else {
+                    "\u{2022}\t".to_string()
+                };
+                let mut first = true;
+                for b in item {
+                    let prefix = if first {
+                        text_run(&marker, RunStyle::default())
+                    } else {
+                        String::new()
+                    };
+                    first = false;
+                    let content = match b {
+                        Block::Paragraph(c) | Block::Heading { content: c, .. } => {
+                            runs(c, RunStyle::default())
+                        }
+                        other => text_run(&block_text(other), RunStyle::default()),
+                    };
+                    out.push_str(&format!(
+                        r#"<w:p><w:pPr><w:pStyle w:val="Normal"/><w:ind w:left="1080" w:hanging="360"/></w:pPr>{}{}</w:p>"#,
+                        prefix, content
+                    ));
+                }
+            }
+        }
+        Block::CodeBlock(code) => {
+            for line in code.lines() {
+                out.push_str(&paragraph("Normal", &text_run(line, RunStyle::default())));
+            }
+        }
+        Block::SceneBreak => {
+            out.push_str(&paragraph(
+                "SceneBreak",
+                &text_run(scene_separator, RunStyle::default()),
+            ));
+        }
+    }
+}
+
+fn block_text(block: &Block) -> String {
+    match block {
+        Block::Heading { content, .. } | Block::Paragraph(content) => {
+            super::html::plain_text(content)
+        }
+        Block::BlockQuote(inner) => inner.iter().map(block_text).collect::<Vec<_>>().join(" "),
+        Block::List { items, .. } => items
+            .iter()
+            .flatten()
+            .map(block_text)
+            .collect::<Vec<_>>()
+            .join(" "),
+        Block::CodeBlock(code) => code.clone(),
+        Block::SceneBreak => String::new(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct RunStyle {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strike: bool,
+}
+
+fn runs(inlines: &[Inline], style: RunStyle) -> String {
+    let mut out = String::new();
+    for inline in inlines {
+        match inline {
+            Inline::Text(t) | Inline::Code(t) => out.push_str(&text_run(t, style)),
+            Inline::Emphasis(c) => out.push_str(&runs(
+                c,
+                RunStyle {
+                    italic: true,
+                    ..style
+                },
+            )),
+            Inline::Strong(c) => out.push_str(&runs(
+                c,
+                RunStyle {
+                    bold: true,
+                    ..style
+                },
+            )),
+            Inline::Underline(c) => out.push_str(&runs(
+                c,
+                RunStyle {
+                    underline: true,
+                    ..style
+                },
+            )),
+            Inline::Strikethrough(c) => out.push_str(&runs(
+                c,
+                RunStyle {
+                    strike: true,
+                    ..style
+                },
+            )),
+            Inline::Link { content, .. } => out.push_str(&runs(content, style)),
+            Inline::LineBreak => out.push_str("<w:r><w:br/></w:r>"),
+        }
+    }
+    out
+}
+
+fn text_run(text: &str, style: RunStyle) -> String {
+    let mut props = String::new();
+    if style.bold {
+        props.push_str("<w:b/>");
+    }
+    if style.italic {
+        props.push_str("<w:i/>");
+    }
+    if style.strike {
+        props.push_str("<w:strike/>");
+    }
+    if style.underline {
+        props.push_str(r#"<w:u w:val="single"/>"#);
+    }
+    let props = if props.is_empty() {
+        String::new()
+    } else {
+        format!("<w:rPr>{}</w:rPr>", props)
+    };
+
+    // Tabs must be their own element inside a run
+    let mut content = String::new();
+    for (i, part) in text.split('\t').enumerate() {
+        if i > 0 {
+            content.push_str("<w:tab/>");
+        }
+        if !part.is_empty() {
+            content.push_str(&format!(
+                r#"<w:t xml:space="preserve">{}</w:t>"#,
+                encode_text(part)
+            ));
+        }
+    }
+    format!("<w:r>{}{}</w:r>", props, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::html::parse;
+    use crate::export::CompiledChapter;
+    use std::io::Read;
+
+    #[test]
+    fn test_approximate_word_count() {
+        assert_eq!(approximate_word_count(0), 0);
+        assert_eq!(approximate_word_count(4_349), 4_300);
+        assert_eq!(approximate_word_count(4_350), 4_400);
+        assert_eq!(approximate_word_count(89_612), 90_000);
+        assert_eq!(format_thousands(90_000), "90,000");
+        assert_eq!(format_thousands(900), "900");
+    }
+
+    #[test]
+    fn test_docx_manuscript_format() {
+        let manuscript = Manuscript {
+            id: uuid::Uuid::nil(),
+            title: "Night Train".to_string(),
+            author: "Jane Q. Doe".to_string(),
+            updated_at: chrono::Utc::now(),
+            chapters: vec![CompiledChapter {
+                id: "c1".to_string(),
+                title: "One".to_string(),
+                depth: 0,
+                heading_level: Some(1),
+                blocks: parse("<p>First <em>scene</em>.</p><hr><p>Second scene.</p>"),
+                word_count: 12_345,
+            }],
+        };
+
+        let bytes = build_docx(
+            &manuscript,
+            &CompilePreset {
+                manuscript_font: ManuscriptFont::Times,
+                scene_separator: "#".to_string(),
+                ..Default::default()
+            },
+        )
+        .expect("DOCX build failed");
+        let mut archive =
+            zip::ZipArchive::new(std::io::Cursor::new(bytes)).expect("Invalid ZIP output");
+
+        let mut read = |name: &str| {
+            let mut s = String::new();
+            archive
+                .by_name(name)
+                .expect("Missing part")
+                .read_to_string(&mut s)
+                .expect("Unreadable part");
+            s
+        };
+
+        let document = read("word/document.xml");
+        assert!(document.contains("about 12,000 words"));
+        assert!(document.contains(">NIGHT TRAIN<"));
+        assert!(document.contains(r#"<w:rPr><w:i/></w:rPr><w:t xml:space="preserve">scene</w:t>"#));
+        assert!(document.contains(
+            r#"<w:pStyle w:val="SceneBreak"/></w:pPr><w:r><w:t xml:space="preserve">#</w:t>"#
+        ));
+
+        let header = read("word/header1.xml");
+        assert!(header.contains("Doe / NIGHT TRAIN / "));
+        assert!(header.contains(" PAGE "));
+
+        let styles = read("word/styles.xml");
+        assert!(styles.contains("Times New Roman"));
+        assert!(styles.contains(r#"w:line="480""#));
+    }
+}
@@ -0,0 +1,278 @@
+//! EPUB 3 writer.
+//!
+//! Produces one XHTML document per compiled chapter, a navigation document whose
+//! nesting mirrors the manifest tree, and OPF metadata from the project. Title and
+//! closing pages follow the preset's front/back-matter toggles.
+
+use super::html::{render_toc, render_xhtml};
+use super::manuscript::{CompiledChapter, Manuscript};
+use super::package::Package;
+use crate::errors::Result;
+use crate::models::CompilePreset;
+use html_escape::encode_text;
+
+const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+const STYLESHEET: &str = r#"body { font-family: serif; line-height: 1.5; margin: 0 5%; }
+h1, h2, h3, h4, h5, h6 { text-align: center; margin: 2em 0 1em; }
+p { margin: 0; text-indent: 1.5em; text-align: justify; }
+h1 + p, h2 + p, h3 + p, h4 + p, h5 + p, h6 + p, hr + p { text-indent: 0; }
+blockquote { margin: 1em 2em; font-style: italic; }
+p.scene-break { margin: 1em 0; text-indent: 0; text-align: center; }
+p.scene-break + p { text-indent: 0; }
+.title-page, .end-page { text-align: center; margin-top: 30%; }
+"#;
+
+/// Default language tag, projects do not carry one yet.
+const LANGUAGE: &str = "en";
+
+const TITLE_PAGE_HREF: &str = "text/title.xhtml";
+const END_PAGE_HREF: &str = "text/end.xhtml";
+
+/// Builds the EPUB archive for a manuscript.
+pub fn build_epub(manuscript: &Manuscript, preset: &CompilePreset) -> Result<Vec<u8>> {
+    let mut package = Package::new();
+
+    // The mimetype entry must come first and stay uncompressed
+    package.add_stored("mimetype", b"application/epub+zip")?;
+    package.add_deflated("META-INF/container.xml", CONTAINER_XML.as_bytes())?;
+    package.add_deflated("OEBPS/styles.css", STYLESHEET.as_bytes())?;
+    package.add_deflated(
+        "OEBPS/content.opf",
+        build_opf(manuscript, preset).as_bytes(),
+    )?;
+    package.add_deflated("OEBPS/nav.xhtml", build_nav(manuscript).as_bytes())?;
+
+    if preset.front_matter {
+        let body = format!(
+            "<section class=\"title-page\" epub:type=\"titlepage\">\n<h1>{}</h1>\n<p>{}</p>\n</section>\n",
+            encode_text(&manuscript.title),
+            encode_text(&manuscript.author)
+        );
+        package.add_deflated(
+            &format!("OEBPS/{}", TITLE_PAGE_HREF),
+            xhtml_document(&manuscript.title, &body).as_bytes(),
+        )?;
+    }
+
+    for (index, chapter) in manuscript.chapters.iter().enumerate() {
+        package.add_deflated(
+            &format!("OEBPS/{}", chapter_href(index)),
+            build_chapter(chapter, preset).as_bytes(),
+        )?;
+    }
+
+    if preset.back_matter {
+        let body =
+            "<section class=\"end-page\" epub:type=\"backmatter\">\n<p>The End</p>\n</section>\n";
+        package.add_deflated(
+            &format!("OEBPS/{}", END_PAGE_HREF),
+            xhtml_document("The End", body).as_bytes(),
+        )?;
+    }
+
+    package.finish()
+}
+
+fn chapter_href(index: usize) -> String {
+    format!("text/chapter-{:04}.xhtml", index + 1)
+}
+
+fn build_opf(manuscript: &Manuscript, preset: &CompilePreset) -> String {
+    let modified = manuscript.updated_at.format("%Y-%m-%dT%H:%M:%SZ");
+
+    let mut items = String::new();
+    let mut spine = String::new();
+    if preset.front_matter {
+        items.push_str(&format!(
+            "    <item id=\"title-page\" href=\"{}\" media-type=\"application/xhtml+xml\"/>\n",
+            TITLE_PAGE_HREF
+        ));
+        spine.push_str("    <itemref idref=\"title-page\"/>\n");
+    }
+    for index in 0..manuscript.chapters.len() {
+        items.push_str(&format!(
+            "    <item id=\"chapter-{0}\" href=\"{1}\" media-type=\"application/xhtml+xml\"/>\n",
+            index + 1,
+            chapter_href(index)
+        ));
+        spine.push_str(&format!("    <itemref idref=\"chapter-{}\"/>\n", index + 1));
+    }
+    if preset.back_matter {
+        items.push_str(&format!(
+            "    <item id=\"end-page\" href=\"{}\" media-type=\"application/xhtml+xml\"/>\n",
+            END_PAGE_HREF
+        ));
+        spine.push_str("    <itemref idref=\"end-page\"/>\n");
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id" xml:lang="{lang}">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="book-id">urn:uuid:{id}</dc:identifier>
+    <dc:title>{title}</dc:title>
+    <dc:creator>{author}</dc:creator>
+    <dc:language>{lang}</dc:language>
+    <meta property="dcterms:modified">{modified}</meta>
+  </metadata>
+  <manifest>
+    <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+    <item id="css" href="styles.css" media-type="text/css"/>
+{items}  </manifest>
+  <spine>
+{spine}  </spine>
+</package>
+"#,
+        lang = LANGUAGE,
+        id = manuscript.id,
+        title = encode_text(&manuscript.title),
+        author = encode_text(&manuscript.author),
+    )
+}
+
+/// Builds the navigation document. Nested `<ol>` lists follow the depth of each node.
+fn build_nav(manuscript: &Manuscript) -> String {
+    let toc = render_toc(&manuscript.chapters, chapter_href);
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" lang="{lang}" xml:lang="{lang}">
+<head>
+<meta charset="UTF-8"/>
+<title>{title}</title>
+</head>
+<body>
+<nav epub:type="toc" id="toc">
+<h1>{title}</h1>
+{toc}</nav>
+</body>
+</html>
+"#,
+        lang = LANGUAGE,
+        title = encode_text(&manuscript.title),
+    )
+}
+
+fn build_chapter(chapter: &CompiledChapter, preset: &CompilePreset) -> String {
+    let heading = chapter
+        .heading_level
+        .map(|level| format!("<h{0}>{1}</h{0}>\n", level, encode_text(&chapter.title)))
+        .unwrap_or_default();
+    let body = format!(
+        "<section epub:type=\"chapter\">\n{}{}</section>\n",
+        heading,
+        render_xhtml(&chapter.blocks, &preset.scene_separator)
+    );
+    xhtml_document(&chapter.title, &body)
+}
+
+fn xhtml_document(title: &str, body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" lang="{lang}" xml:lang="{lang}">
+<head>
+<meta charset="UTF-8"/>
+<title>{title}</title>
+<link rel="stylesheet" type="text/css" href="../styles.css"/>
+</head>
+<body>
+{body}</body>
+</html>
+"#,
+        lang = LANGUAGE,
+        title = encode_text(title),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::html::parse;
+    use std::io::Read;
+
+    fn compiled(id: &str, depth: usize, html: &str) -> CompiledChapter {
+        CompiledChapter {
+            id: id.to_string(),
+            title: id.to_string(),
+            depth,
+            heading_level: Some(depth as u8 + 1),
+            blocks: parse(html),
+            word_count: 0,
+        }
+    }
+
+    fn sample() -> Manuscript {
+        Manuscript {
+            id: uuid::Uuid::nil(),
+            title: "Tales & Lies".to_string(),
+            author: "Jane Doe".to_string(),
+            updated_at: chrono::Utc::now(),
+            chapters: vec![
+                compiled("Part One", 0, ""),
+                compiled(
+                    "Arrival",
+                    1,
+                    r#"<p><span data-type="character-mention" data-id="1">Alice</span> arrives.</p>"#,
+                ),
+                compiled("Departure", 1, "<p>She leaves.</p>"),
+                compiled("Epilogue", 0, "<p>The end.</p>"),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_epub_structure() {
+        let bytes = build_epub(&sample(), &CompilePreset::default()).expect("EPUB build failed");
+        let mut archive =
+            zip::ZipArchive::new(std::io::Cursor::new(bytes)).expect("Invalid ZIP output");
+
+        {
+            let first = archive.by_index(0).expect("Missing first entry");
+            assert_eq!(first.name(), "mimetype");
+            assert_eq!(first.compression(), zip::CompressionMethod::Stored);
+        }
+
+        let mut opf = String::new();
+        archive
+            .by_name("OEBPS/content.opf")
+            .expect("Missing OPF")
+            .read_to_string(&mut opf)
+            .expect("Unreadable OPF");
+        assert!(opf.contains("<dc:title>Tales &amp; Lies</dc:title>"));
+        assert!(opf.contains("<dc:creator>Jane Doe</dc:creator>"));
+        // Title page plus four chapters
+        assert_eq!(opf.matches("<itemref").count(), 5);
+
+        let mut chapter = String::new();
+        archive
+            .by_name("OEBPS/text/chapter-0002.xhtml")
+            .expect("Missing chapter")
+            .read_to_string(&mut chapter)
+            .expect("Unreadable chapter");
+        assert!(chapter.contains("<h2>Arrival</h2>"));
+        assert!(chapter.contains("<p>Alice arrives.</p>"));
+        assert!(!chapter.contains("character-mention"));
+    }
+
+    #[test]
+    fn test_nav_mirrors_tree_depth() {
+        let nav = build_nav(&sample());
+        assert_eq!(nav.matches("<ol>").count(), 2);
+        assert_eq!(nav.matches("<ol>").count(), nav.matches("</ol>").count());
+        assert_eq!(nav.matches("<li>").count(), nav.matches("</li>").count());
+        // The nested list sits inside the "Part One" item
+        let part = nav.find("Part One").expect("Missing part");
+        let nested = nav.rfind("<ol>").expect("Missing nested list");
+        let epilogue = nav.find("Epilogue").expect("Missing epilogue");
+        assert!(part < nested && nested < epilogue);
+    }
+}
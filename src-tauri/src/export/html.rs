@@ -0,0 +1,858 @@
+//! Shared HTML layer for exporters.
+//!
+//! Chapters are stored as Tiptap HTML. This module parses that markup into a small
+//! block/inline document model that every output format renders from, so no exporter
+//! has to deal with raw markup on its own. Anything outside the supported subset is
+//! either unwrapped (unknown inline tags) or dropped (scripts, styles, media).
+
+use super::manuscript::CompiledChapter;
+use html_escape::{decode_html_entities, encode_double_quoted_attribute, encode_text};
+
+/// A block-level element of a chapter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    Heading {
+        level: u8,
+        content: Vec<Inline>,
+    },
+    Paragraph(Vec<Inline>),
+    BlockQuote(Vec<Block>),
+    List {
+        ordered: bool,
+        items: Vec<Vec<Block>>,
+    },
+    CodeBlock(String),
+    /// Horizontal rule, used by writers as a scene separator.
+    SceneBreak,
+}
+
+/// An inline run inside a block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inline {
+    Text(String),
+    Emphasis(Vec<Inline>),
+    Strong(Vec<Inline>),
+    Strikethrough(Vec<Inline>),
+    Underline(Vec<Inline>),
+    Code(String),
+    Link { href: String, content: Vec<Inline> },
+    LineBreak,
+}
+
+/// Parses chapter HTML into blocks.
+///
+/// Content without any markup (older chapters, imported text) is treated as plain
+/// text where blank lines separate paragraphs.
+pub fn parse(html: &str) -> Vec<Block> {
+    if !html.contains('<') {
+        return parse_plain_text(&decode_html_entities(html));
+    }
+    let nodes = build_tree(html);
+    blocks_from_nodes(&nodes)
+}
+
+fn parse_plain_text(text: &str) -> Vec<Block> {
+    text.replace("\r\n", "\n")
+        .split("\n\n")
+        .filter_map(|para| {
+            let joined = collapse_whitespace(&strip_control_chars(para));
+            let trimmed = joined.trim();
+            (!trimmed.is_empty()).then(|| Block::Paragraph(vec![Inline::Text(trimmed.to_string())]))
+        })
+        .collect()
+}
+
+// =============================================================================
+// Tokenizer & Tree
+// =============================================================================
+
+#[derive(Debug, Clone)]
+enum Node {
+    Element {
+        name: String,
+        attrs: Vec<(String, String)>,
+        children: Vec<Node>,
+    },
+    Text(String),
+}
+
+impl Node {
+    fn attr(&self, key: &str) -> Option<&str> {
+        match self {
+            Node::Element { attrs, .. } => attrs
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str()),
+            Node::Text(_) => None,
+        }
+    }
+
+    fn text_content(&self) -> String {
+        match self {
+            Node::Text(t) => t.clone(),
+            Node::Element { children, .. } => children.iter().map(Node::text_content).collect(),
+        }
+    }
+}
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Elements whose content is never exported.
+const DROPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "head", "title", "template", "iframe", "object", "svg", "noscript",
+    "textarea", "select", "button",
+];
+
+/// Elements that implicitly close an open `<p>`.
+const CLOSES_PARAGRAPH: &[&str] = &[
+    "p",
+    "div",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "ul",
+    "ol",
+    "blockquote",
+    "pre",
+    "hr",
+    "table",
+    "section",
+    "article",
+];
+
+struct OpenElement {
+    name: String,
+    attrs: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+/// Builds a forgiving element tree: unmatched end tags are ignored and unclosed
+/// elements are closed at the end of input.
+fn build_tree(html: &str) -> Vec<Node> {
+    let mut stack: Vec<OpenElement> = vec![OpenElement {
+        name: String::new(),
+        attrs: Vec::new(),
+        children: Vec::new(),
+    }];
+    let bytes = html.as_bytes();
+    let mut pos = 0;
+    let mut text_start = 0;
+
+    fn close_top(stack: &mut Vec<OpenElement>) {
+        if stack.len() > 1 {
+            if let Some(el) = stack.pop() {
+                let node = Node::Element {
+                    name: el.name,
+                    attrs: el.attrs,
+                    children: el.children,
+                };
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(node);
+                }
+            }
+        }
+    }
+
+    fn push_text(stack: &mut [OpenElement], raw: &str) {
+        if raw.is_empty() {
+            return;
+        }
+        let decoded = strip_control_chars(&decode_html_entities(raw));
+        if let Some(top) = stack.last_mut() {
+            top.children.push(Node::Text(decoded));
+        }
+    }
+
+    while pos < bytes.len() {
+        if bytes[pos] != b'<' {
+            pos += 1;
+            continue;
+        }
+
+        let rest = &html[pos..];
+        // Comments, doctype and processing instructions
+        if rest.starts_with("<!--") {
+            push_text(&mut stack, &html[text_start..pos]);
+            let end = rest.find("-->").map(|i| pos + i + 3).unwrap_or(html.len());
+            pos = end;
+            text_start = pos;
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            push_text(&mut stack, &html[text_start..pos]);
+            let end = rest.find('>').map(|i| pos + i + 1).unwrap_or(html.len());
+            pos = end;
+            text_start = pos;
+            continue;
+        }
+
+        let is_end = rest.starts_with("</");
+        let name_start = if is_end { pos + 2 } else { pos + 1 };
+        if !bytes
+            .get(name_start)
+            .is_some_and(|b| b.is_ascii_alphabetic())
+        {
+            // A lone '<' in text
+            pos += 1;
+            continue;
+        }
+
+        let Some(tag_len) = find_tag_end(&html[pos..]) else {
+            break;
+        };
+        push_text(&mut stack, &html[text_start..pos]);
+        let tag_src = &html[name_start..pos + tag_len - 1];
+        pos += tag_len;
+        text_start = pos;
+
+        let (name, attrs, self_closing) = parse_tag(tag_src);
+
+        if is_end {
+            if let Some(depth) = stack.iter().rposition(|el| el.name == name) {
+                if depth > 0 {
+                    while stack.len() > depth {
+                        close_top(&mut stack);
+                    }
+                }
+            }
+            continue;
+        }
+
+        if DROPPED_ELEMENTS.contains(&name.as_str()) && !self_closing {
+            // Skip raw content up to the matching end tag
+            let closing = format!("</{}", name);
+            let lower = html[pos..].to_ascii_lowercase();
+            let end = lower.find(&closing).map(|i| pos + i).unwrap_or(html.len());
+            pos = html[end..]
+                .find('>')
+                .map(|i| end + i + 1)
+                .unwrap_or(html.len());
+            text_start = pos;
+            continue;
+        }
+
+        if CLOSES_PARAGRAPH.contains(&name.as_str()) {
+            if let Some(depth) = stack.iter().rposition(|el| el.name == "p") {
+                while stack.len() > depth {
+                    close_top(&mut stack);
+                }
+            }
+        }
+        if name == "li" {
+            if let Some(top) = stack.iter().rposition(|el| el.name == "li") {
+                let in_nested_list = stack[top..]
+                    .iter()
+                    .any(|el| el.name == "ul" || el.name == "ol");
+                if !in_nested_list {
+                    while stack.len() > top {
+                        close_top(&mut stack);
+                    }
+                }
+            }
+        }
+
+        if self_closing || VOID_ELEMENTS.contains(&name.as_str()) {
+            if let Some(top) = stack.last_mut() {
+                top.children.push(Node::Element {
+                    name,
+                    attrs,
+                    children: Vec::new(),
+                });
+            }
+        } else {
+            stack.push(OpenElement {
+                name,
+                attrs,
+                children: Vec::new(),
+            });
+        }
+    }
+
+    push_text(&mut stack, &html[text_start..]);
+    while stack.len() > 1 {
+        close_top(&mut stack);
+    }
+    stack.pop().map(|root| root.children).unwrap_or_default()
+}
+
+/// Returns the length of the tag starting at `src[0] == '<'`, honouring quoted attributes.
+fn find_tag_end(src: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (i, c) in src.char_indices().skip(1) {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return Some(i + 1),
+            None => {}
+        }
+    }
+    None
+}
+
+fn parse_tag(src: &str) -> (String, Vec<(String, String)>, bool) {
+    let src = src.trim();
+    let self_closing = src.ends_with('/');
+    let src = src.trim_end_matches('/');
+
+    let name_end = src.find(|c: char| c.is_whitespace()).unwrap_or(src.len());
+    let name = src[..name_end].to_ascii_lowercase();
+
+    let mut attrs = Vec::new();
+    let mut chars = src[name_end..].chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        let mut key = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() || c == '=' {
+                break;
+            }
+            key.push(c);
+            chars.next();
+        }
+        if key.is_empty() {
+            break;
+        }
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        let mut value = String::new();
+        if chars.peek() == Some(&'=') {
+            chars.next();
+            while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                chars.next();
+            }
+            match chars.peek().copied() {
+                Some(q @ ('"' | '\'')) => {
+                    chars.next();
+                    for c in chars.by_ref() {
+                        if c == q {
+                            break;
+                        }
+                        value.push(c);
+                    }
+                }
+                _ => {
+                    while let Some(&c) = chars.peek() {
+                        if c.is_whitespace() {
+                            break;
+                        }
+                        value.push(c);
+                        chars.next();
+                    }
+                }
+            }
+        }
+        attrs.push((
+            key.to_ascii_lowercase(),
+            decode_html_entities(&value).into_owned(),
+        ));
+    }
+
+    (name, attrs, self_closing)
+}
+
+// =============================================================================
+// Tree -> Document model
+// =============================================================================
+
+fn blocks_from_nodes(nodes: &[Node]) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut pending: Vec<Inline> = Vec::new();
+
+    fn flush(pending: &mut Vec<Inline>, blocks: &mut Vec<Block>) {
+        let mut inlines = std::mem::take(pending);
+        normalize_inlines(&mut inlines);
+        if !inlines.is_empty() {
+            blocks.push(Block::Paragraph(inlines));
+        }
+    }
+
+    for node in nodes {
+        let Node::Element { name, children, .. } = node else {
+            pending.extend(inlines_from_nodes(std::slice::from_ref(node)));
+            continue;
+        };
+
+        match name.as_str() {
+            "p" | "td" | "th" | "caption" | "dt" | "dd" | "figcaption" => {
+                flush(&mut pending, &mut blocks);
+                let mut content = inlines_from_nodes(children);
+                normalize_inlines(&mut content);
+                if !content.is_empty() {
+                    blocks.push(Block::Paragraph(content));
+                }
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                flush(&mut pending, &mut blocks);
+                let level = name[1..].parse().unwrap_or(1);
+                let mut content = inlines_from_nodes(children);
+                normalize_inlines(&mut content);
+                if !content.is_empty() {
+                    blocks.push(Block::Heading { level, content });
+                }
+            }
+            "blockquote" => {
+                flush(&mut pending, &mut blocks);
+                let inner = blocks_from_nodes(children);
+                if !inner.is_empty() {
+                    blocks.push(Block::BlockQuote(inner));
+                }
+            }
+            "ul" | "ol" => {
+                flush(&mut pending, &mut blocks);
+                let items: Vec<Vec<Block>> = children
+                    .iter()
+                    .filter_map(|child| match child {
+                        Node::Element { name, children, .. } if name == "li" => {
+                            Some(blocks_from_nodes(children))
+                        }
+                        _ => None,
+                    })
+                    .filter(|item| !item.is_empty())
+                    .collect();
+                if !items.is_empty() {
+                    blocks.push(Block::List {
+                        ordered: name == "ol",
+                        items,
+                    });
+                }
+            }
+            "pre" => {
+                flush(&mut pending, &mut blocks);
+                let code = node.text_content();
+                let code = code.trim_matches('\n');
+                if !code.is_empty() {
+                    blocks.push(Block::CodeBlock(code.to_string()));
+                }
+            }
+            "hr" => {
+                flush(&mut pending, &mut blocks);
+                blocks.push(Block::SceneBreak);
+            }
+            "div" | "section" | "article" | "main" | "header" | "footer" | "aside" | "nav"
+            | "body" | "html" | "figure" | "li" | "table" | "thead" | "tbody" | "tfoot" | "tr"
+            | "dl" => {
+                flush(&mut pending, &mut blocks);
+                blocks.extend(blocks_from_nodes(children));
+            }
+            "img" | "video" | "audio" | "picture" | "canvas" | "map" => {}
+            _ => pending.extend(inlines_from_nodes(std::slice::from_ref(node))),
+        }
+    }
+    flush(&mut pending, &mut blocks);
+    blocks
+}
+
+fn inlines_from_nodes(nodes: &[Node]) -> Vec<Inline> {
+    let mut out = Vec::new();
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push(Inline::Text(collapse_whitespace(text))),
+            Node::Element { name, children, .. } => match name.as_str() {
+                "em" | "i" | "cite" | "var" | "dfn" => {
+                    out.push(Inline::Emphasis(inlines_from_nodes(children)))
+                }
+                "strong" | "b" => out.push(Inline::Strong(inlines_from_nodes(children))),
+                "s" | "del" | "strike" => {
+                    out.push(Inline::Strikethrough(inlines_from_nodes(children)))
+                }
+                "u" | "ins" => out.push(Inline::Underline(inlines_from_nodes(children))),
+                "code" | "kbd" | "samp" => out.push(Inline::Code(node.text_content())),
+                "br" => out.push(Inline::LineBreak),
+                "a" => {
+                    let content = inlines_from_nodes(children);
+                    match node.attr("href").filter(|href| is_safe_href(href)) {
+                        Some(href) => out.push(Inline::Link {
+                            href: href.to_string(),
+                            content,
+                        }),
+                        None => out.extend(content),
+                    }
+                }
+                "span" if node.attr("data-type") == Some("character-mention") => {
+                    // Mentions are flattened to the visible name
+                    out.push(Inline::Text(collapse_whitespace(&node.text_content())))
+                }
+                "img" | "video" | "audio" | "picture" | "canvas" | "map" | "wbr" => {}
+                _ => out.extend(inlines_from_nodes(children)),
+            },
+        }
+    }
+    out
+}
+
+fn is_safe_href(href: &str) -> bool {
+    let lower = href.trim().to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://") || lower.starts_with("mailto:")
+}
+
+/// Merges adjacent text runs, drops empty styling wrappers and trims the edges.
+pub fn normalize_inlines(inlines: &mut Vec<Inline>) {
+    merge_text(inlines);
+    trim_edges(inlines);
+    merge_text(inlines);
+}
+
+fn merge_text(inlines: &mut Vec<Inline>) {
+    let mut merged: Vec<Inline> = Vec::with_capacity(inlines.len());
+    for inline in inlines.drain(..) {
+        let inline = match inline {
+            Inline::Emphasis(mut c) => {
+                merge_text(&mut c);
+                Inline::Emphasis(c)
+            }
+            Inline::Strong(mut c) => {
+                merge_text(&mut c);
+                Inline::Strong(c)
+            }
+            Inline::Strikethrough(mut c) => {
+                merge_text(&mut c);
+                Inline::Strikethrough(c)
+            }
+            Inline::Underline(mut c) => {
+                merge_text(&mut c);
+                Inline::Underline(c)
+            }
+            Inline::Link { href, mut content } => {
+                merge_text(&mut content);
+                Inline::Link { href, content }
+            }
+            other => other,
+        };
+        if inline.is_empty() {
+            continue;
+        }
+        match (merged.last_mut(), inline) {
+            (Some(Inline::Text(prev)), Inline::Text(next)) => {
+                if prev.ends_with(' ') && next.starts_with(' ') {
+                    prev.push_str(&next[1..]);
+                } else {
+                    prev.push_str(&next);
+                }
+            }
+            (_, inline) => merged.push(inline),
+        }
+    }
+    *inlines = merged;
+}
+
+fn trim_edges(inlines: &mut [Inline]) {
+    if let Some(first) = inlines.first_mut() {
+        first.trim_start();
+    }
+    if let Some(last) = inlines.last_mut() {
+        last.trim_end();
+    }
+    // Whitespace around hard breaks is not significant
+    for i in 0..inlines.len() {
+        if inlines[i] == Inline::LineBreak {
+            if i > 0 {
+                inlines[i - 1].trim_end();
+            }
+            if i + 1 < inlines.len() {
+                inlines[i + 1].trim_start();
+            }
+        }
+    }
+}
+
+impl Inline {
+    fn is_empty(&self) -> bool {
+        match self {
+            Inline::Text(t) | Inline::Code(t) => t.is_empty(),
+            Inline::Emphasis(c)
+            | Inline::Strong(c)
+            | Inline::Strikethrough(c)
+            | Inline::Underline(c) => c.iter().all(Inline::is_empty),
+            Inline::Link { content, .. } => content.iter().all(Inline::is_empty),
+            Inline::LineBreak => false,
+        }
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Inline>> {
+        match self {
+            Inline::Emphasis(c)
+            | Inline::Strong(c)
+            | Inline::Strikethrough(c)
+            | Inline::Underline(c) => Some(c),
+            Inline::Link { content, .. } => Some(content),
+            _ => None,
+        }
+    }
+
+    fn trim_start(&mut self) {
+        if let Inline::Text(t) = self {
+            *t = t.trim_start().to_string();
+        } else if let Some(first) = self.children_mut().and_then(|c| c.first_mut()) {
+            first.trim_start();
+        }
+    }
+
+    fn trim_end(&mut self) {
+        if let Inline::Text(t) = self {
+            *t = t.trim_end().to_string();
+        } else if let Some(last) = self.children_mut().and_then(|c| c.last_mut()) {
+            last.trim_end();
+        }
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() && c != '\u{a0}' {
+            if !in_space {
+                out.push(' ');
+                in_space = true;
+            }
+        } else {
+            out.push(c);
+            in_space = false;
+        }
+    }
+    out
+}
+
+/// Removes characters that are invalid in XML 1.0 documents.
+fn strip_control_chars(text: &str) -> String {
+    text.chars()
+        .filter(|&c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect()
+}
+
+// =============================================================================
+// Plain text helpers
+// =============================================================================
+
+/// Concatenated visible text of inline runs (hard breaks become spaces).
+pub fn plain_text(inlines: &[Inline]) -> String {
+    let mut out = String::new();
+    for inline in inlines {
+        match inline {
+            Inline::Text(t) | Inline::Code(t) => out.push_str(t),
+            Inline::Emphasis(c)
+            | Inline::Strong(c)
+            | Inline::Strikethrough(c)
+            | Inline::Underline(c) => out.push_str(&plain_text(c)),
+            Inline::Link { content, .. } => out.push_str(&plain_text(content)),
+            Inline::LineBreak => out.push(' '),
+        }
+    }
+    out
+}
+
+// =============================================================================
+// (X)HTML serialization
+// =============================================================================
+
+/// Renders blocks as well-formed XHTML (also valid HTML5), one block per line.
+/// Scene breaks become a centred paragraph holding `scene_separator`.
+pub fn render_xhtml(blocks: &[Block], scene_separator: &str) -> String {
+    let scene_break = format!(
+        "<p class=\"scene-break\">{}</p>\n",
+        encode_text(scene_separator)
+    );
+    let mut out = String::new();
+    for block in blocks {
+        render_block(block, &scene_break, &mut out);
+    }
+    out
+}
+
+/// Renders blocks as editor HTML, the form chapters are stored in. Scene breaks
+/// become horizontal rules; everything else matches `render_xhtml`.
+pub fn render_editor_html(blocks: &[Block]) -> String {
+    let mut out = String::new();
+    for block in blocks {
+        render_block(block, "<hr>\n", &mut out);
+    }
+    out
+}
+
+/// Renders a table of contents as nested `<ol>` lists following each chapter's
+/// depth. `href` maps a chapter's index to its link target.
+pub fn render_toc(chapters: &[CompiledChapter], href: impl Fn(usize) -> String) -> String {
+    let mut toc = String::new();
+    let mut current_depth: Option<usize> = None;
+
+    for (index, chapter) in chapters.iter().enumerate() {
+        let link = format!(
+            "<a href=\"{}\">{}</a>",
+            encode_double_quoted_attribute(&href(index)),
+            encode_text(&chapter.title)
+        );
+
+        match current_depth {
+            None => {
+                toc.push_str("<ol>\n<li>");
+                current_depth = Some(0);
+            }
+            Some(prev) if chapter.depth > prev => {
+                toc.push_str("\n<ol>\n<li>");
+                current_depth = Some(prev + 1);
+            }
+            Some(prev) => {
+                let target = chapter.depth.min(prev);
+                toc.push_str("</li>\n");
+                for _ in target..prev {
+                    toc.push_str("</ol>\n</li>\n");
+                }
+                toc.push_str("<li>");
+                current_depth = Some(target);
+            }
+        }
+        toc.push_str(&link);
+    }
+
+    if let Some(depth) = current_depth {
+        toc.push_str("</li>\n");
+        for _ in 0..depth {
+            toc.push_str("</ol>\n</li>\n");
+        }
+        toc.push_str("</ol>\n");
+    }
+    toc
+}
+
+/// `scene_break` is the complete markup written for `Block::SceneBreak`.
+fn render_block(block: &Block, scene_break: &str, out: &mut String) {
+    match block {
+        Block::Heading { level, content } => {
+            out.push_str(&format!("<h{}>", level));
+            render_inlines(content, out);
+            out.push_str(&format!("</h{}>\n", level));
+        }
+        Block::Paragraph(content) => {
+            out.push_str("<p>");
+            render_inlines(content, out);
+            out.push_str("</p>\n");
+        }
+        Block::BlockQuote(inner) => {
+            out.push_str("<blockquote>\n");
+            for b in inner {
+                render_block(b, scene_break, out);
+            }
+            out.push_str("</blockquote>\n");
+        }
+        Block::List { ordered, items } => {
+            let tag = if *ordered { "ol" } else { "ul" };
+            out.push_str(&format!("<{}>\n", tag));
+            for item in items {
+                out.push_str("<li>");
+                // Single-paragraph items are rendered tight
+                if let [Block::Paragraph(content)] = item.as_slice() {
+                    render_inlines(content, out);
+                } else {
+                    out.push('\n');
+                    for b in item {
+                        render_block(b, scene_break, out);
+                    }
+                }
+                out.push_str("</li>\n");
+            }
+            out.push_str(&format!("</{}>\n", tag));
+        }
+        Block::CodeBlock(code) => {
+            out.push_str("<pre><code>");
+            out.push_str(&encode_text(code));
+            out.push_str("</code></pre>\n");
+        }
+        Block::SceneBreak => out.push_str(scene_break),
+    }
+}
+
+fn render_inlines(inlines: &[Inline], out: &mut String) {
+    for inline in inlines {
+        match inline {
+            Inline::Text(t) => out.push_str(&encode_text(t)),
+            Inline::Emphasis(c) => wrap("em", c, out),
+            Inline::Strong(c) => wrap("strong", c, out),
+            Inline::Strikethrough(c) => wrap("s", c, out),
+            Inline::Underline(c) => wrap("u", c, out),
+            Inline::Code(t) => {
+                out.push_str("<code>");
+                out.push_str(&encode_text(t));
+                out.push_str("</code>");
+            }
+            Inline::Link { href, content } => {
+                out.push_str(&format!(
+                    "<a href=\"{}\">",
+                    encode_double_quoted_attribute(href)
+                ));
+                render_inlines(content, out);
+                out.push_str("</a>");
+            }
+            Inline::LineBreak => out.push_str("<br/>"),
+        }
+    }
+}
+
+fn wrap(tag: &str, content: &[Inline], out: &mut String) {
+    out.push_str(&format!("<{}>", tag));
+    render_inlines(content, out);
+    out.push_str(&format!("</{}>", tag));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tiptap_markup() {
+        let html = r#"<p>Hello <strong>bold</strong> and <em>soft</em></p><hr><blockquote><p>Quoted</p></blockquote><ul><li><p>One</p></li><li><p>Two</p></li></ul>"#;
+        let blocks = parse(html);
+
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(
+            blocks[0],
+            Block::Paragraph(vec![
+                Inline::Text("Hello ".into()),
+                Inline::Strong(vec![Inline::Text("bold".into())]),
+                Inline::Text(" and ".into()),
+                Inline::Emphasis(vec![Inline::Text("soft".into())]),
+            ])
+        );
+        assert_eq!(blocks[1], Block::SceneBreak);
+        assert!(matches!(&blocks[2], Block::BlockQuote(inner) if inner.len() == 1));
+        assert!(matches!(&blocks[3], Block::List { ordered: false, items } if items.len() == 2));
+    }
+
+    #[test]
+    fn test_mentions_are_flattened() {
+        let html = r#"<p>Then <span data-id="123" data-type="character-mention" class="mention">Alice</span> left.</p>"#;
+        let blocks = parse(html);
+        assert_eq!(
+            blocks,
+            vec![Block::Paragraph(vec![Inline::Text(
+                "Then Alice left.".into()
+            )])]
+        );
+    }
+
+    #[test]
+    fn test_unsafe_markup_is_dropped() {
+        let html = r#"<p onclick="x()">Safe<script>alert("no")</script> <a href="javascript:evil()">link</a></p><style>p{}</style>"#;
+        let xhtml = render_xhtml(&parse(html), "#");
+        assert_eq!(xhtml, "<p>Safe link</p>\n");
+    }
+
+    #[test]
+    fn test_plain_text_fallback() {
+        let blocks = parse("First line\ncontinues.\n\nSecond &amp; last.");
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(
+            blocks[1],
+            Block::Paragraph(vec![Inline::Text("Second & last.".into())])
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! Standalone HTML book writer.
+//!
+//! Produces either one self-contained `book.html` or a small static site
+//! (`index.html` plus one page per chapter). Both carry a table of contents that
+//! mirrors the manifest tree, previous/next links and an embedded stylesheet, so
+//! a draft can be opened in any browser without extra files. Chapter bodies are
+//! rendered from the shared block model, never from the stored markup, which keeps
+//! scripts, styles and event handlers from imported chapters out of the output.
+
+use super::html::{render_toc, render_xhtml};
+use super::manuscript::{CompiledChapter, Manuscript};
+use crate::models::CompilePreset;
+use html_escape::{encode_double_quoted_attribute, encode_text};
+
+const STYLESHEET: &str = r#"body { font-family: Georgia, 'Times New Roman', serif; line-height: 1.6; max-width: 38em; margin: 0 auto; padding: 2em 1.5em; color: #222; background: #fdfcf8; }
+h1, h2, h3, h4, h5, h6 { text-align: center; margin: 2em 0 1em; line-height: 1.25; }
+p { margin: 0; text-indent: 1.5em; }
+h1 + p, h2 + p, h3 + p, h4 + p, h5 + p, h6 + p, hr + p { text-indent: 0; }
+blockquote { margin: 1em 2em; font-style: italic; }
+pre { white-space: pre-wrap; font-size: 0.9em; }
+p.scene-break { margin: 1em 0; text-indent: 0; text-align: center; }
+p.scene-break + p { text-indent: 0; }
+a { color: #5a3e8a; }
+.title-page, .end-page { text-align: center; margin: 4em 0; }
+.title-page p { text-indent: 0; font-size: 1.2em; }
+nav.toc ol { list-style: none; padding-left: 1.25em; }
+nav.toc > ol { padding-left: 0; }
+nav.toc li { margin: 0.25em 0; }
+nav.pager { display: flex; justify-content: space-between; gap: 1em; margin: 3em 0 1em; padding-top: 1em; border-top: 1px solid #ddd; font-family: sans-serif; font-size: 0.9em; }
+nav.pager span { flex: 1; }
+nav.pager .pager-contents { text-align: center; }
+nav.pager .pager-next { text-align: right; }
+dl.fields { display: grid; grid-template-columns: max-content 1fr; gap: 0.25em 1em; margin: 1em 0; }
+dl.fields dt { font-weight: bold; }
+dl.fields dd { margin: 0; }
+@media (prefers-color-scheme: dark) {
+  body { color: #ddd; background: #1b1b1d; }
+  a { color: #b9a3e3; }
+  nav.pager { border-color: #444; }
+}
+"#;
+
+/// Default language tag, projects do not carry one yet.
+const LANGUAGE: &str = "en";
+
+/// Renders the whole manuscript as a single `book.html`. Chapters are anchored
+/// sections and navigation links point at those anchors.
+pub fn build_single_page(manuscript: &Manuscript, preset: &CompilePreset) -> String {
+    let mut body = String::new();
+
+    if preset.front_matter {
+        body.push_str(&title_page(manuscript));
+    }
+    body.push_str(&contents(manuscript, |i| format!("#{}", anchor(i))));
+
+    body.push_str("<main>\n");
+    for (index, chapter) in manuscript.chapters.iter().enumerate() {
+        body.push_str(&format!(
+            "<section class=\"chapter\" id=\"{}\">\n",
+            anchor(index)
+        ));
+        body.push_str(&chapter_body(chapter, preset));
+        body.push_str(&pager(manuscript, index, "#contents", |i| {
+            format!("#{}", anchor(i))
+        }));
+        body.push_str("</section>\n");
+    }
+    body.push_str("</main>\n");
+
+    if preset.back_matter {
+        body.push_str(END_PAGE);
+    }
+
+    document(&manuscript.title, &body)
+}
+
+/// Renders the manuscript as `(file name, content)` pages: `index.html` (title
+/// page and contents) first, then one page per chapter.
+pub fn build_site(manuscript: &Manuscript, preset: &CompilePreset) -> Vec<(String, String)> {
+    let mut index = String::new();
+    if preset.front_matter {
+        index.push_str(&title_page(manuscript));
+    }
+    index.push_str(&contents(manuscript, page_name));
+
+    let mut files = vec![(
+        "index.html".to_string(),
+        document(&manuscript.title, &index),
+    )];
+
+    let last = manuscript.chapters.len().saturating_sub(1);
+    for (i, chapter) in manuscript.chapters.iter().enumerate() {
+        let mut body = format!(
+            "<main>\n<section class=\"chapter\">\n{}",
+            chapter_body(chapter, preset)
+        );
+        body.push_str("</section>\n</main>\n");
+        if preset.back_matter && i == last {
+            body.push_str(END_PAGE);
+        }
+        body.push_str(&pager(manuscript, i, "index.html", page_name));
+
+        let title = format!("{} - {}", chapter.title, manuscript.title);
+        files.push((page_name(i), document(&title, &body)));
+    }
+
+    files
+}
+
+const END_PAGE: &str = "<section class=\"end-page\">\n<p>The End</p>\n</section>\n";
+
+fn anchor(index: usize) -> String {
+    format!("chapter-{:04}", index + 1)
+}
+
+fn page_name(index: usize) -> String {
+    format!("{}.html", anchor(index))
+}
+
+fn title_page(manuscript: &Manuscript) -> String {
+    format!(
+        "<header class=\"title-page\">\n<h1>{}</h1>\n<p>{}</p>\n</header>\n",
+        encode_text(&manuscript.title),
+        encode_text(&manuscript.author)
+    )
+}
+
+fn contents(manuscript: &Manuscript, href: impl Fn(usize) -> String) -> String {
+    format!(
+        "<nav class=\"toc\" id=\"contents\">\n<h2>Contents</h2>\n{}</nav>\n",
+        render_toc(&manuscript.chapters, href)
+    )
+}
+
+fn chapter_body(chapter: &CompiledChapter, preset: &CompilePreset) -> String {
+    let heading = chapter
+        .heading_level
+        .map(|level| format!("<h{0}>{1}</h{0}>\n", level, encode_text(&chapter.title)))
+        .unwrap_or_default();
+    format!(
+        "{}{}",
+        heading,
+        render_xhtml(&chapter.blocks, &preset.scene_separator)
+    )
+}
+
+/// Previous / contents / next links for the chapter at `index`. Missing
+/// neighbours leave an empty slot so the contents link stays centred.
+fn pager(
+    manuscript: &Manuscript,
+    index: usize,
+    contents_href: &str,
+    href: impl Fn(usize) -> String,
+) -> String {
+    let link = |i: usize, label: &str| {
+        format!(
+            "<a href=\"{}\">{}</a>",
+            encode_double_quoted_attribute(&href(i)),
+            label
+        )
+    };
+
+    let prev = index
+        .checked_sub(1)
+        .map(|i| {
+            link(
+                i,
+                &format!("&larr; {}", encode_text(&manuscript.chapters[i].title)),
+            )
+        })
+        .unwrap_or_default();
+    let next = manuscript
+        .chapters
+        .get(index + 1)
+        .map(|chapter| {
+            link(
+                index + 1,
+                &format!("{} &rarr;", encode_text(&chapter.title)),
+            )
+        })
+        .unwrap_or_default();
+
+    format!(
+        "<nav class=\"pager\">\n<span class=\"pager-prev\">{}</span>\n<span class=\"pager-contents\"><a href=\"{}\">Contents</a></span>\n<span class=\"pager-next\">{}</span>\n</nav>\n",
+        prev,
+        encode_double_quoted_attribute(contents_href),
+        next
+    )
+}
+
+/// Wraps `body` in a complete HTML document with the embedded stylesheet.
+pub fn document(title: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="generator" content="BrutWrite">
+<title>{title}</title>
+<style>
+{css}</style>
+</head>
+<body>
+{body}</body>
+</html>
+"#,
+        lang = LANGUAGE,
+        title = encode_text(title),
+        css = STYLESHEET,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::html::parse;
+
+    fn compiled(title: &str, depth: usize, html: &str) -> CompiledChapter {
+        CompiledChapter {
+            id: title.to_string(),
+            title: title.to_string(),
+            depth,
+            heading_level: Some(depth as u8 + 1),
+            blocks: parse(html),
+            word_count: 0,
+        }
+    }
+
+    fn sample() -> Manuscript {
+        Manuscript {
+            id: uuid::Uuid::nil(),
+            title: "Tales & Lies".to_string(),
+            author: "Jane Doe".to_string(),
+            updated_at: chrono::Utc::now(),
+            chapters: vec![
+                compiled("Part One", 0, ""),
+                compiled(
+                    "Arrival",
+                    1,
+                    r#"<p onclick="steal()">Hello<script>alert(1)</script> <img src="x" onerror="boom()">there.</p><hr><p>Later.</p>"#,
+                ),
+                compiled("Night", 1, "<p>Dark.</p>"),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_single_page_book() {
+        let html = build_single_page(&sample(), &CompilePreset::default());
+
+        assert!(html.contains("<title>Tales &amp; Lies</title>"));
+        assert!(html.contains("<style>\nbody {"));
+        assert!(html.contains("<a href=\"#chapter-0002\">Arrival</a>"));
+        assert!(html.contains("<section class=\"chapter\" id=\"chapter-0003\">"));
+        assert!(html.contains("<a href=\"#chapter-0003\">Night &rarr;</a>"));
+        assert!(html.contains("<p class=\"scene-break\">* * *</p>"));
+
+        // Nothing from the stored markup survives except the supported subset
+        assert!(html.contains("<p>Hello there.</p>"));
+        for unsafe_markup in ["<script", "onclick", "onerror", "<img"] {
+            assert!(!html.contains(unsafe_markup), "leaked {}", unsafe_markup);
+        }
+    }
+
+    #[test]
+    fn test_site_pages_link_in_order() {
+        let preset = CompilePreset {
+            back_matter: true,
+            ..CompilePreset::default()
+        };
+        let files = build_site(&sample(), &preset);
+        let names: Vec<&str> = files.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "index.html",
+                "chapter-0001.html",
+                "chapter-0002.html",
+                "chapter-0003.html"
+            ]
+        );
+
+        let index = &files[0].1;
+        assert!(index.contains("<h1>Tales &amp; Lies</h1>"));
+        assert_eq!(index.matches("<ol>").count(), 2);
+
+        let arrival = &files[2].1;
+        assert!(arrival.contains("<title>Arrival - Tales &amp; Lies</title>"));
+        assert!(arrival.contains("<a href=\"chapter-0001.html\">&larr; Part One</a>"));
+        assert!(arrival.contains("<a href=\"chapter-0003.html\">Night &rarr;</a>"));
+        assert!(arrival.contains("<a href=\"index.html\">Contents</a>"));
+
+        let night = &files[3].1;
+        assert!(night.contains("<span class=\"pager-next\"></span>"));
+        assert!(night.contains("<p>The End</p>"));
+        assert!(!arrival.contains("The End"));
+    }
+}
@@ -0,0 +1,297 @@
+//! LaTeX writer.
+//!
+//! Produces a `main.tex` driver (preamble, title page, table of contents) that
+//! `\input`s one file per compiled chapter from `chapters/`. Heading levels map
+//! onto `\part` / `\chapter` / `\section` and below; when the book has fewer than
+//! three titled levels the mapping starts at `\chapter`, so a flat manifest does
+//! not turn every chapter into a part. Titles already carry the preset's
+//! numbering, so starred commands are used and added to the TOC by hand.
+
+use super::html::{Block, Inline};
+use super::manuscript::{CompiledChapter, Manuscript};
+use crate::models::CompilePreset;
+
+const SECTIONING: [&str; 7] = [
+    "part",
+    "chapter",
+    "section",
+    "subsection",
+    "subsubsection",
+    "paragraph",
+    "subparagraph",
+];
+
+/// Builds the LaTeX sources as `(relative path, content)` pairs, `main.tex` first.
+pub fn build_latex(manuscript: &Manuscript, preset: &CompilePreset) -> Vec<(String, String)> {
+    let max_level = manuscript
+        .chapters
+        .iter()
+        .filter_map(|c| c.heading_level)
+        .max()
+        .unwrap_or(1);
+    let offset = if max_level >= 3 { 0 } else { 1 };
+
+    let mut chapters = Vec::with_capacity(manuscript.chapters.len());
+    let mut inputs = String::new();
+    for (index, chapter) in manuscript.chapters.iter().enumerate() {
+        let name = format!("chapters/chapter-{:04}", index + 1);
+        inputs.push_str(&format!("\\input{{{}}}\n", name));
+        chapters.push((format!("{}.tex", name), build_chapter(chapter, offset)));
+    }
+
+    let mut files = vec![(
+        "main.tex".to_string(),
+        build_main(manuscript, preset, &inputs),
+    )];
+    files.extend(chapters);
+    files
+}
+
+fn build_main(manuscript: &Manuscript, preset: &CompilePreset, inputs: &str) -> String {
+    let front = if preset.front_matter {
+        "\\frontmatter\n\\maketitle\n\\tableofcontents\n"
+    } else {
+        ""
+    };
+    let back = if preset.back_matter {
+        "\n\\backmatter\n\\scenebreak\n\\begin{center}The End\\end{center}\n"
+    } else {
+        ""
+    };
+
+    format!(
+        r#"% Generated by BrutWrite
+\documentclass[11pt,openany]{{book}}
+\usepackage[T1]{{fontenc}}
+\usepackage[utf8]{{inputenc}}
+\usepackage{{lmodern}}
+\usepackage[normalem]{{ulem}}
+\usepackage{{hyperref}}
+
+\newcommand{{\scenebreak}}{{\par\medskip\begin{{center}}{separator}\end{{center}}\medskip\par\noindent}}
+
+\title{{{title}}}
+\author{{{author}}}
+\date{{}}
+
+\begin{{document}}
+{front}
+\mainmatter
+{inputs}{back}
+\end{{document}}
+"#,
+        separator = escape(&preset.scene_separator),
+        title = escape(&manuscript.title),
+        author = escape(&manuscript.author),
+    )
+}
+
+fn build_chapter(chapter: &CompiledChapter, offset: usize) -> String {
+    let mut out = format!("% {}\n", chapter.title.replace('\n', " "));
+    let base = chapter.heading_level.unwrap_or(1);
+    if let Some(level) = chapter.heading_level {
+        out.push_str(&sectioning(level, offset, &escape(&chapter.title)));
+    }
+    for block in &chapter.blocks {
+        out.push('\n');
+        render_block(block, base, offset, &mut out);
+    }
+    out
+}
+
+fn sectioning(level: u8, offset: usize, title: &str) -> String {
+    let index = (level.max(1) as usize - 1 + offset).min(SECTIONING.len() - 1);
+    let command = SECTIONING[index];
+    format!(
+        "\\{0}*{{{1}}}\n\\addcontentsline{{toc}}{{{0}}}{{{1}}}\n",
+        command, title
+    )
+}
+
+fn render_block(block: &Block, base: u8, offset: usize, out: &mut String) {
+    match block {
+        // Headings inside chapter content stay below the chapter heading
+        Block::Heading { level, content } => {
+            let level = (*level).max(base + 1);
+            out.push_str(&sectioning(level, offset, &render_inlines(content)));
+        }
+        Block::Paragraph(content) => {
+            out.push_str(&render_inlines(content));
+            out.push('\n');
+        }
+        Block::BlockQuote(inner) => {
+            out.push_str("\\begin{quote}\n");
+            for (i, b) in inner.iter().enumerate() {
+                if i > 0 {
+                    out.push('\n');
+                }
+                render_block(b, base, offset, out);
+            }
+            out.push_str("\\end{quote}\n");
+        }
+        Block::List { ordered, items } => {
+            let env = if *ordered { "enumerate" } else { "itemize" };
+            out.push_str(&format!("\\begin{{{}}}\n", env));
+            for item in items {
+                out.push_str("\\item ");
+                for (i, b) in item.iter().enumerate() {
+                    if i > 0 {
+                        out.push('\n');
+                    }
+                    render_block(b, base, offset, out);
+                }
+            }
+            out.push_str(&format!("\\end{{{}}}\n", env));
+        }
+        Block::CodeBlock(code) => {
+            out.push_str("\\begin{verbatim}\n");
+            out.push_str(&code.replace("\\end{verbatim}", "\\end {verbatim}"));
+            if !code.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str("\\end{verbatim}\n");
+        }
+        Block::SceneBreak => out.push_str("\\scenebreak\n"),
+    }
+}
+
+fn render_inlines(inlines: &[Inline]) -> String {
+    let mut out = String::new();
+    for inline in inlines {
+        match inline {
+            Inline::Text(t) => {
+                // `\\` looks ahead for an optional argument
+                if out.ends_with("\\\\\n") && t.starts_with('[') {
+                    out.push_str("{}");
+                }
+                out.push_str(&escape(t));
+            }
+            Inline::Emphasis(c) => wrap(&mut out, "emph", c),
+            Inline::Strong(c) => wrap(&mut out, "textbf", c),
+            Inline::Underline(c) => wrap(&mut out, "uline", c),
+            Inline::Strikethrough(c) => wrap(&mut out, "sout", c),
+            Inline::Code(t) => out.push_str(&format!("\\texttt{{{}}}", escape(t))),
+            Inline::Link { href, content } => out.push_str(&format!(
+                "\\href{{{}}}{{{}}}",
+                escape_url(href),
+                render_inlines(content)
+            )),
+            // A break with nothing before it is an error in LaTeX
+            Inline::LineBreak if !out.trim().is_empty() => out.push_str("\\\\\n"),
+            Inline::LineBreak => {}
+        }
+    }
+    out
+}
+
+fn wrap(out: &mut String, command: &str, content: &[Inline]) {
+    out.push_str(&format!("\\{}{{{}}}", command, render_inlines(content)));
+}
+
+/// Escapes LaTeX special characters in running text.
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '{' | '}' | '$' | '&' | '#' | '%' | '_' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '^' => out.push_str("\\textasciicircum{}"),
+            '~' => out.push_str("\\textasciitilde{}"),
+            '\n' => out.push(' '),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// `\href` takes its URL verbatim except for these.
+fn escape_url(url: &str) -> String {
+    let mut out = String::with_capacity(url.len());
+    for c in url.chars() {
+        if matches!(c, '\\' | '{' | '}' | '#' | '%') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::html::parse;
+
+    fn compiled(title: &str, level: u8, html: &str) -> CompiledChapter {
+        CompiledChapter {
+            id: title.to_string(),
+            title: title.to_string(),
+            depth: level as usize - 1,
+            heading_level: Some(level),
+            blocks: parse(html),
+            word_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_escape_specials() {
+        assert_eq!(
+            escape(r"50% of $5 & #1_a {b} ~x^2 \o/"),
+            r"50\% of \$5 \& \#1\_a \{b\} \textasciitilde{}x\textasciicircum{}2 \textbackslash{}o/"
+        );
+    }
+
+    #[test]
+    fn test_depth_maps_to_sectioning() {
+        let manuscript = Manuscript {
+            id: uuid::Uuid::nil(),
+            title: "Tales & Lies".to_string(),
+            author: "Jane Doe".to_string(),
+            updated_at: chrono::Utc::now(),
+            chapters: vec![
+                compiled("Part One", 1, ""),
+                compiled(
+                    "Arrival",
+                    2,
+                    "<p>She <em>ran</em>.</p><hr><blockquote><p>Run!</p></blockquote>",
+                ),
+                compiled("Night", 3, "<p>Dark.</p>"),
+            ],
+        };
+
+        let files = build_latex(&manuscript, &CompilePreset::default());
+        let names: Vec<&str> = files.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "main.tex",
+                "chapters/chapter-0001.tex",
+                "chapters/chapter-0002.tex",
+                "chapters/chapter-0003.tex"
+            ]
+        );
+
+        let main = &files[0].1;
+        assert!(main.contains("\\title{Tales \\& Lies}"));
+        assert!(main.contains("\\input{chapters/chapter-0002}"));
+
+        assert!(files[1].1.contains("\\part*{Part One}"));
+        let arrival = &files[2].1;
+        assert!(arrival.contains("\\chapter*{Arrival}"));
+        assert!(arrival.contains("She \\emph{ran}."));
+        assert!(arrival.contains("\\scenebreak\n"));
+        assert!(arrival.contains("\\begin{quote}\nRun!\n\\end{quote}"));
+        assert!(files[3].1.contains("\\section*{Night}"));
+
+        // Without parts, the top level becomes \chapter
+        let flat = Manuscript {
+            chapters: vec![compiled("Only", 1, "")],
+            ..manuscript
+        };
+        assert!(build_latex(&flat, &CompilePreset::default())[1]
+            .1
+            .contains("\\chapter*{Only}"));
+    }
+}
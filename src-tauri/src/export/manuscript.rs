@@ -0,0 +1,280 @@
+use super::html::{self, Block};
+use crate::errors::Result;
+use crate::models::{Chapter, CompilePreset, Manifest, ProjectMetadata};
+use crate::storage::{self, FileRepository};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use uuid::Uuid;
+
+/// A manifest node together with its depth in the tree (roots are depth 0).
+#[derive(Debug, Clone, Copy)]
+pub struct OutlineEntry<'a> {
+    pub chapter: &'a Chapter,
+    pub depth: usize,
+}
+
+/// Flattens the manifest tree into reading order: depth-first, siblings sorted by
+/// `order`. Nodes whose parent is missing are skipped, matching the sidebar.
+pub fn reading_order(manifest: &Manifest) -> Vec<OutlineEntry<'_>> {
+    let mut children: HashMap<Option<&str>, Vec<&Chapter>> = HashMap::new();
+    for chapter in &manifest.chapters {
+        children
+            .entry(chapter.parent_id.as_deref())
+            .or_default()
+            .push(chapter);
+    }
+    // Stable sort keeps manifest order for equal `order` values
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(|c| c.order);
+    }
+
+    let mut entries = Vec::with_capacity(manifest.chapters.len());
+    let mut stack: Vec<(&Chapter, usize)> = children
+        .get(&None)
+        .map(|roots| roots.iter().rev().map(|c| (*c, 0)).collect())
+        .unwrap_or_default();
+
+    while let Some((chapter, depth)) = stack.pop() {
+        entries.push(OutlineEntry { chapter, depth });
+        if let Some(kids) = children.get(&Some(chapter.id.as_str())) {
+            stack.extend(kids.iter().rev().map(|c| (*c, depth + 1)));
+        }
+    }
+
+    entries
+}
+
+/// Applies the preset's include/exclude lists to the reading order. Depths are
+/// re-based so that each included subtree root sits at depth 0.
+pub fn select_entries<'a>(manifest: &'a Manifest, preset: &CompilePreset) -> Vec<OutlineEntry<'a>> {
+    let included: HashSet<&str> = preset.include.iter().map(String::as_str).collect();
+    let excluded: HashSet<&str> = preset.exclude.iter().map(String::as_str).collect();
+
+    // Depth of the subtree root currently being included / skipped
+    let mut include_root: Option<usize> = None;
+    let mut exclude_root: Option<usize> = None;
+    let mut selected = Vec::new();
+
+    for entry in reading_order(manifest) {
+        let id = entry.chapter.id.as_str();
+        if include_root.is_some_and(|d| entry.depth <= d) {
+            include_root = None;
+        }
+        if exclude_root.is_some_and(|d| entry.depth <= d) {
+            exclude_root = None;
+        }
+
+        if exclude_root.is_none() && excluded.contains(id) {
+            exclude_root = Some(entry.depth);
+        }
+        if exclude_root.is_some() {
+            continue;
+        }
+
+        if included.is_empty() {
+            selected.push(entry);
+            continue;
+        }
+        if include_root.is_none() && included.contains(id) {
+            include_root = Some(entry.depth);
+        }
+        if let Some(base) = include_root {
+            selected.push(OutlineEntry {
+                chapter: entry.chapter,
+                depth: entry.depth - base,
+            });
+        }
+    }
+
+    selected
+}
+
+/// A chapter ready to be rendered by an exporter.
+#[derive(Debug, Clone)]
+pub struct CompiledChapter {
+    pub id: String,
+    /// Display title, numbered according to the preset.
+    pub title: String,
+    pub depth: usize,
+    /// Heading level from the preset, `None` when the title is not printed.
+    pub heading_level: Option<u8>,
+    pub blocks: Vec<Block>,
+    pub word_count: u32,
+}
+
+/// The whole book, in reading order.
+#[derive(Debug, Clone)]
+pub struct Manuscript {
+    pub id: Uuid,
+    pub title: String,
+    pub author: String,
+    pub updated_at: DateTime<Utc>,
+    pub chapters: Vec<CompiledChapter>,
+}
+
+impl Manuscript {
+    pub fn word_count(&self) -> u32 {
+        self.chapters.iter().map(|c| c.word_count).sum()
+    }
+}
+
+/// Reads the nodes selected by the preset in reading order and parses their
+/// content. Untitled nodes are appended to the previous chapter after a scene break.
+pub async fn load_manuscript<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    metadata: &ProjectMetadata,
+    preset: &CompilePreset,
+) -> Result<Manuscript> {
+    let mut chapters: Vec<CompiledChapter> = Vec::new();
+    let mut chapter_number = 0;
+
+    for entry in select_entries(&metadata.manifest, preset) {
+        let content =
+            storage::read_chapter_content(repo, root_path, metadata, &entry.chapter.id).await?;
+        let mut blocks = html::parse(&content);
+        let word_count = crate::models::count_words(&content);
+        let heading_level = preset.heading_level(entry.depth);
+
+        if heading_level.is_none() {
+            if let Some(previous) = chapters.last_mut() {
+                if !previous.blocks.is_empty() && !blocks.is_empty() {
+                    previous.blocks.push(Block::SceneBreak);
+                }
+                previous.blocks.append(&mut blocks);
+                previous.word_count += word_count;
+                continue;
+            }
+        }
+
+        let title = if heading_level == Some(1) {
+            chapter_number += 1;
+            preset.chapter_title(chapter_number, &entry.chapter.title)
+        } else {
+            entry.chapter.title.clone()
+        };
+
+        chapters.push(CompiledChapter {
+            id: entry.chapter.id.clone(),
+            title,
+            depth: entry.depth,
+            heading_level,
+            blocks,
+            word_count,
+        });
+    }
+
+    Ok(Manuscript {
+        id: metadata.id,
+        title: metadata.title.clone(),
+        author: metadata.author.clone(),
+        updated_at: metadata.updated_at,
+        chapters,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapter(id: &str, parent: Option<&str>, order: u32) -> Chapter {
+        Chapter {
+            id: id.to_string(),
+            parent_id: parent.map(|s| s.to_string()),
+            title: id.to_string(),
+            filename: format!("{}.md", id),
+            word_count: 0,
+            order,
+            chronological_date: None,
+            abstract_timeframe: None,
+            duration: None,
+            plotline_tag: None,
+            depends_on: None,
+            pov_character_id: None,
+            synopsis: None,
+        }
+    }
+
+    #[test]
+    fn test_reading_order_follows_tree() {
+        let manifest = Manifest::new(vec![
+            chapter("part2", None, 1),
+            chapter("c2", Some("part1"), 1),
+            chapter("part1", None, 0),
+            chapter("c1", Some("part1"), 0),
+            chapter("c3", Some("part2"), 0),
+            chapter("orphan", Some("missing"), 0),
+        ]);
+
+        let order: Vec<(&str, usize)> = reading_order(&manifest)
+            .iter()
+            .map(|e| (e.chapter.id.as_str(), e.depth))
+            .collect();
+
+        assert_eq!(
+            order,
+            vec![("part1", 0), ("c1", 1), ("c2", 1), ("part2", 0), ("c3", 1)]
+        );
+    }
+
+    #[test]
+    fn test_select_entries_rebases_subtrees() {
+        let manifest = Manifest::new(vec![
+            chapter("part1", None, 0),
+            chapter("c1", Some("part1"), 0),
+            chapter("s1", Some("c1"), 0),
+            chapter("c2", Some("part1"), 1),
+            chapter("notes", None, 1),
+        ]);
+        let preset = CompilePreset {
+            include: vec!["c1".to_string(), "c2".to_string(), "notes".to_string()],
+            exclude: vec!["notes".to_string()],
+            ..Default::default()
+        };
+
+        let selected: Vec<(&str, usize)> = select_entries(&manifest, &preset)
+            .iter()
+            .map(|e| (e.chapter.id.as_str(), e.depth))
+            .collect();
+
+        assert_eq!(selected, vec![("c1", 0), ("s1", 1), ("c2", 0)]);
+    }
+
+    #[tokio::test]
+    async fn test_untitled_nodes_join_previous_chapter() {
+        let repo = storage::MockFileRepository::new();
+        let root = Path::new("/book");
+        let mut metadata = ProjectMetadata::new("Book".to_string(), "Author".to_string());
+        metadata.manifest = Manifest::new(vec![
+            chapter("c1", None, 0),
+            chapter("s1", Some("c1"), 0),
+            chapter("s2", Some("c1"), 1),
+            chapter("c2", None, 1),
+        ]);
+        for (id, text) in [
+            ("s1", "<p>One two.</p>"),
+            ("s2", "<p>Three.</p>"),
+            ("c2", "<p>Four.</p>"),
+        ] {
+            repo.set_content(
+                root.join(storage::MANUSCRIPT_DIR)
+                    .join(format!("{}.md", id)),
+                text.to_string(),
+            );
+        }
+
+        let submission = &CompilePreset::builtin()[0];
+        let manuscript = load_manuscript(&repo, root, &metadata, submission)
+            .await
+            .expect("Failed to load manuscript");
+
+        assert_eq!(manuscript.chapters.len(), 2);
+        let first = &manuscript.chapters[0];
+        assert_eq!(first.title, "Chapter One: c1");
+        assert_eq!(first.word_count, 3);
+        assert_eq!(first.blocks.len(), 3);
+        assert_eq!(first.blocks[1], Block::SceneBreak);
+        assert_eq!(manuscript.chapters[1].title, "Chapter Two: c2");
+    }
+}
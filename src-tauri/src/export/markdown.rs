@@ -0,0 +1,289 @@
+//! CommonMark writer.
+//!
+//! Converts the parsed chapter blocks back into Markdown and joins the chapters in
+//! reading order, with chapter headings at the preset's level. Strikethrough uses
+//! the `~~` extension understood by pandoc and GFM; underline has no Markdown form
+//! and is written as plain text.
+
+use super::html::{Block, Inline};
+use super::manuscript::Manuscript;
+use crate::models::CompilePreset;
+
+/// Renders the whole manuscript as a single Markdown document.
+pub fn build_markdown(manuscript: &Manuscript, preset: &CompilePreset) -> String {
+    let mut sections: Vec<String> = Vec::new();
+
+    if preset.front_matter {
+        // YAML metadata block, picked up by pandoc for the title page
+        sections.push(format!(
+            "---\ntitle: {}\nauthor: {}\n---",
+            yaml_string(&manuscript.title),
+            yaml_string(&manuscript.author)
+        ));
+    }
+
+    for chapter in &manuscript.chapters {
+        let mut parts = Vec::new();
+        if let Some(level) = chapter.heading_level {
+            parts.push(heading(level, &escape_line(&escape_text(&chapter.title))));
+        }
+        let base = chapter.heading_level.unwrap_or(1);
+        let body = render_blocks(&chapter.blocks, base, &preset.scene_separator);
+        if !body.is_empty() {
+            parts.push(body);
+        }
+        if !parts.is_empty() {
+            sections.push(parts.join("\n\n"));
+        }
+    }
+
+    if preset.back_matter {
+        sections.push(scene_separator(&preset.scene_separator));
+        sections.push("The End".to_string());
+    }
+
+    let mut out = sections.join("\n\n");
+    out.push('\n');
+    out
+}
+
+/// Renders blocks as Markdown. Headings inside the content are pushed below
+/// `base_level` so they never outrank the chapter heading.
+pub fn render_blocks(blocks: &[Block], base_level: u8, separator: &str) -> String {
+    blocks
+        .iter()
+        .map(|b| render_block(b, base_level, separator))
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn render_block(block: &Block, base_level: u8, separator: &str) -> String {
+    match block {
+        Block::Heading { level, content } => {
+            let level = (*level).max(base_level + 1).min(6);
+            let text = render_inlines(content).replace("\\\n", " ");
+            heading(level, &escape_line(&text))
+        }
+        Block::Paragraph(content) => render_inlines(content)
+            .lines()
+            .map(|line| escape_line(line.trim_start()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Block::BlockQuote(inner) => render_blocks(inner, base_level, separator)
+            .lines()
+            .map(|line| {
+                if line.is_empty() {
+                    ">".to_string()
+                } else {
+                    format!("> {}", line)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Block::List { ordered, items } => {
+            let tight = items
+                .iter()
+                .all(|item| matches!(item.as_slice(), [Block::Paragraph(_)]));
+            let rendered: Vec<String> = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let marker = if *ordered {
+                        format!("{}. ", i + 1)
+                    } else {
+                        "- ".to_string()
+                    };
+                    let indent = " ".repeat(marker.len());
+                    let body = render_blocks(item, base_level, separator);
+                    let mut lines = body.lines();
+                    let mut out = format!("{}{}", marker, lines.next().unwrap_or(""));
+                    for line in lines {
+                        out.push('\n');
+                        if !line.is_empty() {
+                            out.push_str(&indent);
+                            out.push_str(line);
+                        }
+                    }
+                    out
+                })
+                .collect();
+            rendered.join(if tight { "\n" } else { "\n\n" })
+        }
+        Block::CodeBlock(code) => {
+            let fence = "`".repeat(longest_run(code, '`').max(2) + 1);
+            format!("{}\n{}\n{}", fence, code.trim_end_matches('\n'), fence)
+        }
+        Block::SceneBreak => scene_separator(separator),
+    }
+}
+
+fn heading(level: u8, text: &str) -> String {
+    format!("{} {}", "#".repeat(level.clamp(1, 6) as usize), text)
+}
+
+/// A separator made only of `*`, `-` or `_` is already a thematic break; anything
+/// else is written as an escaped paragraph so it survives a round trip.
+fn scene_separator(separator: &str) -> String {
+    let marks: Vec<char> = separator.chars().filter(|c| !c.is_whitespace()).collect();
+    let is_break = marks.len() >= 3
+        && matches!(marks[0], '*' | '-' | '_')
+        && marks.iter().all(|c| *c == marks[0]);
+    if is_break {
+        separator.trim().to_string()
+    } else {
+        escape_line(&escape_text(separator.trim()))
+    }
+}
+
+fn render_inlines(inlines: &[Inline]) -> String {
+    let mut out = String::new();
+    for inline in inlines {
+        match inline {
+            Inline::Text(t) => out.push_str(&escape_text(t)),
+            Inline::Emphasis(c) => delimit(&mut out, "*", &render_inlines(c)),
+            Inline::Strong(c) => delimit(&mut out, "**", &render_inlines(c)),
+            Inline::Strikethrough(c) => delimit(&mut out, "~~", &render_inlines(c)),
+            Inline::Underline(c) => out.push_str(&render_inlines(c)),
+            Inline::Code(code) => {
+                let ticks = "`".repeat(longest_run(code, '`') + 1);
+                let pad = if code.starts_with('`') || code.ends_with('`') {
+                    " "
+                } else {
+                    ""
+                };
+                out.push_str(&format!("{0}{1}{2}{1}{0}", ticks, pad, code));
+            }
+            Inline::Link { href, content } => {
+                let target = if href.contains([' ', '(', ')']) {
+                    format!("<{}>", href)
+                } else {
+                    href.clone()
+                };
+                out.push_str(&format!("[{}]({})", render_inlines(content), target));
+            }
+            Inline::LineBreak => out.push_str("\\\n"),
+        }
+    }
+    out
+}
+
+/// Wraps `inner` in `delimiter`, keeping surrounding whitespace outside so the
+/// delimiters stay left/right-flanking.
+fn delimit(out: &mut String, delimiter: &str, inner: &str) {
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        out.push_str(inner);
+        return;
+    }
+    let leading = &inner[..inner.len() - inner.trim_start().len()];
+    let trailing = &inner[inner.trim_end().len()..];
+    out.push_str(leading);
+    out.push_str(delimiter);
+    out.push_str(trimmed);
+    out.push_str(delimiter);
+    out.push_str(trailing);
+}
+
+/// Backslash-escapes characters with inline meaning.
+pub fn escape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '~' | '&' | '|'
+        ) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Escapes markers that would turn the start of a line into a block construct.
+pub fn escape_line(line: &str) -> String {
+    if line.starts_with(['#', '+', '-', '=']) {
+        return format!("\\{}", line);
+    }
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 && line[digits..].starts_with(['.', ')']) {
+        return format!("{}\\{}", &line[..digits], &line[digits..]);
+    }
+    line.to_string()
+}
+
+fn longest_run(text: &str, ch: char) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for c in text.chars() {
+        if c == ch {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    longest
+}
+
+fn yaml_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::html::parse;
+    use crate::export::CompiledChapter;
+
+    #[test]
+    fn test_html_to_commonmark() {
+        let html = r#"<h2>Arrival</h2><p>She said <em>no </em>to <strong>everyone</strong>.<br>Then <span data-type="character-mention" data-id="1">Bob</span> left.</p><blockquote><p>Quoted</p><p>Twice</p></blockquote><ol><li><p>One</p></li><li><p>Two</p></li></ol><p>1. Not a list # and *not* bold</p>"#;
+        let markdown = render_blocks(&parse(html), 1, "#");
+
+        assert_eq!(
+            markdown,
+            "## Arrival\n\n\
+             She said *no* to **everyone**.\\\nThen Bob left.\n\n\
+             > Quoted\n>\n> Twice\n\n\
+             1. One\n2. Two\n\n\
+             1\\. Not a list # and \\*not\\* bold"
+        );
+    }
+
+    #[test]
+    fn test_chapters_follow_tree_depth() {
+        let manuscript = Manuscript {
+            id: uuid::Uuid::nil(),
+            title: "Book".to_string(),
+            author: "Jane \"J\" Doe".to_string(),
+            updated_at: chrono::Utc::now(),
+            chapters: vec![
+                CompiledChapter {
+                    id: "p".to_string(),
+                    title: "Part One".to_string(),
+                    depth: 0,
+                    heading_level: Some(1),
+                    blocks: vec![],
+                    word_count: 0,
+                },
+                CompiledChapter {
+                    id: "c".to_string(),
+                    title: "Chapter".to_string(),
+                    depth: 1,
+                    heading_level: Some(2),
+                    blocks: parse("<p>First.</p><hr><p>Second.</p>"),
+                    word_count: 2,
+                },
+            ],
+        };
+
+        let markdown = build_markdown(&manuscript, &CompilePreset::default());
+        assert_eq!(
+            markdown,
+            "---\ntitle: \"Book\"\nauthor: \"Jane \\\"J\\\" Doe\"\n---\n\n\
+             # Part One\n\n\
+             ## Chapter\n\nFirst.\n\n* * *\n\nSecond.\n"
+        );
+    }
+}
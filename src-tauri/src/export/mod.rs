@@ -0,0 +1,84 @@
+pub mod bible;
+pub mod characters;
+pub mod csv;
+pub mod docx;
+pub mod epub;
+pub mod html;
+pub mod html_book;
+pub mod latex;
+pub mod manuscript;
+pub mod markdown;
+pub mod odt;
+pub mod outline;
+pub mod package;
+pub mod pdf;
+pub mod timeline;
+
+pub use manuscript::{
+    load_manuscript, reading_order, select_entries, CompiledChapter, Manuscript, OutlineEntry,
+};
+
+use crate::models::CompilePreset;
+use serde::{Deserialize, Serialize};
+
+/// Output formats supported by `compile_project`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompileFormat {
+    Epub,
+    /// Standard Manuscript Format for submissions.
+    Docx,
+    /// Single CommonMark file.
+    Markdown,
+    /// Folder with `main.tex` and one file per chapter.
+    Latex,
+    /// Typeset reading proof.
+    Pdf,
+    /// OpenDocument Text with named paragraph styles.
+    Odt,
+    /// Self-contained `book.html` for sharing drafts.
+    Html,
+    /// Folder with `index.html` and one page per chapter.
+    HtmlSite,
+}
+
+/// Result of a compile: a single file, or a set of files written below the
+/// output folder.
+#[derive(Debug)]
+pub enum CompileOutput {
+    File(Vec<u8>),
+    Directory(Vec<(String, Vec<u8>)>),
+}
+
+/// Renders a compiled manuscript in the requested format.
+pub fn render(
+    manuscript: &Manuscript,
+    preset: &CompilePreset,
+    format: CompileFormat,
+) -> crate::errors::Result<CompileOutput> {
+    let output = match format {
+        CompileFormat::Epub => CompileOutput::File(epub::build_epub(manuscript, preset)?),
+        CompileFormat::Docx => CompileOutput::File(docx::build_docx(manuscript, preset)?),
+        CompileFormat::Markdown => {
+            CompileOutput::File(markdown::build_markdown(manuscript, preset).into_bytes())
+        }
+        CompileFormat::Odt => CompileOutput::File(odt::build_odt(manuscript, preset)?),
+        CompileFormat::Html => {
+            CompileOutput::File(html_book::build_single_page(manuscript, preset).into_bytes())
+        }
+        CompileFormat::HtmlSite => CompileOutput::Directory(
+            html_book::build_site(manuscript, preset)
+                .into_iter()
+                .map(|(name, content)| (name, content.into_bytes()))
+                .collect(),
+        ),
+        CompileFormat::Pdf => CompileOutput::File(pdf::build_pdf(manuscript, preset)),
+        CompileFormat::Latex => CompileOutput::Directory(
+            latex::build_latex(manuscript, preset)
+                .into_iter()
+                .map(|(name, content)| (name, content.into_bytes()))
+                .collect(),
+        ),
+    };
+    Ok(output)
+}
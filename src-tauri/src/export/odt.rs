@@ -0,0 +1,398 @@
+//! OpenDocument Text writer.
+//!
+//! All formatting goes through named styles (`Heading 1`..`Heading 6`, `Body`,
+//! `Scene Break`, `Quotations`, and `Emphasis`/`Strong Emphasis` for text runs), so
+//! the document can be restyled in LibreOffice after export.
+
+use super::html::{Block, Inline};
+use super::manuscript::Manuscript;
+use super::package::Package;
+use crate::errors::Result;
+use crate::models::CompilePreset;
+use html_escape::{encode_double_quoted_attribute, encode_text};
+
+const MIMETYPE: &str = "application/vnd.oasis.opendocument.text";
+
+const NAMESPACES: &str = r#"xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0" xmlns:xlink="http://www.w3.org/1999/xlink" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:meta="urn:oasis:names:tc:opendocument:xmlns:meta:1.0" office:version="1.3""#;
+
+const MANIFEST_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.3">
+ <manifest:file-entry manifest:full-path="/" manifest:media-type="application/vnd.oasis.opendocument.text"/>
+ <manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>
+ <manifest:file-entry manifest:full-path="styles.xml" manifest:media-type="text/xml"/>
+ <manifest:file-entry manifest:full-path="meta.xml" manifest:media-type="text/xml"/>
+</manifest:manifest>
+"#;
+
+/// Builds the `.odt` package for a manuscript.
+pub fn build_odt(manuscript: &Manuscript, preset: &CompilePreset) -> Result<Vec<u8>> {
+    let mut package = Package::new();
+
+    // As in EPUB, the mimetype entry must come first and stay uncompressed
+    package.add_stored("mimetype", MIMETYPE.as_bytes())?;
+    package.add_deflated("META-INF/manifest.xml", MANIFEST_XML.as_bytes())?;
+    package.add_deflated("meta.xml", build_meta(manuscript).as_bytes())?;
+    package.add_deflated("styles.xml", build_styles().as_bytes())?;
+    package.add_deflated("content.xml", build_content(manuscript, preset).as_bytes())?;
+
+    package.finish()
+}
+
+fn build_meta(manuscript: &Manuscript) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-meta {ns}>
+ <office:meta>
+  <meta:generator>BrutWrite</meta:generator>
+  <dc:title>{title}</dc:title>
+  <dc:creator>{author}</dc:creator>
+  <dc:date>{date}</dc:date>
+ </office:meta>
+</office:document-meta>
+"#,
+        ns = NAMESPACES,
+        title = encode_text(&manuscript.title),
+        author = encode_text(&manuscript.author),
+        date = manuscript.updated_at.format("%Y-%m-%dT%H:%M:%S"),
+    )
+}
+
+fn build_styles() -> String {
+    let mut headings = String::new();
+    for level in 1..=6u8 {
+        // Level 1 opens a new page; lower levels shrink towards body size
+        let (size, break_before) = match level {
+            1 => ("18pt", r#" fo:break-before="page""#),
+            2 => ("15pt", ""),
+            3 => ("13pt", ""),
+            _ => ("12pt", ""),
+        };
+        headings.push_str(&format!(
+            r#"  <style:style style:name="Heading_20_{level}" style:display-name="Heading {level}" style:family="paragraph" style:parent-style-name="Heading" style:next-style-name="Body" style:default-outline-level="{level}" style:class="text">
+   <style:paragraph-properties{break_before}/>
+   <style:text-properties fo:font-size="{size}" fo:font-weight="bold"/>
+  </style:style>
+"#
+        ));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-styles {ns}>
+ <office:font-face-decls>
+  <style:font-face style:name="Liberation Serif" svg:font-family="'Liberation Serif'" style:font-family-generic="roman" xmlns:svg="urn:oasis:names:tc:opendocument:xmlns:svg-compatible:1.0"/>
+  <style:font-face style:name="Liberation Mono" svg:font-family="'Liberation Mono'" style:font-family-generic="modern" style:font-pitch="fixed" xmlns:svg="urn:oasis:names:tc:opendocument:xmlns:svg-compatible:1.0"/>
+ </office:font-face-decls>
+ <office:styles>
+  <style:default-style style:family="paragraph">
+   <style:text-properties style:font-name="Liberation Serif" fo:font-size="12pt" fo:language="en" fo:country="US"/>
+  </style:default-style>
+  <style:style style:name="Standard" style:family="paragraph" style:class="text"/>
+  <style:style style:name="Body" style:family="paragraph" style:parent-style-name="Standard" style:class="text">
+   <style:paragraph-properties fo:text-indent="0.5in" fo:line-height="150%" fo:text-align="justify"/>
+  </style:style>
+  <style:style style:name="First_20_Paragraph" style:display-name="First Paragraph" style:family="paragraph" style:parent-style-name="Body" style:next-style-name="Body" style:class="text">
+   <style:paragraph-properties fo:text-indent="0in"/>
+  </style:style>
+  <style:style style:name="Heading" style:family="paragraph" style:parent-style-name="Standard" style:next-style-name="Body" style:class="text">
+   <style:paragraph-properties fo:margin-top="0.3in" fo:margin-bottom="0.2in" fo:text-align="center" fo:keep-with-next="always"/>
+  </style:style>
+{headings}  <style:style style:name="Scene_20_Break" style:display-name="Scene Break" style:family="paragraph" style:parent-style-name="Standard" style:next-style-name="First_20_Paragraph" style:class="text">
+   <style:paragraph-properties fo:margin-top="0.15in" fo:margin-bottom="0.15in" fo:text-align="center"/>
+  </style:style>
+  <style:style style:name="Quotations" style:family="paragraph" style:parent-style-name="Standard" style:class="html">
+   <style:paragraph-properties fo:margin-left="0.4in" fo:margin-right="0.4in" fo:margin-top="0.1in" fo:margin-bottom="0.1in"/>
+  </style:style>
+  <style:style style:name="List_20_Paragraph" style:display-name="List Paragraph" style:family="paragraph" style:parent-style-name="Standard" style:class="list"/>
+  <style:style style:name="Preformatted_20_Text" style:display-name="Preformatted Text" style:family="paragraph" style:parent-style-name="Standard" style:class="html">
+   <style:text-properties style:font-name="Liberation Mono" fo:font-size="10pt"/>
+  </style:style>
+  <style:style style:name="Title" style:family="paragraph" style:parent-style-name="Standard" style:next-style-name="Subtitle" style:class="chapter">
+   <style:paragraph-properties fo:margin-top="2.5in" fo:text-align="center"/>
+   <style:text-properties fo:font-size="28pt" fo:font-weight="bold"/>
+  </style:style>
+  <style:style style:name="Subtitle" style:family="paragraph" style:parent-style-name="Standard" style:class="chapter">
+   <style:paragraph-properties fo:margin-top="0.2in" fo:text-align="center"/>
+   <style:text-properties fo:font-size="16pt"/>
+  </style:style>
+  <style:style style:name="Emphasis" style:family="text">
+   <style:text-properties fo:font-style="italic"/>
+  </style:style>
+  <style:style style:name="Strong_20_Emphasis" style:display-name="Strong Emphasis" style:family="text">
+   <style:text-properties fo:font-weight="bold"/>
+  </style:style>
+  <style:style style:name="Underline" style:family="text">
+   <style:text-properties style:text-underline-style="solid" style:text-underline-width="auto" style:text-underline-color="font-color"/>
+  </style:style>
+  <style:style style:name="Strikethrough" style:family="text">
+   <style:text-properties style:text-line-through-style="solid"/>
+  </style:style>
+  <style:style style:name="Source_20_Text" style:display-name="Source Text" style:family="text">
+   <style:text-properties style:font-name="Liberation Mono"/>
+  </style:style>
+  <text:list-style style:name="List_20_Bullet" style:display-name="List Bullet">
+   <text:list-level-style-bullet text:level="1" text:bullet-char="•">
+    <style:list-level-properties text:list-level-position-and-space-mode="label-alignment">
+     <style:list-level-label-alignment text:label-followed-by="listtab" fo:text-indent="-0.25in" fo:margin-left="0.5in"/>
+    </style:list-level-properties>
+   </text:list-level-style-bullet>
+  </text:list-style>
+  <text:list-style style:name="List_20_Number" style:display-name="List Number">
+   <text:list-level-style-number text:level="1" style:num-suffix="." style:num-format="1">
+    <style:list-level-properties text:list-level-position-and-space-mode="label-alignment">
+     <style:list-level-label-alignment text:label-followed-by="listtab" fo:text-indent="-0.25in" fo:margin-left="0.5in"/>
+    </style:list-level-properties>
+   </text:list-level-style-number>
+  </text:list-style>
+ </office:styles>
+ <office:automatic-styles>
+  <style:page-layout style:name="PageLayout">
+   <style:page-layout-properties fo:page-width="8.5in" fo:page-height="11in" fo:margin-top="1in" fo:margin-bottom="1in" fo:margin-left="1in" fo:margin-right="1in"/>
+  </style:page-layout>
+ </office:automatic-styles>
+ <office:master-styles>
+  <style:master-page style:name="Standard" style:page-layout-name="PageLayout"/>
+ </office:master-styles>
+</office:document-styles>
+"#,
+        ns = NAMESPACES,
+    )
+}
+
+fn build_content(manuscript: &Manuscript, preset: &CompilePreset) -> String {
+    let mut body = String::new();
+
+    if preset.front_matter {
+        paragraph(&mut body, "Title", &encode_text(&manuscript.title));
+        paragraph(&mut body, "Subtitle", &encode_text(&manuscript.author));
+    }
+
+    for chapter in &manuscript.chapters {
+        if let Some(level) = chapter.heading_level {
+            heading(&mut body, level, &encode_text(&chapter.title));
+        }
+        let mut first = true;
+        for block in &chapter.blocks {
+            render_block(block, &preset.scene_separator, &mut first, &mut body);
+        }
+    }
+
+    if preset.back_matter {
+        paragraph(
+            &mut body,
+            "Scene_20_Break",
+            &encode_text(&preset.scene_separator),
+        );
+        paragraph(&mut body, "Scene_20_Break", "The End");
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content {ns}>
+ <office:body>
+  <office:text>
+{body}  </office:text>
+ </office:body>
+</office:document-content>
+"#,
+        ns = NAMESPACES,
+    )
+}
+
+fn paragraph(out: &mut String, style: &str, content: &str) {
+    out.push_str(&format!(
+        "<text:p text:style-name=\"{}\">{}</text:p>\n",
+        style, content
+    ));
+}
+
+fn heading(out: &mut String, level: u8, content: &str) {
+    let level = level.clamp(1, 6);
+    out.push_str(&format!(
+        "<text:h text:style-name=\"Heading_20_{0}\" text:outline-level=\"{0}\">{1}</text:h>\n",
+        level, content
+    ));
+}
+
+/// Renders a block. `first` tracks whether the next paragraph follows a heading
+/// or scene break and so takes the unindented `First Paragraph` style.
+fn render_block(block: &Block, separator: &str, first: &mut bool, out: &mut String) {
+    match block {
+        Block::Heading { level, content } => {
+            // Headings inside chapter content never outrank a chapter title
+            heading(out, (*level).max(2), &render_inlines(content));
+            *first = true;
+        }
+        Block::Paragraph(content) => {
+            let style = if *first { "First_20_Paragraph" } else { "Body" };
+            paragraph(out, style, &render_inlines(content));
+            *first = false;
+        }
+        Block::BlockQuote(inner) => {
+            for b in inner {
+                match b {
+                    Block::Paragraph(content) => {
+                        paragraph(out, "Quotations", &render_inlines(content))
+                    }
+                    other => render_block(other, separator, &mut true, out),
+                }
+            }
+            *first = true;
+        }
+        Block::List { ordered, items } => {
+            let style = if *ordered {
+                "List_20_Number"
+            } else {
+                "List_20_Bullet"
+            };
+            out.push_str(&format!("<text:list text:style-name=\"{}\">\n", style));
+            for item in items {
+                out.push_str("<text:list-item>\n");
+                for b in item {
+                    match b {
+                        Block::Paragraph(content) => {
+                            paragraph(out, "List_20_Paragraph", &render_inlines(content))
+                        }
+                        other => render_block(other, separator, &mut true, out),
+                    }
+                }
+                out.push_str("</text:list-item>\n");
+            }
+            out.push_str("</text:list>\n");
+            *first = true;
+        }
+        Block::CodeBlock(code) => {
+            for line in code.lines() {
+                paragraph(out, "Preformatted_20_Text", &preserve_spaces(line));
+            }
+            *first = true;
+        }
+        Block::SceneBreak => {
+            paragraph(out, "Scene_20_Break", &encode_text(separator));
+            *first = true;
+        }
+    }
+}
+
+fn render_inlines(inlines: &[Inline]) -> String {
+    let mut out = String::new();
+    for inline in inlines {
+        match inline {
+            Inline::Text(t) => out.push_str(&encode_text(t)),
+            Inline::Emphasis(c) => span(&mut out, "Emphasis", c),
+            Inline::Strong(c) => span(&mut out, "Strong_20_Emphasis", c),
+            Inline::Underline(c) => span(&mut out, "Underline", c),
+            Inline::Strikethrough(c) => span(&mut out, "Strikethrough", c),
+            Inline::Code(t) => out.push_str(&format!(
+                "<text:span text:style-name=\"Source_20_Text\">{}</text:span>",
+                preserve_spaces(t)
+            )),
+            Inline::Link { href, content } => out.push_str(&format!(
+                "<text:a xlink:type=\"simple\" xlink:href=\"{}\">{}</text:a>",
+                encode_double_quoted_attribute(href),
+                render_inlines(content)
+            )),
+            Inline::LineBreak => out.push_str("<text:line-break/>"),
+        }
+    }
+    out
+}
+
+fn span(out: &mut String, style: &str, content: &[Inline]) {
+    out.push_str(&format!(
+        "<text:span text:style-name=\"{}\">{}</text:span>",
+        style,
+        render_inlines(content)
+    ));
+}
+
+/// ODF collapses runs of spaces; keep them with `<text:s/>` and tabs with `<text:tab/>`.
+fn preserve_spaces(text: &str) -> String {
+    let mut out = String::new();
+    let mut spaces = 0;
+    let flush = |out: &mut String, spaces: &mut usize| {
+        match *spaces {
+            0 => {}
+            1 if !out.is_empty() => out.push(' '),
+            n => out.push_str(&format!("<text:s text:c=\"{}\"/>", n)),
+        }
+        *spaces = 0;
+    };
+    for c in text.chars() {
+        match c {
+            ' ' => spaces += 1,
+            '\t' => {
+                flush(&mut out, &mut spaces);
+                out.push_str("<text:tab/>");
+            }
+            _ => {
+                flush(&mut out, &mut spaces);
+                out.push_str(&encode_text(c.encode_utf8(&mut [0; 4])));
+            }
+        }
+    }
+    flush(&mut out, &mut spaces);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::html::parse;
+    use crate::export::CompiledChapter;
+    use std::io::Read;
+
+    #[test]
+    fn test_odt_uses_named_styles() {
+        let manuscript = Manuscript {
+            id: uuid::Uuid::nil(),
+            title: "Tales & Lies".to_string(),
+            author: "Jane Doe".to_string(),
+            updated_at: chrono::Utc::now(),
+            chapters: vec![CompiledChapter {
+                id: "c1".to_string(),
+                title: "Arrival".to_string(),
+                depth: 0,
+                heading_level: Some(1),
+                blocks: parse(
+                    "<p>First <em>scene</em>.</p><hr><p>Second.</p><pre><code>a  b</code></pre>",
+                ),
+                word_count: 3,
+            }],
+        };
+
+        let bytes = build_odt(&manuscript, &CompilePreset::default()).expect("ODT build failed");
+        let mut archive =
+            zip::ZipArchive::new(std::io::Cursor::new(bytes)).expect("Invalid ZIP output");
+        {
+            let first = archive.by_index(0).expect("Missing first entry");
+            assert_eq!(first.name(), "mimetype");
+            assert_eq!(first.compression(), zip::CompressionMethod::Stored);
+        }
+
+        let mut read = |name: &str| {
+            let mut s = String::new();
+            archive
+                .by_name(name)
+                .expect("Missing part")
+                .read_to_string(&mut s)
+                .expect("Unreadable part");
+            s
+        };
+
+        let content = read("content.xml");
+        assert!(content.contains(
+            r#"<text:h text:style-name="Heading_20_1" text:outline-level="1">Arrival</text:h>"#
+        ));
+        assert!(content.contains(r#"<text:p text:style-name="First_20_Paragraph">First <text:span text:style-name="Emphasis">scene</text:span>.</text:p>"#));
+        assert!(content.contains(r#"<text:p text:style-name="Scene_20_Break">* * *</text:p>"#));
+        assert!(content.contains(r#"a<text:s text:c="2"/>b"#));
+        assert!(content.contains("Tales &amp; Lies"));
+
+        let styles = read("styles.xml");
+        for name in ["Heading 1", "Heading 6", "Scene Break", "First Paragraph"] {
+            assert!(styles.contains(&format!("style:display-name=\"{}\"", name)));
+        }
+        assert!(styles.contains(r#"style:name="Body""#));
+    }
+}
@@ -0,0 +1,230 @@
+//! Outline writers.
+//!
+//! Summarises the manifest tree in reading order for editorial meetings: one line
+//! (Markdown) or one record (CSV) per node with its depth, stored word count,
+//! plotline, POV character and timeframe. The timeframe is the node's abstract
+//! timeframe, or its chronological date when it has none.
+
+use super::csv;
+use super::docx::format_thousands;
+use super::manuscript::{reading_order, OutlineEntry};
+use super::markdown::escape_text;
+use crate::models::{Plotline, ProjectMetadata};
+use serde::{Deserialize, Serialize};
+
+/// Output formats supported by `export_outline`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutlineFormat {
+    Markdown,
+    Csv,
+}
+
+/// Renders the outline in the requested format.
+pub fn build_outline(metadata: &ProjectMetadata, format: OutlineFormat) -> String {
+    match format {
+        OutlineFormat::Markdown => build_markdown(metadata),
+        OutlineFormat::Csv => build_csv(metadata),
+    }
+}
+
+/// Resolved fields of one outline row.
+struct Row<'a> {
+    entry: OutlineEntry<'a>,
+    plotline: Option<Plotline>,
+    pov: Option<&'a str>,
+    timeframe: Option<&'a str>,
+}
+
+fn rows(metadata: &ProjectMetadata) -> Vec<Row<'_>> {
+    reading_order(&metadata.manifest)
+        .into_iter()
+        .map(|entry| {
+            let chapter = entry.chapter;
+            // Unknown tags are kept, with no colour, rather than dropped
+            let plotline = chapter.plotline_tag.as_deref().map(|tag| {
+                metadata.plotline(tag).cloned().unwrap_or_else(|| Plotline {
+                    id: tag.to_string(),
+                    name: tag.to_string(),
+                    color: String::new(),
+                })
+            });
+            let pov = chapter
+                .pov_character_id
+                .as_deref()
+                .and_then(|id| metadata.character(id))
+                .map(|c| c.name.as_str());
+            let timeframe = chapter
+                .abstract_timeframe
+                .as_deref()
+                .filter(|t| !t.trim().is_empty())
+                .or(chapter.chronological_date.as_deref())
+                .filter(|t| !t.trim().is_empty());
+
+            Row {
+                entry,
+                plotline,
+                pov,
+                timeframe,
+            }
+        })
+        .collect()
+}
+
+fn build_markdown(metadata: &ProjectMetadata) -> String {
+    let rows = rows(metadata);
+    let total: u32 = rows.iter().map(|r| r.entry.chapter.word_count).sum();
+
+    let mut out = format!(
+        "# {} - Outline\n\n{} words in {} nodes\n\n",
+        escape_text(&metadata.title),
+        format_thousands(total),
+        rows.len()
+    );
+
+    for row in &rows {
+        let chapter = row.entry.chapter;
+        let mut details = vec![format!("{} words", format_thousands(chapter.word_count))];
+        if let Some(plotline) = &row.plotline {
+            details.push(if plotline.color.is_empty() {
+                format!("Plotline: {}", escape_text(&plotline.name))
+            } else {
+                format!(
+                    "Plotline: {} ({})",
+                    escape_text(&plotline.name),
+                    escape_text(&plotline.color)
+                )
+            });
+        }
+        if let Some(pov) = row.pov {
+            details.push(format!("POV: {}", escape_text(pov)));
+        }
+        if let Some(timeframe) = row.timeframe {
+            details.push(format!("Timeframe: {}", escape_text(timeframe)));
+        }
+
+        out.push_str(&format!(
+            "{}- **{}** - {}\n",
+            "  ".repeat(row.entry.depth),
+            escape_text(chapter.title.trim()),
+            details.join(" · ")
+        ));
+    }
+    out
+}
+
+fn build_csv(metadata: &ProjectMetadata) -> String {
+    let mut out = csv::record(&[
+        "position",
+        "id",
+        "title",
+        "depth",
+        "word_count",
+        "plotline_tag",
+        "plotline",
+        "plotline_color",
+        "pov_character",
+        "timeframe",
+    ]);
+
+    for (index, row) in rows(metadata).iter().enumerate() {
+        let chapter = row.entry.chapter;
+        let plotline = row.plotline.as_ref();
+        out.push_str(&csv::record(&[
+            (index + 1).to_string(),
+            chapter.id.clone(),
+            chapter.title.clone(),
+            row.entry.depth.to_string(),
+            chapter.word_count.to_string(),
+            chapter.plotline_tag.clone().unwrap_or_default(),
+            plotline.map(|p| p.name.clone()).unwrap_or_default(),
+            plotline.map(|p| p.color.clone()).unwrap_or_default(),
+            row.pov.unwrap_or_default().to_string(),
+            row.timeframe.unwrap_or_default().to_string(),
+        ]));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Chapter, Character, CharacterRole, Manifest};
+    use uuid::Uuid;
+
+    fn node(id: &str, parent: Option<&str>, order: u32, words: u32) -> Chapter {
+        Chapter {
+            id: id.to_string(),
+            parent_id: parent.map(String::from),
+            title: format!("Node {}", id),
+            filename: format!("{}.md", id),
+            word_count: words,
+            order,
+            chronological_date: None,
+            abstract_timeframe: None,
+            duration: None,
+            plotline_tag: None,
+            depends_on: None,
+            pov_character_id: None,
+            synopsis: None,
+        }
+    }
+
+    fn sample() -> ProjectMetadata {
+        let mut metadata = ProjectMetadata::new("Tales".to_string(), "Jane Doe".to_string());
+        metadata.characters = vec![Character {
+            id: Uuid::from_u128(7),
+            name: "Alice".to_string(),
+            role: CharacterRole::Protagonist,
+            archetype: String::new(),
+            description: String::new(),
+            engine: Default::default(),
+            physical_features: String::new(),
+            traits: vec![],
+            arc: String::new(),
+            notes: String::new(),
+            aliases: vec![],
+        }];
+
+        let mut scene = node("s1", Some("p1"), 0, 1500);
+        scene.plotline_tag = Some("main".to_string());
+        scene.pov_character_id = Some(Uuid::from_u128(7).to_string());
+        scene.abstract_timeframe = Some("Day 1".to_string());
+
+        let mut dated = node("s2", Some("p1"), 1, 800);
+        dated.plotline_tag = Some("romance".to_string());
+        dated.chronological_date = Some("2024-03-01".to_string());
+
+        metadata.manifest = Manifest::new(vec![dated, node("p1", None, 0, 0), scene]);
+        metadata
+    }
+
+    #[test]
+    fn test_markdown_outline() {
+        let md = build_outline(&sample(), OutlineFormat::Markdown);
+        assert_eq!(
+            md,
+            "# Tales - Outline\n\n\
+             2,300 words in 3 nodes\n\n\
+             - **Node p1** - 0 words\n  \
+             - **Node s1** - 1,500 words · Plotline: Main Plot (#3b82f6) · POV: Alice · Timeframe: Day 1\n  \
+             - **Node s2** - 800 words · Plotline: romance · Timeframe: 2024-03-01\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_outline() {
+        let csv = build_outline(&sample(), OutlineFormat::Csv);
+        let rows: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(
+            rows[0],
+            "position,id,title,depth,word_count,plotline_tag,plotline,plotline_color,pov_character,timeframe"
+        );
+        assert_eq!(rows[1], "1,p1,Node p1,0,0,,,,,");
+        assert_eq!(
+            rows[2],
+            "2,s1,Node s1,1,1500,main,Main Plot,#3b82f6,Alice,Day 1"
+        );
+        assert_eq!(rows[3], "3,s2,Node s2,1,800,romance,romance,,,2024-03-01");
+    }
+}
@@ -0,0 +1,51 @@
+//! Minimal ZIP container writer shared by the package-based formats (EPUB, DOCX, ODT).
+
+use crate::errors::{Error, Result};
+use std::io::{Cursor, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// In-memory ZIP archive. Entries carry no timestamps, so output is deterministic.
+pub struct Package {
+    writer: ZipWriter<Cursor<Vec<u8>>>,
+}
+
+impl Package {
+    pub fn new() -> Self {
+        Self {
+            writer: ZipWriter::new(Cursor::new(Vec::new())),
+        }
+    }
+
+    /// Adds an uncompressed entry (required for `mimetype` in EPUB and ODF).
+    pub fn add_stored(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        self.add(name, data, CompressionMethod::Stored)
+    }
+
+    pub fn add_deflated(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        self.add(name, data, CompressionMethod::Deflated)
+    }
+
+    fn add(&mut self, name: &str, data: &[u8], method: CompressionMethod) -> Result<()> {
+        let options = SimpleFileOptions::default().compression_method(method);
+        self.writer
+            .start_file(name, options)
+            .map_err(|e| Error::Export(format!("Failed to add `{}`: {}", name, e)))?;
+        self.writer.write_all(data)?;
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<Vec<u8>> {
+        let cursor = self
+            .writer
+            .finish()
+            .map_err(|e| Error::Export(format!("Failed to finalize archive: {}", e)))?;
+        Ok(cursor.into_inner())
+    }
+}
+
+impl Default for Package {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,940 @@
+//! PDF proof writer.
+//!
+//! Lays the manuscript out with the preset's trim size, margins, font and line
+//! spacing, using the standard Type 1 fonts so nothing needs to be embedded or
+//! installed. Level-1 chapters open on a new page; running headers carry the book
+//! title on even pages and the chapter title on odd ones, with page numbers on the
+//! outer edge. The same input always produces the same bytes.
+
+mod metrics;
+mod writer;
+
+use super::html::{Block, Inline};
+use super::manuscript::Manuscript;
+use crate::models::{CompilePreset, PdfFont, PdfLayout};
+use writer::{literal, num, win_ansi, DocumentInfo};
+
+/// Points per millimetre.
+const MM: f64 = 72.0 / 25.4;
+
+/// Builds a PDF proof of the manuscript.
+pub fn build_pdf(manuscript: &Manuscript, preset: &CompilePreset) -> Vec<u8> {
+    let options = &preset.pdf;
+    let mut layout = Layout::new(options);
+
+    if preset.front_matter {
+        layout.title_page(&manuscript.title, &manuscript.author);
+    }
+
+    for chapter in &manuscript.chapters {
+        layout.chapter = chapter.title.clone();
+        match chapter.heading_level {
+            Some(1) => {
+                layout.open_page();
+                layout.chapter_heading(&chapter.title);
+            }
+            Some(level) => layout.section_heading(&chapter.title, level),
+            None if layout.pages.is_empty() => layout.open_page(),
+            None => {}
+        }
+        layout.indent_next = false;
+        for block in &chapter.blocks {
+            layout.block(block, &preset.scene_separator, Margins::default());
+        }
+    }
+
+    if preset.back_matter {
+        let size = options.font_size;
+        layout.skip(layout.line_height(size) * 2.0);
+        layout.paragraph(
+            plain_words(&layout, "The End", Style::default(), size),
+            &ParagraphStyle::centered(size),
+        );
+    }
+    if layout.pages.is_empty() {
+        layout.new_page(true);
+    }
+
+    let fonts = base_fonts(options.font);
+    let pages = layout.finish(&manuscript.title);
+    writer::write_document(
+        &DocumentInfo {
+            title: &manuscript.title,
+            author: &manuscript.author,
+            modified: manuscript.updated_at,
+            fonts: &fonts,
+            page_size: (options.page_width * MM, options.page_height * MM),
+        },
+        &pages,
+    )
+}
+
+// =============================================================================
+// Fonts
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Face {
+    Regular,
+    Italic,
+    Bold,
+    BoldItalic,
+    Mono,
+}
+
+impl Face {
+    /// Resource name, matching the order of `base_fonts`.
+    fn resource(self) -> &'static str {
+        match self {
+            Face::Regular => "F1",
+            Face::Italic => "F2",
+            Face::Bold => "F3",
+            Face::BoldItalic => "F4",
+            Face::Mono => "F5",
+        }
+    }
+}
+
+fn base_fonts(font: PdfFont) -> [&'static str; 5] {
+    match font {
+        PdfFont::Times => [
+            "Times-Roman",
+            "Times-Italic",
+            "Times-Bold",
+            "Times-BoldItalic",
+            "Courier",
+        ],
+        PdfFont::Helvetica => [
+            "Helvetica",
+            "Helvetica-Oblique",
+            "Helvetica-Bold",
+            "Helvetica-BoldOblique",
+            "Courier",
+        ],
+        PdfFont::Courier => [
+            "Courier",
+            "Courier-Oblique",
+            "Courier-Bold",
+            "Courier-BoldOblique",
+            "Courier",
+        ],
+    }
+}
+
+/// Advance width of a WinAnsi code in 1/1000 em.
+fn glyph_width(font: PdfFont, face: Face, code: u8) -> f64 {
+    let table = match (font, face) {
+        (_, Face::Mono) | (PdfFont::Courier, _) => return 600.0,
+        (PdfFont::Times, Face::Regular) => &metrics::TIMES_ROMAN,
+        (PdfFont::Times, Face::Italic) => &metrics::TIMES_ITALIC,
+        (PdfFont::Times, Face::Bold) => &metrics::TIMES_BOLD,
+        (PdfFont::Times, Face::BoldItalic) => &metrics::TIMES_BOLDITALIC,
+        (PdfFont::Helvetica, Face::Regular) => &metrics::HELVETICA,
+        (PdfFont::Helvetica, Face::Italic) => &metrics::HELVETICA_OBLIQUE,
+        (PdfFont::Helvetica, Face::Bold) => &metrics::HELVETICA_BOLD,
+        (PdfFont::Helvetica, Face::BoldItalic) => &metrics::HELVETICA_BOLDOBLIQUE,
+    };
+    f64::from(table[usize::from(code.max(32) - 32)])
+}
+
+// =============================================================================
+// Words
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Style {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strike: bool,
+    mono: bool,
+}
+
+impl Style {
+    fn face(self) -> Face {
+        match (self.mono, self.bold, self.italic) {
+            (true, _, _) => Face::Mono,
+            (false, true, true) => Face::BoldItalic,
+            (false, true, false) => Face::Bold,
+            (false, false, true) => Face::Italic,
+            (false, false, false) => Face::Regular,
+        }
+    }
+}
+
+/// A run of text in a single style.
+#[derive(Debug, Clone)]
+struct Piece {
+    bytes: Vec<u8>,
+    style: Style,
+    size: f64,
+    width: f64,
+}
+
+/// Unbreakable unit of a line; may mix styles ("*un*believable").
+#[derive(Debug, Clone, Default)]
+struct Word {
+    pieces: Vec<Piece>,
+    width: f64,
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    Word(Word),
+    Break,
+}
+
+/// Splits styled text into words.
+struct WordBuilder {
+    font: PdfFont,
+    size: f64,
+    tokens: Vec<Token>,
+    current: Word,
+}
+
+impl WordBuilder {
+    fn new(font: PdfFont, size: f64) -> Self {
+        Self {
+            font,
+            size,
+            tokens: Vec::new(),
+            current: Word::default(),
+        }
+    }
+
+    fn push_inlines(&mut self, inlines: &[Inline], style: Style) {
+        for inline in inlines {
+            match inline {
+                Inline::Text(t) => self.push_str(t, style),
+                Inline::Emphasis(c) => self.push_inlines(
+                    c,
+                    Style {
+                        italic: !style.italic,
+                        ..style
+                    },
+                ),
+                Inline::Strong(c) => self.push_inlines(
+                    c,
+                    Style {
+                        bold: true,
+                        ..style
+                    },
+                ),
+                Inline::Underline(c) => self.push_inlines(
+                    c,
+                    Style {
+                        underline: true,
+                        ..style
+                    },
+                ),
+                Inline::Strikethrough(c) => self.push_inlines(
+                    c,
+                    Style {
+                        strike: true,
+                        ..style
+                    },
+                ),
+                Inline::Code(t) => self.push_str(
+                    t,
+                    Style {
+                        mono: true,
+                        ..style
+                    },
+                ),
+                Inline::Link { content, .. } => self.push_inlines(content, style),
+                Inline::LineBreak => {
+                    self.flush();
+                    self.tokens.push(Token::Break);
+                }
+            }
+        }
+    }
+
+    fn push_str(&mut self, text: &str, style: Style) {
+        for c in text.chars() {
+            if c.is_whitespace() {
+                self.flush();
+                continue;
+            }
+            let bytes = win_ansi(c.encode_utf8(&mut [0; 4]));
+            let width: f64 = bytes
+                .iter()
+                .map(|b| glyph_width(self.font, style.face(), *b) * self.size / 1000.0)
+                .sum();
+            match self.current.pieces.last_mut() {
+                Some(piece) if piece.style == style => {
+                    piece.bytes.extend(bytes);
+                    piece.width += width;
+                }
+                _ => self.current.pieces.push(Piece {
+                    bytes,
+                    style,
+                    size: self.size,
+                    width,
+                }),
+            }
+            self.current.width += width;
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.current.pieces.is_empty() {
+            self.tokens
+                .push(Token::Word(std::mem::take(&mut self.current)));
+        }
+    }
+
+    fn finish(mut self) -> Vec<Token> {
+        self.flush();
+        self.tokens
+    }
+}
+
+fn plain_words(layout: &Layout, text: &str, style: Style, size: f64) -> Vec<Token> {
+    let mut builder = WordBuilder::new(layout.options.font, size);
+    builder.push_str(text, style);
+    builder.finish()
+}
+
+// =============================================================================
+// Layout
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Center,
+    Justify,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ParagraphStyle {
+    size: f64,
+    align: Align,
+    first_indent: f64,
+    margins: Margins,
+}
+
+impl ParagraphStyle {
+    fn centered(size: f64) -> Self {
+        Self {
+            size,
+            align: Align::Center,
+            first_indent: 0.0,
+            margins: Margins::default(),
+        }
+    }
+}
+
+/// Extra indentation on both sides of the text block (quotes, lists).
+#[derive(Debug, Clone, Copy, Default)]
+struct Margins {
+    left: f64,
+    right: f64,
+}
+
+struct Page {
+    content: String,
+    chapter: String,
+    /// Chapter opening pages carry no running header.
+    opening: bool,
+    numbered: bool,
+}
+
+struct Layout<'a> {
+    options: &'a PdfLayout,
+    pages: Vec<Page>,
+    /// Distance from the top margin to the top of the next line.
+    cursor: f64,
+    chapter: String,
+    /// Whether the next body paragraph gets a first-line indent.
+    indent_next: bool,
+}
+
+impl<'a> Layout<'a> {
+    fn new(options: &'a PdfLayout) -> Self {
+        Self {
+            options,
+            pages: Vec::new(),
+            cursor: 0.0,
+            chapter: String::new(),
+            indent_next: false,
+        }
+    }
+
+    fn page_width(&self) -> f64 {
+        self.options.page_width * MM
+    }
+
+    fn page_height(&self) -> f64 {
+        self.options.page_height * MM
+    }
+
+    fn content_width(&self) -> f64 {
+        self.page_width() - (self.options.margin_inner + self.options.margin_outer) * MM
+    }
+
+    fn content_height(&self) -> f64 {
+        self.page_height() - (self.options.margin_top + self.options.margin_bottom) * MM
+    }
+
+    /// Left edge of the text block; odd pages are rectos with the binding on the left.
+    fn left_edge(&self) -> f64 {
+        if self.pages.len() % 2 == 1 {
+            self.options.margin_inner * MM
+        } else {
+            self.options.margin_outer * MM
+        }
+    }
+
+    fn line_height(&self, size: f64) -> f64 {
+        size * self.options.line_spacing
+    }
+
+    fn space_width(&self, size: f64) -> f64 {
+        glyph_width(self.options.font, Face::Regular, b' ') * size / 1000.0
+    }
+
+    fn new_page(&mut self, opening: bool) {
+        self.pages.push(Page {
+            content: String::new(),
+            chapter: self.chapter.clone(),
+            opening,
+            numbered: true,
+        });
+        self.cursor = 0.0;
+    }
+
+    /// Starts a chapter on a fresh page.
+    fn open_page(&mut self) {
+        match self.pages.last_mut() {
+            Some(page) if page.numbered && page.content.is_empty() => {
+                page.opening = true;
+                page.chapter = self.chapter.clone();
+            }
+            _ => self.new_page(true),
+        }
+    }
+
+    /// Moves to a new page unless `height` still fits on the current one.
+    fn ensure(&mut self, height: f64) {
+        if self.pages.is_empty()
+            || (self.cursor > 0.0 && self.cursor + height > self.content_height())
+        {
+            self.new_page(false);
+        }
+    }
+
+    /// Vertical space; dropped at the top of a page.
+    fn skip(&mut self, amount: f64) {
+        if self.cursor > 0.0 {
+            self.cursor = (self.cursor + amount).min(self.content_height());
+        }
+    }
+
+    fn title_page(&mut self, title: &str, author: &str) {
+        let size = self.options.font_size;
+        self.new_page(false);
+        if let Some(page) = self.pages.last_mut() {
+            page.numbered = false;
+        }
+        self.cursor = self.content_height() * 0.3;
+        let title_words = plain_words(
+            self,
+            title,
+            Style {
+                bold: true,
+                ..Style::default()
+            },
+            size * 2.0,
+        );
+        self.paragraph(title_words, &ParagraphStyle::centered(size * 2.0));
+        self.skip(self.line_height(size) * 2.0);
+        let author_words = plain_words(self, author, Style::default(), size * 1.2);
+        self.paragraph(author_words, &ParagraphStyle::centered(size * 1.2));
+    }
+
+    fn chapter_heading(&mut self, title: &str) {
+        let size = self.options.font_size * 1.6;
+        self.cursor = self.content_height() * 0.2;
+        let words = plain_words(
+            self,
+            title,
+            Style {
+                bold: true,
+                ..Style::default()
+            },
+            size,
+        );
+        self.paragraph(words, &ParagraphStyle::centered(size));
+        self.skip(self.line_height(self.options.font_size) * 1.5);
+    }
+
+    fn section_heading(&mut self, title: &str, level: u8) {
+        let size = self.options.font_size * if level <= 2 { 1.25 } else { 1.1 };
+        let body = self.line_height(self.options.font_size);
+        // Keep the heading with at least two lines of text
+        self.ensure(self.line_height(size) + body * 3.0);
+        self.skip(body);
+        let words = plain_words(
+            self,
+            title,
+            Style {
+                bold: true,
+                ..Style::default()
+            },
+            size,
+        );
+        self.paragraph(words, &ParagraphStyle::centered(size));
+        self.skip(body * 0.5);
+    }
+
+    fn block(&mut self, block: &Block, separator: &str, margins: Margins) {
+        let size = self.options.font_size;
+        let line = self.line_height(size);
+        let em = size;
+
+        match block {
+            Block::Heading { level, content } => {
+                let heading_size = size * if *level <= 2 { 1.2 } else { 1.05 };
+                self.ensure(self.line_height(heading_size) + line * 2.0);
+                self.skip(line * 0.5);
+                let mut builder = WordBuilder::new(self.options.font, heading_size);
+                builder.push_inlines(
+                    content,
+                    Style {
+                        bold: true,
+                        ..Style::default()
+                    },
+                );
+                self.paragraph(
+                    builder.finish(),
+                    &ParagraphStyle {
+                        size: heading_size,
+                        align: Align::Left,
+                        first_indent: 0.0,
+                        margins,
+                    },
+                );
+                self.skip(line * 0.25);
+                self.indent_next = false;
+            }
+            Block::Paragraph(content) => {
+                let mut builder = WordBuilder::new(self.options.font, size);
+                builder.push_inlines(content, Style::default());
+                let first_indent = if self.indent_next { em * 1.5 } else { 0.0 };
+                self.paragraph(
+                    builder.finish(),
+                    &ParagraphStyle {
+                        size,
+                        align: Align::Justify,
+                        first_indent,
+                        margins,
+                    },
+                );
+                self.indent_next = true;
+            }
+            Block::BlockQuote(inner) => {
+                self.skip(line * 0.5);
+                self.indent_next = false;
+                let nested = Margins {
+                    left: margins.left + em * 1.5,
+                    right: margins.right + em * 1.5,
+                };
+                for b in inner {
+                    self.block(b, separator, nested);
+                }
+                self.skip(line * 0.5);
+                self.indent_next = false;
+            }
+            Block::List { ordered, items } => {
+                let hang = em * 1.5;
+                let nested = Margins {
+                    left: margins.left + hang,
+                    right: margins.right,
+                };
+                for (i, item) in items.iter().enumerate() {
+                    let marker = if *ordered {
+                        format!("{}.", i + 1)
+                    } else {
+                        "\u{2022}".to_string()
+                    };
+                    let mut marker_words = plain_words(self, &marker, Style::default(), size);
+                    // The marker occupies the hanging indent, less the space after it
+                    if let Some(Token::Word(word)) = marker_words.first_mut() {
+                        word.width = (hang - self.space_width(size)).max(word.width);
+                    }
+
+                    for (j, b) in item.iter().enumerate() {
+                        match (j, b) {
+                            (0, Block::Paragraph(content)) => {
+                                let mut builder = WordBuilder::new(self.options.font, size);
+                                builder.push_inlines(content, Style::default());
+                                let mut tokens = marker_words.clone();
+                                tokens.extend(builder.finish());
+                                self.paragraph(
+                                    tokens,
+                                    &ParagraphStyle {
+                                        size,
+                                        align: Align::Left,
+                                        first_indent: -hang,
+                                        margins: nested,
+                                    },
+                                );
+                            }
+                            (0, other) => {
+                                self.paragraph(
+                                    marker_words.clone(),
+                                    &ParagraphStyle {
+                                        size,
+                                        align: Align::Left,
+                                        first_indent: -hang,
+                                        margins: nested,
+                                    },
+                                );
+                                self.indent_next = false;
+                                self.block(other, separator, nested);
+                            }
+                            (_, other) => {
+                                self.indent_next = false;
+                                self.block(other, separator, nested);
+                            }
+                        }
+                    }
+                }
+                self.indent_next = false;
+            }
+            Block::CodeBlock(code) => {
+                for text in code.lines() {
+                    let bytes = win_ansi(text);
+                    let width = bytes.len() as f64 * 600.0 * size / 1000.0;
+                    let word = Word {
+                        pieces: vec![Piece {
+                            bytes,
+                            style: Style {
+                                mono: true,
+                                ..Style::default()
+                            },
+                            size,
+                            width,
+                        }],
+                        width,
+                    };
+                    self.paragraph(
+                        vec![Token::Word(word)],
+                        &ParagraphStyle {
+                            size,
+                            align: Align::Left,
+                            first_indent: 0.0,
+                            margins,
+                        },
+                    );
+                }
+                self.indent_next = false;
+            }
+            Block::SceneBreak => {
+                self.ensure(line * 3.0);
+                self.skip(line * 0.5);
+                let words = plain_words(self, separator, Style::default(), size);
+                self.paragraph(words, &ParagraphStyle::centered(size));
+                self.skip(line * 0.5);
+                self.indent_next = false;
+            }
+        }
+    }
+
+    /// Breaks words into lines (greedy) and places them.
+    fn paragraph(&mut self, tokens: Vec<Token>, style: &ParagraphStyle) {
+        let space = self.space_width(style.size);
+        let avail = self.content_width() - style.margins.left - style.margins.right;
+
+        let mut lines: Vec<(Vec<Word>, bool)> = Vec::new();
+        let mut current: Vec<Word> = Vec::new();
+        let mut width = 0.0;
+        for token in tokens {
+            match token {
+                Token::Word(word) => {
+                    let limit = if lines.is_empty() {
+                        avail - style.first_indent
+                    } else {
+                        avail
+                    };
+                    if !current.is_empty() && width + space + word.width > limit {
+                        lines.push((std::mem::take(&mut current), true));
+                        width = 0.0;
+                    }
+                    if !current.is_empty() {
+                        width += space;
+                    }
+                    width += word.width;
+                    current.push(word);
+                }
+                Token::Break => {
+                    lines.push((std::mem::take(&mut current), false));
+                    width = 0.0;
+                }
+            }
+        }
+        if !current.is_empty() {
+            lines.push((current, false));
+        }
+
+        for (index, (words, stretch)) in lines.iter().enumerate() {
+            let indent = if index == 0 { style.first_indent } else { 0.0 };
+            self.place_line(words, *stretch, indent, avail, space, style);
+        }
+    }
+
+    fn place_line(
+        &mut self,
+        words: &[Word],
+        stretch: bool,
+        indent: f64,
+        avail: f64,
+        space: f64,
+        style: &ParagraphStyle,
+    ) {
+        let line = self.line_height(style.size);
+        self.ensure(line);
+
+        let available = avail - indent;
+        let natural: f64 = words.iter().map(|w| w.width).sum::<f64>()
+            + space * words.len().saturating_sub(1) as f64;
+        let mut x = self.left_edge() + style.margins.left + indent;
+        let mut gap = space;
+        match style.align {
+            Align::Center => x += ((available - natural) / 2.0).max(0.0),
+            Align::Justify if stretch && words.len() > 1 && natural < available => {
+                gap += (available - natural) / (words.len() - 1) as f64;
+            }
+            _ => {}
+        }
+
+        // Baseline sits in the middle of the leading, ascent taken as 0.8 em
+        let baseline = self.page_height()
+            - self.options.margin_top * MM
+            - self.cursor
+            - (line - style.size) / 2.0
+            - style.size * 0.8;
+
+        let mut ops = String::new();
+        for word in words {
+            let start = x;
+            for piece in &word.pieces {
+                ops.push_str(&text_op(
+                    piece.style.face(),
+                    piece.size,
+                    x,
+                    baseline,
+                    &piece.bytes,
+                ));
+                if piece.style.underline {
+                    ops.push_str(&rule(
+                        x,
+                        baseline - piece.size * 0.12,
+                        piece.width,
+                        piece.size,
+                    ));
+                }
+                if piece.style.strike {
+                    ops.push_str(&rule(
+                        x,
+                        baseline + piece.size * 0.28,
+                        piece.width,
+                        piece.size,
+                    ));
+                }
+                x += piece.width;
+            }
+            // Word width may exceed its pieces (list markers fill the hanging indent)
+            x = start + word.width + gap;
+        }
+
+        if let Some(page) = self.pages.last_mut() {
+            page.content.push_str(&ops);
+        }
+        self.cursor += line;
+    }
+
+    /// Adds running headers / folios and returns the content streams.
+    fn finish(self, book_title: &str) -> Vec<String> {
+        let size = self.options.font_size * 0.85;
+        let width = self.page_width();
+        let header_y = self.page_height() - self.options.margin_top * MM / 2.0 - size * 0.3;
+        let footer_y = self.options.margin_bottom * MM / 2.0 - size * 0.3;
+        let outer = self.options.margin_outer * MM;
+        let font = self.options.font;
+        let measure = |bytes: &[u8], face: Face| -> f64 {
+            bytes
+                .iter()
+                .map(|b| glyph_width(font, face, *b) * size / 1000.0)
+                .sum()
+        };
+
+        self.pages
+            .iter()
+            .enumerate()
+            .map(|(index, page)| {
+                let number = index + 1;
+                let mut ops = String::new();
+                if page.numbered {
+                    let folio = win_ansi(&number.to_string());
+                    let folio_width = measure(&folio, Face::Regular);
+                    if self.options.running_headers && !page.opening {
+                        let recto = number % 2 == 1;
+                        let label = win_ansi(if recto { &page.chapter } else { book_title });
+                        let label_width = measure(&label, Face::Italic);
+                        ops.push_str(&text_op(
+                            Face::Italic,
+                            size,
+                            (width - label_width) / 2.0,
+                            header_y,
+                            &label,
+                        ));
+                        let folio_x = if recto {
+                            width - outer - folio_width
+                        } else {
+                            outer
+                        };
+                        ops.push_str(&text_op(Face::Regular, size, folio_x, header_y, &folio));
+                    } else {
+                        ops.push_str(&text_op(
+                            Face::Regular,
+                            size,
+                            (width - folio_width) / 2.0,
+                            footer_y,
+                            &folio,
+                        ));
+                    }
+                }
+                ops.push_str(&page.content);
+                ops
+            })
+            .collect()
+    }
+}
+
+fn text_op(face: Face, size: f64, x: f64, y: f64, bytes: &[u8]) -> String {
+    format!(
+        "BT /{} {} Tf 1 0 0 1 {} {} Tm {} Tj ET\n",
+        face.resource(),
+        num(size),
+        num(x),
+        num(y),
+        literal(bytes)
+    )
+}
+
+/// Thin filled rectangle for underline and strikethrough.
+fn rule(x: f64, y: f64, width: f64, size: f64) -> String {
+    format!(
+        "{} {} {} {} re f\n",
+        num(x),
+        num(y),
+        num(width),
+        num(size * 0.05)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::html::parse;
+    use crate::export::CompiledChapter;
+    use crate::models::ChapterNumbering;
+    use chrono::TimeZone;
+    use std::path::Path;
+
+    fn sample() -> Manuscript {
+        let chapter = |title: &str, level: u8, html: &str| CompiledChapter {
+            id: title.to_string(),
+            title: title.to_string(),
+            depth: level as usize - 1,
+            heading_level: Some(level),
+            blocks: parse(html),
+            word_count: 0,
+        };
+        Manuscript {
+            id: uuid::Uuid::nil(),
+            title: "Night Train".to_string(),
+            author: "Jane Doe".to_string(),
+            updated_at: chrono::Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap(),
+            chapters: vec![
+                chapter(
+                    "Chapter 1: Departure",
+                    1,
+                    "<p>The train left at <em>midnight</em>, carrying <strong>everyone</strong> \
+                     she had ever cared about into a fog that swallowed the platform lights one \
+                     by one until nothing remained but the hum of the rails.</p>\
+                     <p>She counted the carriages. <u>Seven.</u> Always seven.</p>\
+                     <hr><p>Morning came late \u{2014} \u{201c}too late,\u{201d} said the guard.</p>",
+                ),
+                chapter(
+                    "Chapter 2: Arrival",
+                    1,
+                    "<blockquote><p>No one waits at the end of the line.</p></blockquote>\
+                     <ul><li><p>A ticket</p></li><li><p>A key</p></li></ul>",
+                ),
+            ],
+        }
+    }
+
+    fn preset() -> CompilePreset {
+        CompilePreset {
+            numbering: ChapterNumbering::Arabic,
+            back_matter: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pdf_matches_golden_file() {
+        let bytes = build_pdf(&sample(), &preset());
+        let golden = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/export/testdata/proof.pdf");
+
+        // Regenerate with `UPDATE_GOLDEN=1 cargo test` after intended layout changes
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&golden, &bytes).expect("Failed to write golden file");
+        }
+        let expected = std::fs::read(&golden).expect("Failed to read golden file");
+        assert!(
+            bytes == expected,
+            "PDF output differs from {}",
+            golden.display()
+        );
+    }
+
+    #[test]
+    fn test_pdf_structure() {
+        let bytes = build_pdf(&sample(), &preset());
+        // Byte-for-byte view so that offsets stay valid
+        let text: String = bytes
+            .iter()
+            .map(|&b| if b.is_ascii() { b as char } else { '?' })
+            .collect();
+
+        // Title page plus one opening page per chapter
+        assert!(text.contains("/Count 3 "));
+        assert!(text.contains("/BaseFont /Times-Italic"));
+        assert!(text.contains("(Arrival) Tj"));
+        assert!(text.contains("(\\223too) Tj"));
+
+        // Every cross-reference entry points at its object
+        let startxref = text.rfind("startxref\n").expect("Missing startxref");
+        let xref: usize = text[startxref + 10..]
+            .lines()
+            .next()
+            .and_then(|l| l.parse().ok())
+            .expect("Invalid startxref");
+        assert!(text[xref..].starts_with("xref"));
+        for (id, entry) in text[xref..]
+            .lines()
+            .skip(3)
+            .take_while(|l| l.ends_with(" n "))
+            .enumerate()
+        {
+            let offset: usize = entry[..10].parse().expect("Invalid offset");
+            assert!(text[offset..].starts_with(&format!("{} 0 obj", id + 1)));
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! Low-level PDF serialization: objects, cross-reference table, string encoding.
+//!
+//! Everything is written uncompressed and in a fixed object order, so the same
+//! input always yields byte-identical output.
+
+use chrono::{DateTime, Utc};
+
+/// Document-level data shared by all pages.
+pub struct DocumentInfo<'a> {
+    pub title: &'a str,
+    pub author: &'a str,
+    pub modified: DateTime<Utc>,
+    /// Base fonts available on every page, in resource order (`/F1`, `/F2`, ...).
+    pub fonts: &'a [&'static str],
+    pub page_size: (f64, f64),
+}
+
+/// Serializes pages (already rendered content streams) into a PDF file.
+pub fn write_document(info: &DocumentInfo, pages: &[String]) -> Vec<u8> {
+    let mut writer = ObjectWriter::new();
+
+    // Fixed layout: 1 catalog, 2 page tree, 3 info, 4 resources, fonts, then
+    // a (page, content) pair per page.
+    let first_font = 5;
+    let first_page = first_font + info.fonts.len();
+    let page_id = |i: usize| first_page + i * 2;
+
+    writer.object(1, "<< /Type /Catalog /Pages 2 0 R >>");
+
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", page_id(i)))
+        .collect();
+    writer.object(
+        2,
+        &format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        ),
+    );
+
+    writer.object(
+        3,
+        &format!(
+            "<< /Producer (BrutWrite) /Title {} /Author {} /CreationDate ({}) /ModDate ({}) >>",
+            text_string(info.title),
+            text_string(info.author),
+            pdf_date(info.modified),
+            pdf_date(info.modified)
+        ),
+    );
+
+    let font_refs: Vec<String> = (0..info.fonts.len())
+        .map(|i| format!("/F{} {} 0 R", i + 1, first_font + i))
+        .collect();
+    writer.object(4, &format!("<< /Font << {} >> >>", font_refs.join(" ")));
+
+    for (i, base_font) in info.fonts.iter().enumerate() {
+        writer.object(
+            first_font + i,
+            &format!(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                base_font
+            ),
+        );
+    }
+
+    let (width, height) = info.page_size;
+    for (i, content) in pages.iter().enumerate() {
+        writer.object(
+            page_id(i),
+            &format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources 4 0 R /Contents {} 0 R >>",
+                num(width),
+                num(height),
+                page_id(i) + 1
+            ),
+        );
+        writer.stream(page_id(i) + 1, content.as_bytes());
+    }
+
+    writer.finish(1, 3)
+}
+
+struct ObjectWriter {
+    buf: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl ObjectWriter {
+    fn new() -> Self {
+        let mut buf = Vec::new();
+        // Binary comment marks the file as 8-bit for transfer tools
+        buf.extend_from_slice(b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n");
+        Self {
+            buf,
+            offsets: Vec::new(),
+        }
+    }
+
+    fn begin(&mut self, id: usize) {
+        debug_assert_eq!(
+            id,
+            self.offsets.len() + 1,
+            "objects must be written in order"
+        );
+        self.offsets.push(self.buf.len());
+        self.buf
+            .extend_from_slice(format!("{} 0 obj\n", id).as_bytes());
+    }
+
+    fn object(&mut self, id: usize, body: &str) {
+        self.begin(id);
+        self.buf.extend_from_slice(body.as_bytes());
+        self.buf.extend_from_slice(b"\nendobj\n");
+    }
+
+    fn stream(&mut self, id: usize, data: &[u8]) {
+        self.begin(id);
+        self.buf
+            .extend_from_slice(format!("<< /Length {} >>\nstream\n", data.len()).as_bytes());
+        self.buf.extend_from_slice(data);
+        self.buf.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+
+    fn finish(mut self, root: usize, info: usize) -> Vec<u8> {
+        let xref = self.buf.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for offset in &self.offsets {
+            table.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        table.push_str(&format!(
+            "trailer\n<< /Size {} /Root {} 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.offsets.len() + 1,
+            root,
+            info,
+            xref
+        ));
+        self.buf.extend_from_slice(table.as_bytes());
+        self.buf
+    }
+}
+
+/// Formats a coordinate with at most two decimals.
+pub fn num(value: f64) -> String {
+    let rounded = (value * 100.0).round() / 100.0;
+    let text = format!("{:.2}", rounded);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Encodes text for the WinAnsiEncoding fonts; unmappable characters become `?`.
+pub fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            ' '..='~' => c as u8,
+            '\u{a0}'..='\u{ff}' => c as u32 as u8,
+            '\u{20ac}' => 0x80,
+            '\u{201a}' => 0x82,
+            '\u{0192}' => 0x83,
+            '\u{201e}' => 0x84,
+            '\u{2026}' => 0x85,
+            '\u{2020}' => 0x86,
+            '\u{2021}' => 0x87,
+            '\u{02c6}' => 0x88,
+            '\u{2030}' => 0x89,
+            '\u{0160}' => 0x8a,
+            '\u{2039}' => 0x8b,
+            '\u{0152}' => 0x8c,
+            '\u{017d}' => 0x8e,
+            '\u{2018}' => 0x91,
+            '\u{2019}' => 0x92,
+            '\u{201c}' => 0x93,
+            '\u{201d}' => 0x94,
+            '\u{2022}' => 0x95,
+            '\u{2013}' => 0x96,
+            '\u{2014}' => 0x97,
+            '\u{02dc}' => 0x98,
+            '\u{2122}' => 0x99,
+            '\u{0161}' => 0x9a,
+            '\u{203a}' => 0x9b,
+            '\u{0153}' => 0x9c,
+            '\u{017e}' => 0x9e,
+            '\u{0178}' => 0x9f,
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// Literal string for a content stream. Bytes outside ASCII are octal-escaped so
+/// the content stays readable (and diffable) as text.
+pub fn literal(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() + 2);
+    out.push('(');
+    for &b in bytes {
+        match b {
+            b'(' | b')' | b'\\' => {
+                out.push('\\');
+                out.push(b as char);
+            }
+            0x20..=0x7e => out.push(b as char),
+            _ => out.push_str(&format!("\\{:03o}", b)),
+        }
+    }
+    out.push(')');
+    out
+}
+
+/// Text string for the document information dictionary (UTF-16BE with BOM).
+fn text_string(text: &str) -> String {
+    let mut out = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        out.push_str(&format!("{:04X}", unit));
+    }
+    out.push('>');
+    out
+}
+
+fn pdf_date(date: DateTime<Utc>) -> String {
+    date.format("D:%Y%m%d%H%M%SZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_encoding() {
+        assert_eq!(num(12.0), "12");
+        assert_eq!(num(-0.001), "0");
+        assert_eq!(num(1.23456), "1.23");
+        assert_eq!(win_ansi("a\u{2014}é\u{4e2d}"), vec![b'a', 0x97, 0xe9, b'?']);
+        assert_eq!(literal(&win_ansi("(x) \u{201c}")), "(\\(x\\) \\223)");
+    }
+}
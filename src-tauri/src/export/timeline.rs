@@ -0,0 +1,410 @@
+//! Timeline writers.
+//!
+//! Exports the temporal metadata of manifest nodes. The iCalendar file holds one
+//! `VEVENT` per node whose `chronological_date` parses; the CSV lists every node in
+//! reading order with all of its temporal fields, dated or not.
+//!
+//! Dates are accepted as RFC 3339 (stored by the timeline view, written as UTC),
+//! as a local date-time without offset (written as floating time) or as a plain
+//! date (written as an all-day event). Durations follow the timeline view's
+//! reading: a number and a unit, months counted as 30 days and years as 365.
+
+use super::csv;
+use super::manuscript::reading_order;
+use crate::models::{Chapter, ProjectMetadata};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Output formats supported by `export_timeline`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineFormat {
+    /// iCalendar (RFC 5545), one event per dated scene.
+    Ics,
+    Csv,
+}
+
+/// A parsed `chronological_date`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SceneTime {
+    Utc(DateTime<Utc>),
+    Floating(NaiveDateTime),
+    Date(NaiveDate),
+}
+
+impl SceneTime {
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+            return Some(Self::Utc(dt.with_timezone(&Utc)));
+        }
+        for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"] {
+            if let Ok(dt) = NaiveDateTime::parse_from_str(value, format) {
+                return Some(Self::Floating(dt));
+            }
+        }
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .map(Self::Date)
+    }
+
+    /// End of a scene lasting `duration`. All-day events always span at least
+    /// one day, since iCalendar end dates are exclusive.
+    pub fn end(self, duration: Option<Duration>) -> Option<Self> {
+        match self {
+            Self::Utc(start) => duration.and_then(|d| start.checked_add_signed(d).map(Self::Utc)),
+            Self::Floating(start) => {
+                duration.and_then(|d| start.checked_add_signed(d).map(Self::Floating))
+            }
+            Self::Date(start) => {
+                let days = duration.map_or(0, |d| d.num_days()).max(1);
+                start
+                    .checked_add_signed(Duration::days(days))
+                    .map(Self::Date)
+            }
+        }
+    }
+
+    fn to_ics(self, name: &str) -> String {
+        match self {
+            Self::Utc(dt) => format!("{}:{}", name, dt.format("%Y%m%dT%H%M%SZ")),
+            Self::Floating(dt) => format!("{}:{}", name, dt.format("%Y%m%dT%H%M%S")),
+            Self::Date(date) => format!("{};VALUE=DATE:{}", name, date.format("%Y%m%d")),
+        }
+    }
+
+    fn to_iso(self) -> String {
+        match self {
+            Self::Utc(dt) => dt.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            Self::Floating(dt) => dt.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            Self::Date(date) => date.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+/// Parses an in-world duration such as "2 hours" or "1.5 days". Returns `None`
+/// when the unit is missing or the amount is not positive.
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let lower = value.trim().to_lowercase();
+    let number_len = lower
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(lower.len());
+    let amount: f64 = lower[..number_len].parse().ok()?;
+    if amount <= 0.0 {
+        return None;
+    }
+
+    const MINUTE: f64 = 60.0;
+    const HOUR: f64 = 60.0 * MINUTE;
+    const DAY: f64 = 24.0 * HOUR;
+    // Checked in this order so that "minutes" is not read as "month"
+    let unit = [
+        ("min", MINUTE),
+        ("hour", HOUR),
+        ("week", 7.0 * DAY),
+        ("month", 30.0 * DAY),
+        ("year", 365.0 * DAY),
+        ("day", DAY),
+    ]
+    .into_iter()
+    .find(|(name, _)| lower.contains(name))
+    .map(|(_, seconds)| seconds)?;
+
+    Duration::try_seconds((amount * unit).round() as i64)
+}
+
+/// Renders the timeline in the requested format.
+pub fn build_timeline(metadata: &ProjectMetadata, format: TimelineFormat) -> String {
+    match format {
+        TimelineFormat::Ics => build_ics(metadata),
+        TimelineFormat::Csv => build_csv(metadata),
+    }
+}
+
+fn build_ics(metadata: &ProjectMetadata) -> String {
+    // Stamped with the project's last change so unchanged projects export identically
+    let stamp = metadata.updated_at.format("%Y%m%dT%H%M%SZ");
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//BrutWrite//Timeline//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape_ics(&metadata.title)),
+    ];
+
+    for entry in reading_order(&metadata.manifest) {
+        let chapter = entry.chapter;
+        let Some(start) = chapter
+            .chronological_date
+            .as_deref()
+            .and_then(SceneTime::parse)
+        else {
+            continue;
+        };
+        let end = start.end(chapter.duration.as_deref().and_then(parse_duration));
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}-{}@brutwrite", metadata.id, chapter.id));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(start.to_ics("DTSTART"));
+        if let Some(end) = end {
+            lines.push(end.to_ics("DTEND"));
+        }
+        lines.push(format!("SUMMARY:{}", escape_ics(&chapter.title)));
+
+        let details = details(metadata, chapter);
+        if !details.is_empty() {
+            let text = details
+                .iter()
+                .map(|(label, value)| format!("{}: {}", label, value))
+                .collect::<Vec<_>>()
+                .join("\n");
+            lines.push(format!("DESCRIPTION:{}", escape_ics(&text)));
+        }
+        if let Some(plotline) = plotline_name(metadata, chapter) {
+            lines.push(format!("CATEGORIES:{}", escape_ics(&plotline)));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+fn build_csv(metadata: &ProjectMetadata) -> String {
+    let mut out = csv::record(&[
+        "position",
+        "id",
+        "title",
+        "depth",
+        "chronological_date",
+        "end_date",
+        "abstract_timeframe",
+        "duration",
+        "plotline_tag",
+        "plotline",
+        "pov_character_id",
+        "pov_character",
+        "depends_on",
+    ]);
+
+    for (index, entry) in reading_order(&metadata.manifest).iter().enumerate() {
+        let chapter = entry.chapter;
+        let end = chapter
+            .chronological_date
+            .as_deref()
+            .and_then(SceneTime::parse)
+            .and_then(|start| start.end(chapter.duration.as_deref().and_then(parse_duration)))
+            .map(SceneTime::to_iso)
+            .unwrap_or_default();
+
+        out.push_str(&csv::record(&[
+            (index + 1).to_string(),
+            chapter.id.clone(),
+            chapter.title.clone(),
+            entry.depth.to_string(),
+            chapter.chronological_date.clone().unwrap_or_default(),
+            end,
+            chapter.abstract_timeframe.clone().unwrap_or_default(),
+            chapter.duration.clone().unwrap_or_default(),
+            chapter.plotline_tag.clone().unwrap_or_default(),
+            plotline_name(metadata, chapter).unwrap_or_default(),
+            chapter.pov_character_id.clone().unwrap_or_default(),
+            pov_name(metadata, chapter).unwrap_or_default(),
+            chapter.depends_on.clone().unwrap_or_default(),
+        ]));
+    }
+    out
+}
+
+fn plotline_name(metadata: &ProjectMetadata, chapter: &Chapter) -> Option<String> {
+    let tag = chapter.plotline_tag.as_deref()?;
+    Some(
+        metadata
+            .plotline(tag)
+            .map_or_else(|| tag.to_string(), |p| p.name.clone()),
+    )
+}
+
+fn pov_name(metadata: &ProjectMetadata, chapter: &Chapter) -> Option<String> {
+    let id = chapter.pov_character_id.as_deref()?;
+    metadata.character(id).map(|c| c.name.clone())
+}
+
+/// Event description lines: the fields a calendar has no slot for.
+fn details(metadata: &ProjectMetadata, chapter: &Chapter) -> Vec<(&'static str, String)> {
+    let mut details = Vec::new();
+    if let Some(timeframe) = chapter
+        .abstract_timeframe
+        .as_deref()
+        .filter(|t| !t.is_empty())
+    {
+        details.push(("Timeframe", timeframe.to_string()));
+    }
+    if let Some(duration) = chapter.duration.as_deref().filter(|d| !d.is_empty()) {
+        details.push(("Duration", duration.to_string()));
+    }
+    if let Some(plotline) = plotline_name(metadata, chapter) {
+        details.push(("Plotline", plotline));
+    }
+    if let Some(pov) = pov_name(metadata, chapter) {
+        details.push(("POV", pov));
+    }
+    details
+}
+
+/// Escapes TEXT values (RFC 5545 section 3.3.11).
+fn escape_ics(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Folds a content line at 75 octets without splitting a UTF-8 sequence, and
+/// terminates it with CRLF.
+fn fold_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            // The leading space of a continuation line counts towards its length
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Character, CharacterRole, Manifest};
+    use uuid::Uuid;
+
+    fn scene(id: &str, order: u32, date: Option<&str>, duration: Option<&str>) -> Chapter {
+        Chapter {
+            id: id.to_string(),
+            parent_id: None,
+            title: format!("Scene {}", id),
+            filename: format!("{}.md", id),
+            word_count: 0,
+            order,
+            chronological_date: date.map(String::from),
+            abstract_timeframe: None,
+            duration: duration.map(String::from),
+            plotline_tag: None,
+            depends_on: None,
+            pov_character_id: None,
+            synopsis: None,
+        }
+    }
+
+    fn sample() -> ProjectMetadata {
+        let mut metadata = ProjectMetadata::new("Tales".to_string(), "Jane Doe".to_string());
+        metadata.id = Uuid::nil();
+        metadata.characters = vec![Character {
+            id: Uuid::from_u128(1),
+            name: "Alice".to_string(),
+            role: CharacterRole::Protagonist,
+            archetype: String::new(),
+            description: String::new(),
+            engine: Default::default(),
+            physical_features: String::new(),
+            traits: vec![],
+            arc: String::new(),
+            notes: String::new(),
+            aliases: vec![],
+        }];
+
+        let mut first = scene("a", 0, Some("2024-03-01T10:00:00.000Z"), Some("2 hours"));
+        first.title = "Arrival; at dawn, finally".to_string();
+        first.plotline_tag = Some("main".to_string());
+        first.pov_character_id = Some(Uuid::from_u128(1).to_string());
+        first.abstract_timeframe = Some("Day 1".to_string());
+
+        metadata.manifest = Manifest::new(vec![
+            scene("c", 2, Some("2024-03-05"), Some("3 days")),
+            first,
+            scene("b", 1, None, Some("1 day")),
+            scene("d", 3, Some("2024-03-06T21:30"), None),
+        ]);
+        metadata
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("2 hours"), Some(Duration::hours(2)));
+        assert_eq!(parse_duration("1.5 days"), Some(Duration::hours(36)));
+        assert_eq!(parse_duration("45 minutes"), Some(Duration::minutes(45)));
+        assert_eq!(parse_duration("2 Months"), Some(Duration::days(60)));
+        assert_eq!(parse_duration("3"), None);
+        assert_eq!(parse_duration("a while"), None);
+        assert_eq!(parse_duration("0 days"), None);
+    }
+
+    #[test]
+    fn test_parse_scene_time() {
+        assert!(matches!(
+            SceneTime::parse("2024-03-01T10:00:00+02:00"),
+            Some(SceneTime::Utc(dt)) if dt.to_rfc3339() == "2024-03-01T08:00:00+00:00"
+        ));
+        assert!(matches!(
+            SceneTime::parse("2024-03-01T10:00"),
+            Some(SceneTime::Floating(_))
+        ));
+        assert!(matches!(
+            SceneTime::parse("2024-03-01"),
+            Some(SceneTime::Date(_))
+        ));
+        assert_eq!(SceneTime::parse("Day 3"), None);
+    }
+
+    #[test]
+    fn test_ics_events() {
+        let ics = build_timeline(&sample(), TimelineFormat::Ics);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 3);
+        assert!(ics.lines().all(|l| l.len() <= 75));
+
+        assert!(ics.contains("DTSTART:20240301T100000Z\r\nDTEND:20240301T120000Z\r\n"));
+        assert!(ics.contains("SUMMARY:Arrival\\; at dawn\\, finally\r\n"));
+        assert!(ics.contains("CATEGORIES:Main Plot\r\n"));
+        assert!(ics.contains("UID:00000000-0000-0000-0000-000000000000-a@brutwrite\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20240305\r\nDTEND;VALUE=DATE:20240308\r\n"));
+        // Floating time without duration has no end
+        assert!(ics.contains("DTSTART:20240306T213000\r\nSUMMARY:Scene d\r\n"));
+
+        let unfolded = ics.replace("\r\n ", "");
+        assert!(unfolded
+            .contains("DESCRIPTION:Timeframe: Day 1\\nDuration: 2 hours\\nPlotline: Main Plot\\nPOV: Alice\r\n"));
+    }
+
+    #[test]
+    fn test_csv_rows_in_reading_order() {
+        let csv = build_timeline(&sample(), TimelineFormat::Csv);
+        let rows: Vec<&str> = csv.split("\r\n").collect();
+
+        assert!(rows[0].starts_with("position,id,title,depth,chronological_date,end_date,"));
+        assert_eq!(
+            rows[1],
+            "1,a,\"Arrival; at dawn, finally\",0,2024-03-01T10:00:00.000Z,2024-03-01T12:00:00Z,Day 1,2 hours,main,Main Plot,00000000-0000-0000-0000-000000000001,Alice,"
+        );
+        assert_eq!(rows[2], "2,b,Scene b,0,,,,1 day,,,,,");
+        assert!(rows[3].starts_with("3,c,Scene c,0,2024-03-05,2024-03-08,"));
+        assert_eq!(rows.len(), 6);
+    }
+}
@@ -0,0 +1,92 @@
+//! ZIP container reader shared by the package-based importers (DOCX, EPUB).
+
+use crate::errors::{Error, Result};
+use std::io::{Cursor, Read};
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+/// Largest entry read, uncompressed. A few kilobytes of deflated zeros can
+/// claim gigabytes.
+const MAX_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+
+/// In-memory ZIP archive opened for reading.
+pub struct Archive {
+    zip: ZipArchive<Cursor<Vec<u8>>>,
+    max_entry_size: u64,
+}
+
+impl Archive {
+    /// Opens `bytes` as an archive; `kind` names the expected format in errors.
+    pub fn open(bytes: Vec<u8>, kind: &str) -> Result<Self> {
+        let zip = ZipArchive::new(Cursor::new(bytes))
+            .map_err(|e| Error::Import(format!("Not a valid {} file: {}", kind, e)))?;
+        Ok(Self {
+            zip,
+            max_entry_size: MAX_ENTRY_SIZE,
+        })
+    }
+
+    /// Reads a UTF-8 entry, or `None` when the archive has no such entry.
+    pub fn read_optional(&mut self, name: &str) -> Result<Option<String>> {
+        let mut file = match self.zip.by_name(name) {
+            Ok(file) => file,
+            Err(ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(Error::Import(format!("Failed to read `{}`: {}", name, e))),
+        };
+        // The declared size is checked first, then enforced while reading in
+        // case it lies
+        let too_large = || {
+            Error::Validation(format!(
+                "`{}` is larger than {} MB",
+                name,
+                self.max_entry_size / (1024 * 1024)
+            ))
+        };
+        let declared = file.size();
+        if declared > self.max_entry_size {
+            return Err(too_large());
+        }
+        let mut bytes = Vec::with_capacity(declared as usize);
+        (&mut file).take(declared + 1).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 > declared {
+            return Err(too_large());
+        }
+        String::from_utf8(bytes)
+            .map(Some)
+            .map_err(|_| Error::Import(format!("`{}` is not valid UTF-8", name)))
+    }
+
+    /// Reads a UTF-8 entry that the format requires.
+    pub fn read(&mut self, name: &str) -> Result<String> {
+        self.read_optional(name)?
+            .ok_or_else(|| Error::Import(format!("Missing `{}`", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn archive(entries: &[(&str, &str)]) -> Archive {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        Archive::open(writer.finish().unwrap().into_inner(), "test").unwrap()
+    }
+
+    #[test]
+    fn test_refuses_oversized_entries() {
+        let mut archive = archive(&[("small.xml", "<a/>"), ("big.xml", "<a>0123456789</a>")]);
+        archive.max_entry_size = 10;
+
+        assert_eq!(archive.read("small.xml").unwrap(), "<a/>");
+        assert!(matches!(archive.read("big.xml"), Err(Error::Validation(_))));
+        assert!(archive.read_optional("missing.xml").unwrap().is_none());
+    }
+}
@@ -0,0 +1,370 @@
+//! Character table reader.
+//!
+//! Reads the CSV or JSON written by `export::characters` (or by hand) into
+//! partial records and applies them to the project's cast. Every record is
+//! checked before anything changes: names are required for new characters and
+//! roles must be one of the `CharacterRole` names. In merge mode a record updates
+//! the character with the same id, else the same name (ignoring case), and only
+//! the fields it sets; otherwise every record adds a new character.
+
+use super::csv;
+use crate::errors::{Error, Result};
+use crate::export::characters::{role_name, CharacterFormat, COLUMNS, LIST_SEPARATOR};
+use crate::models::{Character, CharacterRole, ProjectMetadata};
+use serde::Deserialize;
+use uuid::Uuid;
+
+/// One imported character; `None` fields are left untouched when merging.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CharacterRecord {
+    pub id: Option<Uuid>,
+    pub name: Option<String>,
+    pub role: Option<CharacterRole>,
+    pub archetype: Option<String>,
+    pub description: Option<String>,
+    pub physical_features: Option<String>,
+    pub traits: Option<Vec<String>>,
+    pub arc: Option<String>,
+    pub notes: Option<String>,
+    pub aliases: Option<Vec<String>>,
+    pub engine: Option<EngineRecord>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct EngineRecord {
+    pub desire: Option<String>,
+    pub fear: Option<String>,
+    pub wound: Option<String>,
+    pub secret: Option<String>,
+}
+
+/// Counts of an applied import.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MergeSummary {
+    pub created: usize,
+    pub updated: usize,
+}
+
+pub fn parse_records(text: &str, format: CharacterFormat) -> Result<Vec<CharacterRecord>> {
+    match format {
+        CharacterFormat::Json => serde_json::from_str(text)
+            .map_err(|e| Error::Validation(format!("Invalid character JSON: {}", e))),
+        CharacterFormat::Csv => parse_csv(text),
+    }
+}
+
+fn parse_csv(text: &str) -> Result<Vec<CharacterRecord>> {
+    let mut rows = csv::parse(text)?.into_iter();
+    let Some(header) = rows.next() else {
+        return Ok(Vec::new());
+    };
+
+    // Field name for each column; unknown columns are ignored
+    let header: Vec<Option<&str>> = header
+        .iter()
+        .map(|name| {
+            let name = name.trim().to_ascii_lowercase();
+            COLUMNS.iter().copied().find(|column| *column == name)
+        })
+        .collect();
+    if !header.contains(&Some("name")) {
+        return Err(Error::Validation(
+            "Character CSV needs a `name` column".to_string(),
+        ));
+    }
+
+    rows.enumerate()
+        .map(|(index, row)| {
+            // Line 1 is the header
+            let line = index + 2;
+            let mut record = CharacterRecord::default();
+            let mut engine = EngineRecord::default();
+
+            for (column, value) in header.iter().zip(row) {
+                let value = value.trim();
+                let Some(column) = column.filter(|_| !value.is_empty()) else {
+                    continue;
+                };
+                let text = Some(value.to_string());
+                match column {
+                    "id" => {
+                        let id = Uuid::parse_str(value).map_err(|_| {
+                            Error::Validation(format!("Line {}: invalid id `{}`", line, value))
+                        })?;
+                        record.id = Some(id);
+                    }
+                    "name" => record.name = text,
+                    "role" => {
+                        record.role = Some(parse_role(value).ok_or_else(|| {
+                            Error::Validation(format!(
+                                "Line {}: unknown role `{}` (expected {})",
+                                line,
+                                value,
+                                role_names()
+                            ))
+                        })?)
+                    }
+                    "archetype" => record.archetype = text,
+                    "description" => record.description = text,
+                    "physical_features" => record.physical_features = text,
+                    "traits" => record.traits = Some(split_list(value)),
+                    "arc" => record.arc = text,
+                    "notes" => record.notes = text,
+                    "aliases" => record.aliases = Some(split_list(value)),
+                    "desire" => engine.desire = text,
+                    "fear" => engine.fear = text,
+                    "wound" => engine.wound = text,
+                    "secret" => engine.secret = text,
+                    _ => {}
+                }
+            }
+
+            if engine != EngineRecord::default() {
+                record.engine = Some(engine);
+            }
+            Ok(record)
+        })
+        .collect()
+}
+
+const ROLES: [CharacterRole; 4] = [
+    CharacterRole::Protagonist,
+    CharacterRole::Antagonist,
+    CharacterRole::Secondary,
+    CharacterRole::Extra,
+];
+
+fn parse_role(value: &str) -> Option<CharacterRole> {
+    ROLES
+        .into_iter()
+        .find(|role| role_name(role).eq_ignore_ascii_case(value))
+}
+
+fn role_names() -> String {
+    ROLES.iter().map(role_name).collect::<Vec<_>>().join(", ")
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(LIST_SEPARATOR.trim())
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Applies records to the project's characters. Nothing changes if any record
+/// is invalid.
+pub fn apply_records(
+    metadata: &mut ProjectMetadata,
+    records: Vec<CharacterRecord>,
+    merge: bool,
+) -> Result<MergeSummary> {
+    let mut characters = metadata.characters.clone();
+    let mut summary = MergeSummary::default();
+
+    for (index, record) in records.into_iter().enumerate() {
+        let existing = if merge {
+            find_match(&characters, &record)
+        } else {
+            None
+        };
+
+        match existing {
+            Some(position) => {
+                record.apply_to(&mut characters[position]);
+                summary.updated += 1;
+            }
+            None => {
+                let name = record.name.as_deref().map(str::trim).unwrap_or_default();
+                if name.is_empty() {
+                    return Err(Error::Validation(format!(
+                        "Character {} has no name",
+                        index + 1
+                    )));
+                }
+                // Ids must stay unique, so a taken id is replaced
+                let id = record
+                    .id
+                    .filter(|id| !characters.iter().any(|c| c.id == *id))
+                    .unwrap_or_else(Uuid::new_v4);
+                let mut character = Character {
+                    id,
+                    name: String::new(),
+                    role: CharacterRole::Secondary,
+                    archetype: String::new(),
+                    description: String::new(),
+                    engine: Default::default(),
+                    physical_features: String::new(),
+                    traits: Vec::new(),
+                    arc: String::new(),
+                    notes: String::new(),
+                    aliases: Vec::new(),
+                };
+                record.apply_to(&mut character);
+                characters.push(character);
+                summary.created += 1;
+            }
+        }
+    }
+
+    metadata.characters = characters;
+    metadata.reindex();
+    Ok(summary)
+}
+
+/// Position of the character a record refers to: same id, else same name.
+fn find_match(characters: &[Character], record: &CharacterRecord) -> Option<usize> {
+    if let Some(position) = record
+        .id
+        .and_then(|id| characters.iter().position(|c| c.id == id))
+    {
+        return Some(position);
+    }
+    let name = record.name.as_deref()?.trim().to_lowercase();
+    characters
+        .iter()
+        .position(|c| c.name.trim().to_lowercase() == name)
+}
+
+impl CharacterRecord {
+    fn apply_to(self, character: &mut Character) {
+        let set = |field: &mut String, value: Option<String>| {
+            if let Some(value) = value {
+                *field = value;
+            }
+        };
+        if let Some(name) = self.name.filter(|n| !n.trim().is_empty()) {
+            character.name = name.trim().to_string();
+        }
+        if let Some(role) = self.role {
+            character.role = role;
+        }
+        set(&mut character.archetype, self.archetype);
+        set(&mut character.description, self.description);
+        set(&mut character.physical_features, self.physical_features);
+        set(&mut character.arc, self.arc);
+        set(&mut character.notes, self.notes);
+        if let Some(traits) = self.traits {
+            character.traits = traits;
+        }
+        if let Some(aliases) = self.aliases {
+            character.aliases = aliases;
+        }
+        if let Some(engine) = self.engine {
+            set(&mut character.engine.desire, engine.desire);
+            set(&mut character.engine.fear, engine.fear);
+            set(&mut character.engine.wound, engine.wound);
+            set(&mut character.engine.secret, engine.secret);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::characters::build_characters;
+
+    fn project() -> ProjectMetadata {
+        let mut metadata = ProjectMetadata::new("Tales".to_string(), "Jane".to_string());
+        let mut records = parse_records(
+            "id,name,role,traits\n00000000-0000-0000-0000-000000000001,Alice,protagonist,bold; kind\n",
+            CharacterFormat::Csv,
+        )
+        .unwrap();
+        records[0].description = Some("Curious".to_string());
+        apply_records(&mut metadata, records, false).unwrap();
+        metadata
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let metadata = project();
+        let csv = build_characters(&metadata.characters, CharacterFormat::Csv).unwrap();
+
+        let mut copy = ProjectMetadata::new("Copy".to_string(), "Jane".to_string());
+        apply_records(
+            &mut copy,
+            parse_records(&csv, CharacterFormat::Csv).unwrap(),
+            false,
+        )
+        .unwrap();
+
+        let alice = &copy.characters[0];
+        assert_eq!(alice.id, Uuid::from_u128(1));
+        assert_eq!(alice.role, CharacterRole::Protagonist);
+        assert_eq!(alice.traits, vec!["bold", "kind"]);
+        assert_eq!(alice.description, "Curious");
+    }
+
+    #[test]
+    fn test_merge_by_name_keeps_unset_fields() {
+        let mut metadata = project();
+        let records = parse_records(
+            "Name,Role,Aliases,Fear,Unknown\nalice ,,Al; Ally,Spiders,x\nBob,extra,,,\n",
+            CharacterFormat::Csv,
+        )
+        .unwrap();
+
+        let summary = apply_records(&mut metadata, records, true).unwrap();
+        assert_eq!(
+            summary,
+            MergeSummary {
+                created: 1,
+                updated: 1
+            }
+        );
+
+        let alice = &metadata.characters[0];
+        assert_eq!(alice.name, "alice");
+        assert_eq!(alice.role, CharacterRole::Protagonist);
+        assert_eq!(alice.traits, vec!["bold", "kind"]);
+        assert_eq!(alice.aliases, vec!["Al", "Ally"]);
+        assert_eq!(alice.engine.fear, "Spiders");
+        assert_eq!(metadata.characters[1].role, CharacterRole::Extra);
+    }
+
+    #[test]
+    fn test_json_merge_by_id_and_append_mode() {
+        let mut metadata = project();
+        let json = r#"[{"id": "00000000-0000-0000-0000-000000000001", "name": "Alicia", "engine": {"desire": "Home"}}]"#;
+
+        apply_records(
+            &mut metadata,
+            parse_records(json, CharacterFormat::Json).unwrap(),
+            true,
+        )
+        .unwrap();
+        assert_eq!(metadata.characters.len(), 1);
+        assert_eq!(metadata.characters[0].name, "Alicia");
+        assert_eq!(metadata.characters[0].engine.desire, "Home");
+
+        // Without merge the same record is a new character with a fresh id
+        apply_records(
+            &mut metadata,
+            parse_records(json, CharacterFormat::Json).unwrap(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(metadata.characters.len(), 2);
+        assert_ne!(metadata.characters[1].id, Uuid::from_u128(1));
+    }
+
+    #[test]
+    fn test_invalid_records_change_nothing() {
+        let err = parse_records("name,role\nAlice,hero\n", CharacterFormat::Csv).unwrap_err();
+        assert!(err.to_string().contains("Line 2: unknown role `hero`"));
+        assert!(
+            parse_records(r#"[{"name": "A", "role": "hero"}]"#, CharacterFormat::Json).is_err()
+        );
+        assert!(parse_records("role\nextra\n", CharacterFormat::Csv).is_err());
+
+        let mut metadata = project();
+        let records =
+            parse_records("name,role\nBob,extra\n,antagonist\n", CharacterFormat::Csv).unwrap();
+        assert!(apply_records(&mut metadata, records, false).is_err());
+        assert_eq!(metadata.characters.len(), 1);
+    }
+}
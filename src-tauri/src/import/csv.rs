@@ -0,0 +1,83 @@
+//! Minimal RFC 4180 CSV reader, the counterpart of `export::csv`.
+
+use crate::errors::{Error, Result};
+
+/// Parses CSV text into records. Quoted fields may hold commas, doubled quotes
+/// and line breaks; both CRLF and LF end records. Blank lines are skipped.
+pub fn parse(text: &str) -> Result<Vec<Vec<String>>> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = Vec::new();
+    let mut record: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                line += 1;
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|f| !f.is_empty()) || record.len() > 1 {
+                    records.push(std::mem::take(&mut record));
+                } else {
+                    record.clear();
+                }
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(Error::Import(format!(
+            "Unterminated quoted field at line {}",
+            line
+        )));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::csv::record;
+
+    #[test]
+    fn test_parse_round_trips_writer() {
+        let fields = ["x,y", "say \"hi\"", "two\r\nlines", ""];
+        let text = format!("{}\n{}", record(&["a", "b", "c", "d"]), record(&fields));
+        assert_eq!(
+            parse(&text).unwrap(),
+            vec![vec!["a", "b", "c", "d"], fields.to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_quote() {
+        assert!(parse("a,\"b\n").is_err());
+    }
+}
@@ -0,0 +1,349 @@
+//! Word (`.docx`) manuscript reader.
+//!
+//! Paragraphs styled `Heading 1`, `Heading 2` and `Heading 3` start a new node at
+//! depth 0, 1 and 2; everything up to the next such heading becomes that node's
+//! content, with bold, italic, underline and strikethrough kept. Headings are
+//! recognised by the built-in style name, which Word keeps in English whatever the
+//! UI language, or failing that by the style's outline level. Paragraphs made only
+//! of `*`, `#` or `~` (`* * *`, `#`) are read as scene breaks.
+
+use super::archive::Archive;
+use super::xml::{self, Element};
+use super::{is_scene_break, ImportedNode, ImportedProject, ParagraphBuilder, TextFormat};
+use crate::errors::{Error, Result};
+use crate::export::html::{plain_text, render_editor_html, Block, Inline};
+use std::collections::HashMap;
+
+/// Deepest heading level that starts a node; lower headings stay in the text.
+const MAX_NODE_LEVEL: u8 = 3;
+
+/// Reads a `.docx` file. The project is named after the document's title
+/// property, or `fallback_title` (the file name) when it has none.
+pub fn read_docx(bytes: Vec<u8>, fallback_title: &str) -> Result<ImportedProject> {
+    let mut archive = Archive::open(bytes, ".docx")?;
+    let document = xml::parse(&archive.read("word/document.xml")?)?;
+    let headings = match archive.read_optional("word/styles.xml")? {
+        Some(styles) => heading_styles(&xml::parse(&styles)?),
+        None => HashMap::new(),
+    };
+
+    let title = match archive.read_optional("docProps/core.xml")? {
+        Some(core) => xml::parse(&core)?
+            .child("title")
+            .map(|t| t.text().trim().to_string())
+            .filter(|t| !t.is_empty()),
+        None => None,
+    }
+    .unwrap_or_else(|| fallback_title.to_string());
+
+    let body = document
+        .child("body")
+        .ok_or_else(|| Error::Import("The document has no body".to_string()))?;
+
+    Ok(ImportedProject {
+        title,
+        nodes: split_nodes(body, &headings),
+        ..Default::default()
+    })
+}
+
+/// Maps paragraph style IDs to their heading level.
+fn heading_styles(styles: &Element) -> HashMap<String, u8> {
+    styles
+        .children_named("style")
+        .filter(|style| style.attr("type") == Some("paragraph"))
+        .filter_map(|style| {
+            let id = style.attr("styleId")?;
+            let by_name = style
+                .child("name")
+                .and_then(|n| n.attr("val"))
+                .and_then(|name| {
+                    name.to_ascii_lowercase()
+                        .strip_prefix("heading")?
+                        .trim()
+                        .parse::<u8>()
+                        .ok()
+                });
+            let level = by_name.or_else(|| outline_level(style))?;
+            Some((id.to_string(), level))
+        })
+        .collect()
+}
+
+/// `w:outlineLvl` is zero-based; 9 means body text.
+fn outline_level(element: &Element) -> Option<u8> {
+    element
+        .child("pPr")?
+        .child("outlineLvl")?
+        .attr("val")?
+        .parse::<u8>()
+        .ok()
+        .filter(|level| *level < 9)
+        .map(|level| level + 1)
+}
+
+/// A node being filled, with the heading level that opened it.
+struct OpenNode {
+    level: u8,
+    node: ImportedNode,
+    blocks: Vec<Block>,
+}
+
+fn split_nodes(body: &Element, headings: &HashMap<String, u8>) -> Vec<ImportedNode> {
+    let mut paragraphs = Vec::new();
+    collect_paragraphs(body, &mut paragraphs);
+
+    let mut roots = Vec::new();
+    let mut open: Vec<OpenNode> = Vec::new();
+
+    for paragraph in paragraphs {
+        let level = paragraph_level(paragraph, headings);
+        let inlines = paragraph_inlines(paragraph);
+        let text = plain_text(&inlines).trim().to_string();
+
+        match level {
+            Some(level) if level <= MAX_NODE_LEVEL => {
+                if text.is_empty() {
+                    continue;
+                }
+                while open.last().is_some_and(|n| n.level >= level) {
+                    close_node(&mut open, &mut roots);
+                }
+                open.push(OpenNode {
+                    level,
+                    node: ImportedNode {
+                        title: text,
+                        ..Default::default()
+                    },
+                    blocks: Vec::new(),
+                });
+            }
+            _ if inlines.is_empty() => {}
+            level => {
+                if open.is_empty() {
+                    // Text before the first heading
+                    open.push(OpenNode {
+                        level: 1,
+                        node: ImportedNode {
+                            title: "Untitled".to_string(),
+                            ..Default::default()
+                        },
+                        blocks: Vec::new(),
+                    });
+                }
+                let block = match level {
+                    Some(level) => Block::Heading {
+                        level: level.min(6),
+                        content: inlines,
+                    },
+                    None if is_scene_break(&text) => Block::SceneBreak,
+                    None => Block::Paragraph(inlines),
+                };
+                if let Some(current) = open.last_mut() {
+                    current.blocks.push(block);
+                }
+            }
+        }
+    }
+
+    while !open.is_empty() {
+        close_node(&mut open, &mut roots);
+    }
+    roots
+}
+
+/// Finishes the innermost open node and attaches it to its parent.
+fn close_node(open: &mut Vec<OpenNode>, roots: &mut Vec<ImportedNode>) {
+    let Some(OpenNode {
+        mut node, blocks, ..
+    }) = open.pop()
+    else {
+        return;
+    };
+    node.content = render_editor_html(&blocks);
+    match open.last_mut() {
+        Some(parent) => parent.node.children.push(node),
+        None => roots.push(node),
+    }
+}
+
+/// Body paragraphs in document order, including those inside tables and content
+/// controls.
+fn collect_paragraphs<'a>(element: &'a Element, out: &mut Vec<&'a Element>) {
+    for child in element.elements() {
+        match child.name.as_str() {
+            "p" => out.push(child),
+            "tbl" | "tr" | "tc" | "sdt" | "sdtContent" | "customXml" => {
+                collect_paragraphs(child, out)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn paragraph_level(paragraph: &Element, headings: &HashMap<String, u8>) -> Option<u8> {
+    paragraph
+        .child("pPr")
+        .and_then(|p| p.child("pStyle"))
+        .and_then(|s| s.attr("val"))
+        .and_then(|id| headings.get(id).copied())
+        .or_else(|| outline_level(paragraph))
+}
+
+fn paragraph_inlines(paragraph: &Element) -> Vec<Inline> {
+    let mut builder = ParagraphBuilder::default();
+    collect_runs(paragraph, &mut builder);
+    builder.finish()
+}
+
+fn collect_runs(element: &Element, builder: &mut ParagraphBuilder) {
+    for child in element.elements() {
+        match child.name.as_str() {
+            "r" => read_run(child, builder),
+            // Links, tracked insertions, simple fields and other run containers
+            "hyperlink" | "ins" | "smartTag" | "customXml" | "sdt" | "sdtContent" | "fldSimple" => {
+                collect_runs(child, builder)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn read_run(run: &Element, builder: &mut ParagraphBuilder) {
+    let properties = run.child("rPr");
+    let toggle = |name: &str| properties.and_then(|p| p.child(name)).is_some_and(is_on);
+    let format = TextFormat {
+        bold: toggle("b"),
+        italic: toggle("i"),
+        underline: properties
+            .and_then(|p| p.child("u"))
+            .is_some_and(|u| u.attr("val") != Some("none")),
+        strike: toggle("strike") || toggle("dstrike"),
+    };
+
+    for child in run.elements() {
+        match child.name.as_str() {
+            "t" => builder.push_text(&child.text(), format),
+            "tab" => builder.push_text(" ", format),
+            "noBreakHyphen" => builder.push_text("\u{2011}", format),
+            // Page and column breaks carry no meaning in a chapter
+            "br" if child.attr("type").is_none_or(|t| t == "textWrapping") => builder.push_break(),
+            "cr" => builder.push_break(),
+            _ => {}
+        }
+    }
+}
+
+/// On/off properties are on when present, unless `w:val` switches them off.
+fn is_on(property: &Element) -> bool {
+    !matches!(property.attr("val"), Some("0" | "false" | "off"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::package::Package;
+
+    const STYLES: &str = r#"<w:styles xmlns:w="w">
+<w:style w:type="paragraph" w:styleId="Normal"><w:name w:val="Normal"/></w:style>
+<w:style w:type="paragraph" w:styleId="berschrift1"><w:name w:val="heading 1"/></w:style>
+<w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/></w:style>
+<w:style w:type="paragraph" w:styleId="SceneTitle"><w:name w:val="Scene Title"/>
+  <w:pPr><w:outlineLvl w:val="2"/></w:pPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading4"><w:name w:val="heading 4"/></w:style>
+</w:styles>"#;
+
+    fn paragraph(style: Option<&str>, runs: &str) -> String {
+        let style = style
+            .map(|s| format!(r#"<w:pPr><w:pStyle w:val="{}"/></w:pPr>"#, s))
+            .unwrap_or_default();
+        format!("<w:p>{}{}</w:p>", style, runs)
+    }
+
+    fn run(text: &str) -> String {
+        format!(r#"<w:r><w:t xml:space="preserve">{}</w:t></w:r>"#, text)
+    }
+
+    fn docx(body: &[String], core_title: Option<&str>) -> Vec<u8> {
+        let document = format!(
+            r#"<?xml version="1.0"?><w:document xmlns:w="w"><w:body>{}<w:sectPr/></w:body></w:document>"#,
+            body.concat()
+        );
+        let mut package = Package::new();
+        package
+            .add_deflated("word/document.xml", document.as_bytes())
+            .unwrap();
+        package
+            .add_deflated("word/styles.xml", STYLES.as_bytes())
+            .unwrap();
+        if let Some(title) = core_title {
+            let core = format!(
+                r#"<cp:coreProperties xmlns:cp="cp" xmlns:dc="dc"><dc:title>{}</dc:title></cp:coreProperties>"#,
+                title
+            );
+            package
+                .add_deflated("docProps/core.xml", core.as_bytes())
+                .unwrap();
+        }
+        package.finish().unwrap()
+    }
+
+    #[test]
+    fn test_headings_become_nested_nodes() {
+        let bytes = docx(
+            &[
+                paragraph(None, &run("Dedication.")),
+                paragraph(Some("berschrift1"), &run("Part One")),
+                paragraph(Some("Heading2"), &run("Chapter 1")),
+                paragraph(
+                    None,
+                    &format!(
+                        r#"{}<w:r><w:rPr><w:b/><w:i/></w:rPr><w:t>dark</w:t></w:r><w:r><w:rPr><w:b w:val="0"/></w:rPr><w:t xml:space="preserve"> night</w:t></w:r>"#,
+                        run("It was a ")
+                    ),
+                ),
+                paragraph(None, &run("* * *")),
+                paragraph(Some("Heading4"), &run("A letter")),
+                paragraph(None, ""),
+                paragraph(Some("SceneTitle"), &run("Scene 1.1")),
+                paragraph(None, &run("Deep.")),
+                paragraph(Some("Heading2"), &run("Chapter 2")),
+                paragraph(Some("berschrift1"), &run("Part Two")),
+                paragraph(None, &run("End.")),
+            ],
+            Some("The Book"),
+        );
+
+        let project = read_docx(bytes, "fallback").unwrap();
+        assert_eq!(project.title, "The Book");
+
+        let titles: Vec<&str> = project.nodes.iter().map(|n| n.title.as_str()).collect();
+        assert_eq!(titles, vec!["Untitled", "Part One", "Part Two"]);
+        assert_eq!(project.nodes[0].content, "<p>Dedication.</p>\n");
+        assert_eq!(project.nodes[2].content, "<p>End.</p>\n");
+
+        let part_one = &project.nodes[1];
+        assert_eq!(part_one.content, "");
+        assert_eq!(part_one.children.len(), 2);
+
+        let chapter = &part_one.children[0];
+        assert_eq!(chapter.title, "Chapter 1");
+        assert_eq!(
+            chapter.content,
+            "<p>It was a <strong><em>dark</em></strong> night</p>\n<hr>\n<h4>A letter</h4>\n"
+        );
+        assert_eq!(chapter.children[0].title, "Scene 1.1");
+        assert_eq!(chapter.children[0].content, "<p>Deep.</p>\n");
+        assert_eq!(part_one.children[1].title, "Chapter 2");
+    }
+
+    #[test]
+    fn test_title_falls_back_and_rejects_non_docx() {
+        let bytes = docx(&[paragraph(None, &run("Only text."))], None);
+        let project = read_docx(bytes, "draft").unwrap();
+        assert_eq!(project.title, "draft");
+        assert_eq!(project.nodes[0].title, "Untitled");
+
+        assert!(read_docx(b"not a zip".to_vec(), "x").is_err());
+    }
+}
@@ -0,0 +1,450 @@
+//! EPUB reader.
+//!
+//! Every XHTML document in the spine becomes a node, in spine order. Titles and
+//! nesting come from the table of contents (the EPUB 3 nav document, or the
+//! EPUB 2 NCX): a document is placed under the nearest TOC ancestor that points to
+//! an earlier document. Documents missing from the TOC take their first heading
+//! as title and stay at the level of the document before them. Chapter markup is
+//! reduced to the editor's subset; a leading heading repeating the title is
+//! dropped, as compile adds it back.
+
+use super::archive::Archive;
+use super::xml::{self, Element};
+use super::{is_scene_break, ImportedNode, ImportedProject};
+use crate::errors::{Error, Result};
+use crate::export::html::{self, Block};
+use std::collections::HashMap;
+
+/// An entry of the table of contents, with its target resolved to an archive path.
+#[derive(Debug, Clone, PartialEq)]
+struct TocEntry {
+    title: String,
+    /// Archive path of the target document, without fragment.
+    path: Option<String>,
+    parent: Option<usize>,
+}
+
+struct ManifestItem {
+    path: String,
+    media_type: String,
+    properties: String,
+}
+
+/// Reads an EPUB. The project is named after the book's `dc:title`, or
+/// `fallback_title` when it has none.
+pub fn read_epub(bytes: Vec<u8>, fallback_title: &str) -> Result<ImportedProject> {
+    let mut archive = Archive::open(bytes, "EPUB")?;
+
+    let container = xml::parse(&archive.read("META-INF/container.xml")?)?;
+    let opf_path = container
+        .find("rootfile")
+        .and_then(|rootfile| rootfile.attr("full-path"))
+        .ok_or_else(|| Error::Import("The EPUB container names no package".to_string()))?
+        .to_string();
+    let opf_dir = parent_dir(&opf_path);
+    let package = xml::parse(&archive.read(&opf_path)?)?;
+
+    let title = package
+        .find("metadata")
+        .and_then(|metadata| metadata.child("title"))
+        .map(|t| t.text().trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| fallback_title.to_string());
+
+    let items: HashMap<&str, ManifestItem> = package
+        .find("manifest")
+        .map(|manifest| {
+            manifest
+                .children_named("item")
+                .filter_map(|item| {
+                    Some((
+                        item.attr("id")?,
+                        ManifestItem {
+                            path: resolve(opf_dir, item.attr("href")?),
+                            media_type: item.attr("media-type").unwrap_or_default().to_string(),
+                            properties: item.attr("properties").unwrap_or_default().to_string(),
+                        },
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let spine = package
+        .find("spine")
+        .ok_or_else(|| Error::Import("The EPUB package has no spine".to_string()))?;
+
+    // EPUB 3 nav document, else the EPUB 2 NCX named by the spine
+    let nav_item = items
+        .values()
+        .find(|item| item.properties.split_whitespace().any(|p| p == "nav"));
+    let toc = match nav_item {
+        Some(item) => {
+            let nav = xml::parse(&archive.read(&item.path)?)?;
+            nav_entries(&nav, parent_dir(&item.path))
+        }
+        None => match spine.attr("toc").and_then(|id| items.get(id)) {
+            Some(item) => {
+                let ncx = xml::parse(&archive.read(&item.path)?)?;
+                ncx_entries(&ncx, parent_dir(&item.path))
+            }
+            None => Vec::new(),
+        },
+    };
+
+    let mut documents = Vec::new();
+    for itemref in spine.children_named("itemref") {
+        let Some(item) = itemref.attr("idref").and_then(|id| items.get(id)) else {
+            continue;
+        };
+        let is_xhtml = matches!(
+            item.media_type.as_str(),
+            "application/xhtml+xml" | "text/html"
+        );
+        if !is_xhtml || nav_item.is_some_and(|nav| nav.path == item.path) {
+            continue;
+        }
+        documents.push((item.path.clone(), archive.read(&item.path)?));
+    }
+
+    Ok(ImportedProject {
+        title,
+        nodes: build_nodes(&toc, documents),
+        ..Default::default()
+    })
+}
+
+/// Places each spine document in the tree and converts its content.
+fn build_nodes(toc: &[TocEntry], documents: Vec<(String, String)>) -> Vec<ImportedNode> {
+    // First TOC entry for each document
+    let mut entry_for_path: HashMap<&str, usize> = HashMap::new();
+    for (index, entry) in toc.iter().enumerate() {
+        if let Some(path) = &entry.path {
+            entry_for_path.entry(path.as_str()).or_insert(index);
+        }
+    }
+
+    // Nodes in spine order, with the index of their parent node
+    let mut flat: Vec<(ImportedNode, Option<usize>)> = Vec::new();
+    let mut node_for_path: HashMap<String, usize> = HashMap::new();
+
+    for (path, source) in documents {
+        let entry = entry_for_path.get(path.as_str()).map(|&index| &toc[index]);
+
+        let parent = match entry {
+            Some(entry) => {
+                let mut ancestor = entry.parent;
+                let mut found = None;
+                while let Some(index) = ancestor {
+                    let node = toc[index]
+                        .path
+                        .as_ref()
+                        .filter(|p| **p != path)
+                        .and_then(|p| node_for_path.get(p));
+                    if let Some(&node) = node {
+                        found = Some(node);
+                        break;
+                    }
+                    ancestor = toc[index].parent;
+                }
+                found
+            }
+            None => flat.last().and_then(|(_, parent)| *parent),
+        };
+
+        let mut blocks: Vec<Block> = html::parse(&source)
+            .into_iter()
+            .map(|block| match &block {
+                Block::Paragraph(inlines) if is_scene_break(&html::plain_text(inlines)) => {
+                    Block::SceneBreak
+                }
+                _ => block,
+            })
+            .collect();
+        let first_heading = match blocks.first() {
+            Some(Block::Heading { content, .. }) => {
+                Some(html::plain_text(content).trim().to_string())
+            }
+            _ => None,
+        };
+
+        let title = entry
+            .map(|e| e.title.clone())
+            .filter(|t| !t.is_empty())
+            .or_else(|| first_heading.clone().filter(|t| !t.is_empty()))
+            .unwrap_or_else(|| "Untitled".to_string());
+        if first_heading.is_some_and(|heading| heading.eq_ignore_ascii_case(&title)) {
+            blocks.remove(0);
+        }
+
+        node_for_path.insert(path, flat.len());
+        flat.push((
+            ImportedNode {
+                title,
+                content: html::render_editor_html(&blocks),
+                ..Default::default()
+            },
+            parent,
+        ));
+    }
+
+    // Parents always precede their children, so attach from the end
+    let mut slots: Vec<Option<(ImportedNode, Option<usize>)>> =
+        flat.into_iter().map(Some).collect();
+    let mut roots = Vec::new();
+    for index in (0..slots.len()).rev() {
+        let Some((node, parent)) = slots[index].take() else {
+            continue;
+        };
+        match parent.and_then(|p| slots[p].as_mut()) {
+            Some((parent, _)) => parent.children.insert(0, node),
+            None => roots.insert(0, node),
+        }
+    }
+    roots
+}
+
+/// Flattens the `toc` nav of an EPUB 3 navigation document.
+fn nav_entries(nav_document: &Element, base: &str) -> Vec<TocEntry> {
+    let mut navs = Vec::new();
+    collect_named(nav_document, "nav", &mut navs);
+    let nav = navs
+        .iter()
+        .find(|nav| nav.attr("type") == Some("toc"))
+        .or(navs.first());
+
+    let mut entries = Vec::new();
+    if let Some(list) = nav.and_then(|nav| nav.child("ol")) {
+        nav_list(list, base, None, &mut entries);
+    }
+    entries
+}
+
+fn nav_list(list: &Element, base: &str, parent: Option<usize>, entries: &mut Vec<TocEntry>) {
+    for item in list.children_named("li") {
+        let label = item.child("a").or_else(|| item.child("span"));
+        let index = entries.len();
+        entries.push(TocEntry {
+            title: label.map(|l| collapse(&l.text())).unwrap_or_default(),
+            path: label
+                .and_then(|l| l.attr("href"))
+                .map(|href| resolve(base, href)),
+            parent,
+        });
+        if let Some(sublist) = item.child("ol") {
+            nav_list(sublist, base, Some(index), entries);
+        }
+    }
+}
+
+/// Flattens the `navMap` of an EPUB 2 NCX file.
+fn ncx_entries(ncx: &Element, base: &str) -> Vec<TocEntry> {
+    let mut entries = Vec::new();
+    if let Some(nav_map) = ncx.find("navMap") {
+        ncx_points(nav_map, base, None, &mut entries);
+    }
+    entries
+}
+
+fn ncx_points(element: &Element, base: &str, parent: Option<usize>, entries: &mut Vec<TocEntry>) {
+    for point in element.children_named("navPoint") {
+        let index = entries.len();
+        entries.push(TocEntry {
+            title: point
+                .child("navLabel")
+                .map(|label| collapse(&label.text()))
+                .unwrap_or_default(),
+            path: point
+                .child("content")
+                .and_then(|c| c.attr("src"))
+                .map(|src| resolve(base, src)),
+            parent,
+        });
+        ncx_points(point, base, Some(index), entries);
+    }
+}
+
+fn collect_named<'a>(element: &'a Element, name: &str, out: &mut Vec<&'a Element>) {
+    for child in element.elements() {
+        if child.name == name {
+            out.push(child);
+        } else {
+            collect_named(child, name, out);
+        }
+    }
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+/// Resolves a relative, percent-encoded `href` against an archive folder and drops
+/// its fragment.
+fn resolve(base: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let mut segments: Vec<String> = base
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect();
+    for segment in href.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(percent_decode(segment)),
+        }
+    }
+    segments.join("/")
+}
+
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let decoded = (bytes[i] == b'%')
+            .then(|| segment.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match decoded {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::epub::build_epub;
+    use crate::export::manuscript::{CompiledChapter, Manuscript};
+    use crate::export::package::Package;
+    use crate::models::CompilePreset;
+
+    fn titles(nodes: &[ImportedNode]) -> Vec<&str> {
+        nodes.iter().map(|n| n.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_round_trip_compiled_epub() {
+        let chapter = |title: &str, depth: usize, html: &str| CompiledChapter {
+            id: title.to_string(),
+            title: title.to_string(),
+            depth,
+            heading_level: Some(depth as u8 + 1),
+            blocks: html::parse(html),
+            word_count: 0,
+        };
+        let manuscript = Manuscript {
+            id: uuid::Uuid::nil(),
+            title: "Tales & Lies".to_string(),
+            author: "Jane Doe".to_string(),
+            updated_at: chrono::Utc::now(),
+            chapters: vec![
+                chapter("Part One", 0, ""),
+                chapter(
+                    "Arrival",
+                    1,
+                    "<p>She <em>arrives</em>.</p><hr><p>Later.</p>",
+                ),
+                chapter("Departure", 1, "<p>She leaves.</p>"),
+                chapter("Epilogue", 0, "<p>The end.</p>"),
+            ],
+        };
+        let bytes = build_epub(&manuscript, &CompilePreset::default()).unwrap();
+
+        let project = read_epub(bytes, "fallback").unwrap();
+        assert_eq!(project.title, "Tales & Lies");
+
+        // The title page is in the spine but not in the TOC
+        assert_eq!(
+            titles(&project.nodes),
+            vec!["Tales & Lies", "Part One", "Epilogue"]
+        );
+        assert_eq!(project.nodes[0].content, "<p>Jane Doe</p>\n");
+
+        let nodes = &project.nodes[1..];
+        assert_eq!(titles(&nodes[0].children), vec!["Arrival", "Departure"]);
+        assert_eq!(
+            nodes[0].children[0].content,
+            "<p>She <em>arrives</em>.</p>\n<hr>\n<p>Later.</p>\n"
+        );
+        assert_eq!(nodes[1].content, "<p>The end.</p>\n");
+    }
+
+    #[test]
+    fn test_ncx_toc_and_untitled_documents() {
+        let container =
+            r#"<container><rootfiles><rootfile full-path="OPS/book.opf"/></rootfiles></container>"#;
+        let opf = r#"<package><metadata/><manifest>
+<item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+<item id="c1" href="Text/One%20Part.html" media-type="application/xhtml+xml"/>
+<item id="c2" href="Text/two.html" media-type="application/xhtml+xml"/>
+<item id="c3" href="Text/three.html" media-type="application/xhtml+xml"/>
+<item id="img" href="cover.jpg" media-type="image/jpeg"/>
+</manifest><spine toc="ncx"><itemref idref="c1"/><itemref idref="img"/><itemref idref="c2"/><itemref idref="c3"/></spine></package>"#;
+        let ncx = r#"<ncx><navMap>
+<navPoint><navLabel><text>Book One</text></navLabel><content src="Text/One%20Part.html"/>
+  <navPoint><navLabel><text>Start</text></navLabel><content src="Text/One%20Part.html#s1"/></navPoint>
+  <navPoint><navLabel><text> The
+ Middle </text></navLabel><content src="Text/two.html"/></navPoint>
+</navPoint></navMap></ncx>"#;
+        let mut package = Package::new();
+        package
+            .add_deflated("META-INF/container.xml", container.as_bytes())
+            .unwrap();
+        package
+            .add_deflated("OPS/book.opf", opf.as_bytes())
+            .unwrap();
+        package.add_deflated("OPS/toc.ncx", ncx.as_bytes()).unwrap();
+        package
+            .add_deflated(
+                "OPS/Text/One Part.html",
+                b"<html><body><p>One &amp; only.</p></body></html>",
+            )
+            .unwrap();
+        package
+            .add_deflated(
+                "OPS/Text/two.html",
+                b"<html><body><p>Two.</p><p>* * *</p></body></html>",
+            )
+            .unwrap();
+        package
+            .add_deflated(
+                "OPS/Text/three.html",
+                b"<html><body><h2>Coda</h2><p>Three.</p></body></html>",
+            )
+            .unwrap();
+
+        let project = read_epub(package.finish().unwrap(), "My File").unwrap();
+        assert_eq!(project.title, "My File");
+        assert_eq!(titles(&project.nodes), vec!["Book One"]);
+
+        let book = &project.nodes[0];
+        assert_eq!(book.content, "<p>One &amp; only.</p>\n");
+        assert_eq!(titles(&book.children), vec!["The Middle", "Coda"]);
+        assert_eq!(book.children[0].content, "<p>Two.</p>\n<hr>\n");
+        assert_eq!(book.children[1].content, "<p>Three.</p>\n");
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(
+            resolve("OEBPS/text", "../images/a%20b.png#x"),
+            "OEBPS/images/a b.png"
+        );
+        assert_eq!(resolve("", "./nav.xhtml"), "nav.xhtml");
+    }
+}
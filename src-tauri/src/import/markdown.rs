@@ -0,0 +1,420 @@
+//! Markdown folder reader, for plain folders of notes and Obsidian vaults.
+//!
+//! Every `.md` file becomes a chapter and every folder holding Markdown a parent
+//! node. Siblings are ordered by their numeric filename prefix (`01-`, `2_`,
+//! `10.`), which is dropped from the title; unprefixed entries follow in
+//! alphabetical order. A folder's `index.md`, or a note named like the folder
+//! (Obsidian's folder notes), supplies the folder node's own text and fields.
+//!
+//! YAML front matter may set `title`, `synopsis`, `plotline_tag`,
+//! `chronological_date`, `abstract_timeframe` and `duration`; other keys are
+//! ignored. Obsidian wikilinks are reduced to their text, and embeds and `%%`
+//! comments are dropped.
+
+use super::{ImportedNode, ImportedProject};
+use crate::errors::{Error, Result};
+use crate::export::html;
+use crate::models::Plotline;
+use pulldown_cmark::{html::push_html, Options, Parser};
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// A Markdown file, with its path relative to the imported folder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownFile {
+    pub path: PathBuf,
+    pub text: String,
+}
+
+/// Reads the folder at `source`. Files below `research_folder` (relative to
+/// `source`, or absolute) are left out of the manuscript and returned as research
+/// files instead.
+pub async fn read_folder(source: &Path, research_folder: Option<&Path>) -> Result<ImportedProject> {
+    let research_root = match research_folder {
+        Some(folder) => {
+            let path = source.join(folder);
+            let is_dir = tokio::fs::metadata(&path)
+                .await
+                .map(|m| m.is_dir())
+                .unwrap_or(false);
+            if !is_dir {
+                return Err(Error::Import(format!("{} is not a folder", path.display())));
+            }
+            Some(path)
+        }
+        None => None,
+    };
+
+    let mut files = Vec::new();
+    let mut research_files = Vec::new();
+    let mut pending = vec![source.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            // `.obsidian`, `.trash`, `.git` and other hidden entries
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let in_research = research_root
+                .as_deref()
+                .is_some_and(|root| path.starts_with(root));
+
+            if entry.file_type().await?.is_dir() {
+                pending.push(path);
+            } else if in_research {
+                research_files.push(path);
+            } else if is_markdown(&path) {
+                let bytes = tokio::fs::read(&path).await?;
+                files.push(MarkdownFile {
+                    path: path.strip_prefix(source).unwrap_or(&path).to_path_buf(),
+                    text: String::from_utf8_lossy(&bytes).into_owned(),
+                });
+            }
+        }
+    }
+    research_files.sort();
+
+    let title = source
+        .file_name()
+        .map(|s| s.to_string_lossy().trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "Imported Project".to_string());
+
+    let mut project = build_project(title, files);
+    project.research_files = research_files;
+    Ok(project)
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .is_some_and(|ext| ext == "md" || ext == "markdown")
+}
+
+/// Folder contents keyed by file or folder name.
+#[derive(Default)]
+struct Folder {
+    folders: HashMap<String, Folder>,
+    files: HashMap<String, String>,
+}
+
+/// Builds the node tree from the files of a folder.
+pub fn build_project(title: String, files: Vec<MarkdownFile>) -> ImportedProject {
+    let mut root = Folder::default();
+    for file in files {
+        let mut components: Vec<String> = file
+            .path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        let Some(name) = components.pop() else {
+            continue;
+        };
+        let mut folder = &mut root;
+        for component in components {
+            folder = folder.folders.entry(component).or_default();
+        }
+        folder.files.insert(name, file.text);
+    }
+
+    let nodes = folder_nodes(root);
+
+    // Tags used in front matter become plotlines; colours are assigned on creation
+    let mut plotlines: Vec<Plotline> = Vec::new();
+    let mut pending: Vec<&ImportedNode> = nodes.iter().collect();
+    while let Some(node) = pending.pop() {
+        if let Some(tag) = &node.plotline_tag {
+            if !plotlines.iter().any(|p| &p.id == tag) {
+                plotlines.push(Plotline {
+                    id: tag.clone(),
+                    name: tag.clone(),
+                    color: String::new(),
+                });
+            }
+        }
+        pending.extend(&node.children);
+    }
+    plotlines.sort_by(|a, b| a.id.cmp(&b.id));
+
+    ImportedProject {
+        title,
+        nodes,
+        plotlines,
+        ..Default::default()
+    }
+}
+
+fn folder_nodes(mut folder: Folder) -> Vec<ImportedNode> {
+    let mut entries: Vec<(String, ImportedNode)> = Vec::new();
+
+    for (name, mut sub) in folder.folders.drain() {
+        let note = ["index.md".to_string(), format!("{}.md", name)]
+            .iter()
+            .find_map(|candidate| sub.files.remove(candidate));
+        let mut node = match note {
+            Some(text) => file_node(&name, &text),
+            None => ImportedNode {
+                title: strip_prefix(&name).to_string(),
+                ..Default::default()
+            },
+        };
+        node.children = folder_nodes(sub);
+        entries.push((name, node));
+    }
+    for (name, text) in folder.files.drain() {
+        let node = file_node(strip_extension(&name), &text);
+        entries.push((name, node));
+    }
+
+    entries.sort_by_cached_key(|(name, _)| (order_prefix(name), name.to_lowercase()));
+    entries.into_iter().map(|(_, node)| node).collect()
+}
+
+fn file_node(name: &str, text: &str) -> ImportedNode {
+    let (fields, body) = split_front_matter(text);
+    let field = |key: &str| fields.get(key).cloned();
+
+    ImportedNode {
+        title: field("title").unwrap_or_else(|| strip_prefix(name).to_string()),
+        content: to_editor_html(body),
+        synopsis: field("synopsis"),
+        plotline_tag: field("plotline_tag"),
+        chronological_date: field("chronological_date"),
+        abstract_timeframe: field("abstract_timeframe"),
+        duration: field("duration"),
+        children: Vec::new(),
+    }
+}
+
+fn strip_extension(name: &str) -> &str {
+    name.rsplit_once('.').map_or(name, |(stem, _)| stem)
+}
+
+/// Numeric prefix of a name, followed by `-`, `_`, `.` or a space. Names without
+/// one sort after all prefixed names.
+fn order_prefix(name: &str) -> u64 {
+    split_prefix(name).map_or(u64::MAX, |(number, _)| number)
+}
+
+fn strip_prefix(name: &str) -> &str {
+    split_prefix(name).map_or(name, |(_, rest)| rest)
+}
+
+fn split_prefix(name: &str) -> Option<(u64, &str)> {
+    let digits = name.find(|c: char| !c.is_ascii_digit())?;
+    let number = name[..digits].parse().ok()?;
+    let rest = name[digits..]
+        .strip_prefix(['-', '_', '.', ' '])?
+        .trim_start_matches(['-', '_', ' '])
+        .trim();
+    // `01.md` keeps its number as the title
+    (!rest.is_empty()).then_some((number, rest))
+}
+
+/// Splits a leading `---` YAML block from the body. Only single-line
+/// `key: value` scalars are read; lists, nested maps and comments are skipped.
+pub fn split_front_matter(text: &str) -> (HashMap<String, String>, &str) {
+    let mut fields = HashMap::new();
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let Some(rest) = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))
+    else {
+        return (fields, text);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let line = line.trim_end();
+        if line == "---" || line == "..." {
+            return (fields, &rest[offset..]);
+        }
+        if line.starts_with([' ', '\t', '-', '#']) {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            let value = unquote(value.trim());
+            if !value.is_empty() && value != "~" && value != "null" {
+                fields.insert(key.trim().to_string(), value.to_string());
+            }
+        }
+    }
+    // No closing delimiter: not front matter after all
+    (HashMap::new(), text)
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+static COMMENT_REGEX: OnceLock<Regex> = OnceLock::new();
+static EMBED_REGEX: OnceLock<Regex> = OnceLock::new();
+static WIKILINK_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Converts Markdown to editor HTML, keeping only what the editor supports.
+pub fn to_editor_html(markdown: &str) -> String {
+    let comment_re = COMMENT_REGEX
+        .get_or_init(|| Regex::new(r"(?s)%%.*?%%").expect("static regex must compile"));
+    let embed_re = EMBED_REGEX
+        .get_or_init(|| Regex::new(r"!\[\[[^\]]*\]\]").expect("static regex must compile"));
+    // `[[target#heading|alias]]` keeps the alias, else the target
+    let wikilink_re = WIKILINK_REGEX.get_or_init(|| {
+        Regex::new(r"\[\[([^\]|#]*)(?:#[^\]|]*)?(?:\|([^\]]*))?\]\]")
+            .expect("static regex must compile")
+    });
+
+    let markdown = comment_re.replace_all(markdown, "");
+    let markdown = embed_re.replace_all(&markdown, "");
+    let markdown = wikilink_re.replace_all(&markdown, |caps: &regex::Captures| {
+        caps.get(2)
+            .or_else(|| caps.get(1))
+            .map_or("", |m| m.as_str())
+            .to_string()
+    });
+
+    let mut rendered = String::new();
+    push_html(
+        &mut rendered,
+        Parser::new_ext(&markdown, Options::ENABLE_STRIKETHROUGH),
+    );
+    html::render_editor_html(&html::parse(&rendered))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, text: &str) -> MarkdownFile {
+        MarkdownFile {
+            path: PathBuf::from(path),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_folders_and_prefix_order() {
+        let project = build_project(
+            "Vault".to_string(),
+            vec![
+                file("Appendix.md", "The end."),
+                file("10-Part Two/01-Return.md", "Back."),
+                file(
+                    "2-Part One/index.md",
+                    "---\nsynopsis: Setup\n---\nPart intro.",
+                ),
+                file("2-Part One/10_Later.md", "Later."),
+                file("2-Part One/9 Earlier.md", "Earlier."),
+                file("Drafts/Old/idea.md", "Idea."),
+            ],
+        );
+
+        let titles: Vec<&str> = project.nodes.iter().map(|n| n.title.as_str()).collect();
+        assert_eq!(titles, vec!["Part One", "Part Two", "Appendix", "Drafts"]);
+
+        let part_one = &project.nodes[0];
+        assert_eq!(part_one.content, "<p>Part intro.</p>\n");
+        assert_eq!(part_one.synopsis.as_deref(), Some("Setup"));
+        let children: Vec<&str> = part_one.children.iter().map(|n| n.title.as_str()).collect();
+        assert_eq!(children, vec!["Earlier", "Later"]);
+
+        let drafts = &project.nodes[3];
+        assert_eq!(drafts.content, "");
+        assert_eq!(drafts.children[0].title, "Old");
+        assert_eq!(drafts.children[0].children[0].title, "idea");
+    }
+
+    #[test]
+    fn test_front_matter_fills_fields() {
+        let project = build_project(
+            "Vault".to_string(),
+            vec![file(
+                "01-arrival.md",
+                "---\ntitle: \"The Arrival\"\nplotline_tag: romance\nchronological_date: 2024-03-01\n\
+                 abstract_timeframe: 'Day 1'\nduration: 2h\ntags:\n  - draft\npov: ~\n---\n# Heading\n\nText.\n",
+            )],
+        );
+
+        let node = &project.nodes[0];
+        assert_eq!(node.title, "The Arrival");
+        assert_eq!(node.plotline_tag.as_deref(), Some("romance"));
+        assert_eq!(node.chronological_date.as_deref(), Some("2024-03-01"));
+        assert_eq!(node.abstract_timeframe.as_deref(), Some("Day 1"));
+        assert_eq!(node.duration.as_deref(), Some("2h"));
+        assert_eq!(node.content, "<h1>Heading</h1>\n<p>Text.</p>\n");
+        assert_eq!(project.plotlines[0].id, "romance");
+    }
+
+    #[test]
+    fn test_unclosed_front_matter_is_body() {
+        let (fields, body) = split_front_matter("---\ntitle: x\n\nNo end.");
+        assert!(fields.is_empty());
+        assert_eq!(body, "---\ntitle: x\n\nNo end.");
+    }
+
+    #[test]
+    fn test_obsidian_syntax() {
+        assert_eq!(
+            to_editor_html(
+                "See [[Alice]] and [[Bob#Past|him]].%% hidden\nnote %% ![[map.png]]\n\n***\n\n**Bold** ~~gone~~"
+            ),
+            "<p>See Alice and him.</p>\n<hr>\n<p><strong>Bold</strong> <s>gone</s></p>\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_folder_splits_research() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let root = dir.path().join("My Book");
+        tokio::fs::create_dir_all(root.join("Research/maps"))
+            .await
+            .unwrap();
+        tokio::fs::create_dir_all(root.join(".obsidian"))
+            .await
+            .unwrap();
+        tokio::fs::write(root.join("01-One.md"), "One.")
+            .await
+            .unwrap();
+        tokio::fs::write(root.join("notes.txt"), "skipped")
+            .await
+            .unwrap();
+        tokio::fs::write(root.join(".obsidian/app.md"), "hidden")
+            .await
+            .unwrap();
+        tokio::fs::write(root.join("Research/people.md"), "Notes")
+            .await
+            .unwrap();
+        tokio::fs::write(root.join("Research/maps/city.png"), "png")
+            .await
+            .unwrap();
+
+        let project = read_folder(&root, Some(Path::new("Research")))
+            .await
+            .unwrap();
+        assert_eq!(project.title, "My Book");
+        assert_eq!(project.nodes.len(), 1);
+        assert_eq!(project.nodes[0].title, "One");
+        assert_eq!(
+            project.research_files,
+            vec![
+                root.join("Research/maps/city.png"),
+                root.join("Research/people.md")
+            ]
+        );
+
+        assert!(read_folder(&root, Some(Path::new("Missing")))
+            .await
+            .is_err());
+    }
+}
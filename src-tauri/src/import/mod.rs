@@ -0,0 +1,381 @@
+//! Importers that turn files from other tools into a new BrutWrite project.
+//!
+//! Each importer reads its source into an `ImportedProject`, a tree of nodes whose
+//! content is already editor HTML. `create_project` then goes through the normal
+//! project creation path and writes one chapter file per node, so imported
+//! projects are indistinguishable from ones built in the app.
+
+pub mod archive;
+pub mod characters;
+pub mod csv;
+pub mod docx;
+pub mod epub;
+pub mod markdown;
+pub mod rtf;
+pub mod scrivener;
+pub mod text;
+pub mod xml;
+
+use crate::errors::Result;
+use crate::export::html::{normalize_inlines, Inline};
+use crate::models::{count_words, Plotline, ProjectMetadata};
+use crate::project::manager::ProjectManager;
+use crate::storage::{self, FileRepository};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// A manifest node to be created, with its children in reading order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedNode {
+    pub title: String,
+    /// Chapter body as editor HTML.
+    pub content: String,
+    pub synopsis: Option<String>,
+    pub plotline_tag: Option<String>,
+    pub chronological_date: Option<String>,
+    pub abstract_timeframe: Option<String>,
+    pub duration: Option<String>,
+    pub children: Vec<ImportedNode>,
+}
+
+impl ImportedNode {
+    /// Title and size of the node and its children, for showing the tree before
+    /// anything is written.
+    pub fn preview(&self) -> PreviewNode {
+        PreviewNode {
+            title: self.title.clone(),
+            word_count: count_words(&self.content),
+            children: self.children.iter().map(ImportedNode::preview).collect(),
+        }
+    }
+}
+
+/// A node as it would be created by an import.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PreviewNode {
+    pub title: String,
+    pub word_count: u32,
+    pub children: Vec<PreviewNode>,
+}
+
+/// Everything read from an import source.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedProject {
+    pub title: String,
+    pub nodes: Vec<ImportedNode>,
+    /// Plotlines referenced by `plotline_tag`s, added unless the ID already exists.
+    /// An empty colour is replaced by the next one from `PLOTLINE_COLORS`.
+    pub plotlines: Vec<Plotline>,
+    /// Files copied into the project's research vault.
+    pub research_files: Vec<PathBuf>,
+}
+
+/// Palette the plotline editor assigns from, in order.
+const PLOTLINE_COLORS: &[&str] = &[
+    "#3b82f6", "#10b981", "#f59e0b", "#ef4444", "#8b5cf6", "#ec4899", "#06b6d4", "#84cc16",
+    "#f97316", "#6366f1",
+];
+
+/// Character formatting of a word processor run.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TextFormat {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strike: bool,
+}
+
+impl TextFormat {
+    /// Wraps `text` in the inline styles this format turns on.
+    pub fn apply(self, text: String) -> Inline {
+        let mut inline = Inline::Text(text);
+        if self.strike {
+            inline = Inline::Strikethrough(vec![inline]);
+        }
+        if self.underline {
+            inline = Inline::Underline(vec![inline]);
+        }
+        if self.italic {
+            inline = Inline::Emphasis(vec![inline]);
+        }
+        if self.bold {
+            inline = Inline::Strong(vec![inline]);
+        }
+        inline
+    }
+}
+
+enum Run {
+    Text(String, TextFormat),
+    Break,
+}
+
+/// Collects the runs of one paragraph, merging neighbours with the same format
+/// so that `<em>a</em><em>b</em>` is never produced.
+#[derive(Default)]
+pub struct ParagraphBuilder {
+    runs: Vec<Run>,
+}
+
+impl ParagraphBuilder {
+    pub fn push_text(&mut self, text: &str, format: TextFormat) {
+        if let Some(Run::Text(prev, f)) = self.runs.last_mut() {
+            if *f == format {
+                prev.push_str(text);
+                return;
+            }
+        }
+        self.runs.push(Run::Text(text.to_string(), format));
+    }
+
+    pub fn push_break(&mut self) {
+        self.runs.push(Run::Break);
+    }
+
+    /// Returns the paragraph's inlines, trimmed, and starts a new paragraph.
+    pub fn finish(&mut self) -> Vec<Inline> {
+        let mut inlines: Vec<Inline> = self
+            .runs
+            .drain(..)
+            .map(|run| match run {
+                Run::Text(text, format) => format.apply(text),
+                Run::Break => Inline::LineBreak,
+            })
+            .collect();
+        normalize_inlines(&mut inlines);
+        inlines
+    }
+}
+
+/// Whether a paragraph is a typed scene separator: only `*`, `#` or `~`
+/// characters, possibly spaced (`* * *`).
+pub fn is_scene_break(text: &str) -> bool {
+    let text = text.trim();
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| matches!(c, '*' | '#' | '~') || c.is_whitespace())
+}
+
+/// Creates a project at `root_path` from an import and registers it with the
+/// manager. Fails like `create_project` if the folder already exists. If the
+/// import fails part way, the project is unregistered and its folder removed,
+/// so it can be retried at the same path.
+pub async fn create_project(
+    projects: &ProjectManager,
+    root_path: PathBuf,
+    author: String,
+    imported: ImportedProject,
+) -> Result<ProjectMetadata> {
+    let ImportedProject {
+        title,
+        nodes,
+        plotlines,
+        research_files,
+    } = imported;
+    let id = projects
+        .create_project(root_path.clone(), title, author)
+        .await?
+        .id;
+
+    let result = fill_project(projects, id, &root_path, nodes, plotlines, research_files).await;
+    if result.is_err() {
+        // Nothing was marked for saving, so this writes nothing
+        if let Err(e) = projects.unload_project(id).await {
+            log::warn!("Failed to unload partly imported project: {}", e);
+        }
+        if let Err(e) = tokio::fs::remove_dir_all(&root_path).await {
+            log::warn!(
+                "Failed to remove partly imported project {}: {}",
+                root_path.display(),
+                e
+            );
+        }
+    }
+    result
+}
+
+async fn fill_project(
+    projects: &ProjectManager,
+    id: uuid::Uuid,
+    root_path: &Path,
+    nodes: Vec<ImportedNode>,
+    plotlines: Vec<Plotline>,
+    research_files: Vec<PathBuf>,
+) -> Result<ProjectMetadata> {
+    let (_, metadata_arc) = projects.get_context(id).await?;
+    let mut metadata = metadata_arc.lock().await;
+
+    for mut plotline in plotlines {
+        if metadata.plotline(&plotline.id).is_none() {
+            if plotline.color.is_empty() {
+                let index = metadata.plotlines.len() % PLOTLINE_COLORS.len();
+                plotline.color = PLOTLINE_COLORS[index].to_string();
+            }
+            metadata.plotlines.push(plotline);
+        }
+    }
+
+    let repo = storage::LocalFileRepository;
+    write_nodes(&repo, root_path, &mut metadata, &nodes).await?;
+
+    metadata.updated_at = chrono::Utc::now();
+    storage::save_project_metadata(root_path, &metadata).await?;
+
+    copy_research_files(&root_path.join("research"), &research_files).await?;
+    Ok(metadata.clone())
+}
+
+/// Copies files into the flat research folder, renaming on clashes
+/// (`map.png`, `map (2).png`).
+async fn copy_research_files(research_dir: &Path, files: &[PathBuf]) -> Result<()> {
+    if files.is_empty() {
+        return Ok(());
+    }
+    tokio::fs::create_dir_all(research_dir).await?;
+
+    for file in files {
+        let Some(name) = file.file_name().map(|n| n.to_string_lossy().into_owned()) else {
+            continue;
+        };
+        let (stem, extension) = match name.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => (stem.to_string(), format!(".{}", ext)),
+            _ => (name.clone(), String::new()),
+        };
+        let mut dest = research_dir.join(&name);
+        let mut copy = 2;
+        while tokio::fs::try_exists(&dest).await? {
+            dest = research_dir.join(format!("{} ({}){}", stem, copy, extension));
+            copy += 1;
+        }
+        tokio::fs::copy(file, &dest).await?;
+    }
+    Ok(())
+}
+
+/// Appends `nodes` to the manifest in reading order and writes their content.
+pub async fn write_nodes<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    metadata: &mut ProjectMetadata,
+    nodes: &[ImportedNode],
+) -> Result<()> {
+    // Depth-first, so that siblings are created in order and get increasing `order`s
+    let mut stack: Vec<(&ImportedNode, Option<String>)> =
+        nodes.iter().rev().map(|node| (node, None)).collect();
+
+    while let Some((node, parent_id)) = stack.pop() {
+        let mut chapter = metadata
+            .manifest
+            .create_chapter(parent_id, node.title.clone());
+        chapter.word_count = count_words(&node.content);
+        chapter.synopsis = node.synopsis.clone();
+        chapter.plotline_tag = node.plotline_tag.clone();
+        chapter.chronological_date = node.chronological_date.clone();
+        chapter.abstract_timeframe = node.abstract_timeframe.clone();
+        chapter.duration = node.duration.clone();
+
+        storage::write_chapter_file(repo, root_path, &chapter.filename, &node.content).await?;
+
+        stack.extend(
+            node.children
+                .iter()
+                .rev()
+                .map(|child| (child, Some(chapter.id.clone()))),
+        );
+        metadata.manifest.push(chapter);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::reading_order;
+    use crate::storage::MockFileRepository;
+
+    fn node(title: &str, content: &str, children: Vec<ImportedNode>) -> ImportedNode {
+        ImportedNode {
+            title: title.to_string(),
+            content: content.to_string(),
+            children,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_nodes_keeps_hierarchy_and_order() {
+        let repo = MockFileRepository::new();
+        let mut metadata = ProjectMetadata::new("Imported".to_string(), "Jane".to_string());
+        let nodes = vec![
+            node(
+                "Part One",
+                "",
+                vec![
+                    node("Arrival", "<p>Three short words.</p>", vec![]),
+                    node("Night", "<p>Dark.</p>", vec![]),
+                ],
+            ),
+            node("Part Two", "<p>Later.</p>", vec![]),
+        ];
+
+        write_nodes(&repo, Path::new("/book"), &mut metadata, &nodes)
+            .await
+            .unwrap();
+
+        let order: Vec<(String, usize, u32)> = reading_order(&metadata.manifest)
+            .iter()
+            .map(|e| (e.chapter.title.clone(), e.depth, e.chapter.word_count))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("Part One".to_string(), 0, 0),
+                ("Arrival".to_string(), 1, 3),
+                ("Night".to_string(), 1, 1),
+                ("Part Two".to_string(), 0, 1),
+            ]
+        );
+
+        let arrival = &metadata.manifest.chapters[1];
+        let content = repo
+            .read_file(&Path::new("/book/manuscript").join(&arrival.filename))
+            .await
+            .unwrap();
+        assert_eq!(content, "<p>Three short words.</p>");
+    }
+
+    #[tokio::test]
+    async fn test_failed_import_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("book");
+        let projects = ProjectManager::new();
+        let imported = ImportedProject {
+            title: "Book".to_string(),
+            nodes: vec![node("One", "<p>Text.</p>", vec![])],
+            research_files: vec![dir.path().join("missing.png")],
+            ..Default::default()
+        };
+
+        assert!(create_project(
+            &projects,
+            root.clone(),
+            "Jane".to_string(),
+            imported.clone()
+        )
+        .await
+        .is_err());
+        assert!(!root.exists());
+        assert!(projects.get_all_loaded().await.is_empty());
+
+        // The same path can be used again
+        let imported = ImportedProject {
+            research_files: Vec::new(),
+            ..imported
+        };
+        let metadata = create_project(&projects, root.clone(), "Jane".to_string(), imported)
+            .await
+            .unwrap();
+        assert_eq!(metadata.manifest.chapters.len(), 1);
+    }
+}
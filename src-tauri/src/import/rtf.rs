@@ -0,0 +1,361 @@
+//! RTF reader.
+//!
+//! Converts the RTF written by Scrivener (and most word processors) into the
+//! shared block model: one paragraph per `\par`, with bold, italic, underline and
+//! strikethrough kept. Fonts, colours, stylesheets, pictures, annotations and any
+//! other destination are skipped; fields keep their visible result only.
+
+use super::{ParagraphBuilder, TextFormat};
+use crate::export::html::Block;
+
+/// Destinations whose content is never part of the text.
+const SKIPPED_DESTINATIONS: &[&str] = &[
+    "fonttbl",
+    "colortbl",
+    "stylesheet",
+    "listtable",
+    "listoverridetable",
+    "info",
+    "pict",
+    "object",
+    "header",
+    "headerl",
+    "headerr",
+    "headerf",
+    "footer",
+    "footerl",
+    "footerr",
+    "footerf",
+    "footnote",
+    "fldinst",
+    "themedata",
+    "colorschememapping",
+    "latentstyles",
+    "datastore",
+    "xmlnstbl",
+    "rsidtbl",
+    "generator",
+];
+
+#[derive(Debug, Clone, Copy)]
+struct GroupState {
+    format: TextFormat,
+    skip: bool,
+    /// Fallback characters to drop after `\uN`
+    unicode_skip: usize,
+}
+
+struct Converter {
+    blocks: Vec<Block>,
+    paragraph: ParagraphBuilder,
+    state: GroupState,
+    stack: Vec<GroupState>,
+    /// Fallback characters still to drop after the last `\uN`
+    pending_skip: usize,
+    high_surrogate: Option<u16>,
+}
+
+/// Parses an RTF document into paragraphs.
+pub fn to_blocks(rtf: &str) -> Vec<Block> {
+    let mut converter = Converter {
+        blocks: Vec::new(),
+        paragraph: ParagraphBuilder::default(),
+        state: GroupState {
+            format: TextFormat::default(),
+            skip: false,
+            unicode_skip: 1,
+        },
+        stack: Vec::new(),
+        pending_skip: 0,
+        high_surrogate: None,
+    };
+
+    let bytes = rtf.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' => {
+                converter.stack.push(converter.state);
+                i += 1;
+                // `{\*\dest ...}` marks a destination that may be ignored
+                if bytes[i..].starts_with(b"\\*") {
+                    converter.state.skip = true;
+                    i += 2;
+                }
+            }
+            b'}' => {
+                if let Some(state) = converter.stack.pop() {
+                    converter.state = state;
+                }
+                i += 1;
+            }
+            b'\\' => i = converter.control(rtf, i + 1),
+            b'\r' | b'\n' => i += 1,
+            _ => {
+                // Copy the run of plain text up to the next special character
+                let end = rtf[i..]
+                    .find(['{', '}', '\\', '\r', '\n'])
+                    .map_or(rtf.len(), |n| i + n);
+                for c in rtf[i..end].chars() {
+                    converter.push_char(c);
+                }
+                i = end;
+            }
+        }
+    }
+    converter.end_paragraph();
+    converter.blocks
+}
+
+impl Converter {
+    /// Handles the control word or symbol starting at `start` (just past the
+    /// backslash) and returns the position after it.
+    fn control(&mut self, rtf: &str, start: usize) -> usize {
+        let bytes = rtf.as_bytes();
+        let Some(&first) = bytes.get(start) else {
+            return start;
+        };
+
+        if !first.is_ascii_alphabetic() {
+            return match first {
+                b'\'' => {
+                    let hex = rtf.get(start + 1..start + 3).unwrap_or("");
+                    match u8::from_str_radix(hex, 16) {
+                        Ok(byte) => {
+                            self.push_char(cp1252(byte));
+                            start + 3
+                        }
+                        // Not an escape after all; what follows is text
+                        Err(_) => start + 1,
+                    }
+                }
+                b'\\' | b'{' | b'}' => {
+                    self.push_char(first as char);
+                    start + 1
+                }
+                b'~' => {
+                    self.push_char('\u{a0}');
+                    start + 1
+                }
+                b'_' => {
+                    self.push_char('\u{2011}');
+                    start + 1
+                }
+                b'\r' | b'\n' => {
+                    self.end_paragraph();
+                    start + 1
+                }
+                // `\-` optional hyphen, `\*` outside a group start, and others
+                _ => start + rtf[start..].chars().next().map_or(1, char::len_utf8),
+            };
+        }
+
+        let mut end = start;
+        while end < bytes.len() && bytes[end].is_ascii_alphabetic() {
+            end += 1;
+        }
+        let word = &rtf[start..end];
+
+        let param_start = end;
+        if end < bytes.len() && bytes[end] == b'-' {
+            end += 1;
+        }
+        while end < bytes.len() && bytes[end].is_ascii_digit() {
+            end += 1;
+        }
+        let param: Option<i32> = rtf[param_start..end].parse().ok();
+        // A single space delimits the control word and is not part of the text
+        if end < bytes.len() && bytes[end] == b' ' {
+            end += 1;
+        }
+
+        if word == "bin" {
+            // Raw binary data follows; never part of the text
+            return char_boundary(rtf, end + param.unwrap_or(0).max(0) as usize);
+        }
+        self.control_word(word, param);
+        end
+    }
+
+    fn control_word(&mut self, word: &str, param: Option<i32>) {
+        if SKIPPED_DESTINATIONS.contains(&word) {
+            self.state.skip = true;
+            return;
+        }
+
+        let on = param != Some(0);
+        match word {
+            "par" | "sect" | "page" => self.end_paragraph(),
+            "line" if !self.state.skip => self.paragraph.push_break(),
+            "tab" | "emspace" | "enspace" | "qmspace" => self.push_char(' '),
+            "emdash" => self.push_char('\u{2014}'),
+            "endash" => self.push_char('\u{2013}'),
+            "lquote" => self.push_char('\u{2018}'),
+            "rquote" => self.push_char('\u{2019}'),
+            "ldblquote" => self.push_char('\u{201c}'),
+            "rdblquote" => self.push_char('\u{201d}'),
+            "bullet" => self.push_char('\u{2022}'),
+            "b" => self.state.format.bold = on,
+            "i" => self.state.format.italic = on,
+            "ul" => self.state.format.underline = on,
+            "ulnone" => self.state.format.underline = false,
+            "strike" | "striked" => self.state.format.strike = on,
+            "plain" => self.state.format = TextFormat::default(),
+            "uc" => self.state.unicode_skip = param.unwrap_or(1).max(0) as usize,
+            "u" => {
+                if let Some(value) = param {
+                    // Values above 32767 are written as negative numbers
+                    self.push_unicode(value.rem_euclid(65536) as u16);
+                }
+                self.pending_skip = self.state.unicode_skip;
+            }
+            _ => {}
+        }
+    }
+
+    fn push_unicode(&mut self, unit: u16) {
+        if let Some(high) = self.high_surrogate.take() {
+            if let Some(Ok(c)) = char::decode_utf16([high, unit]).next() {
+                self.emit(c);
+                return;
+            }
+        }
+        if (0xD800..0xDC00).contains(&unit) {
+            self.high_surrogate = Some(unit);
+        } else if let Some(Ok(c)) = char::decode_utf16([unit]).next() {
+            self.emit(c);
+        }
+    }
+
+    /// Adds a character from the text stream, honouring `\uN` fallbacks.
+    fn push_char(&mut self, c: char) {
+        if self.pending_skip > 0 {
+            self.pending_skip -= 1;
+            return;
+        }
+        self.emit(c);
+    }
+
+    fn emit(&mut self, c: char) {
+        if self.state.skip {
+            return;
+        }
+        self.paragraph
+            .push_text(c.encode_utf8(&mut [0; 4]), self.state.format);
+    }
+
+    fn end_paragraph(&mut self) {
+        self.pending_skip = 0;
+        if self.state.skip {
+            return;
+        }
+        let inlines = self.paragraph.finish();
+        if !inlines.is_empty() {
+            self.blocks.push(Block::Paragraph(inlines));
+        }
+    }
+}
+
+/// The first character boundary at or after `i`. Skipped binary data counts
+/// bytes, and a lossy decode may have turned one byte into several.
+fn char_boundary(rtf: &str, i: usize) -> usize {
+    let mut i = i.min(rtf.len());
+    while !rtf.is_char_boundary(i) {
+        i += 1;
+    }
+    i
+}
+
+/// Decodes a `\'hh` escape, which RTF writers emit in the Windows-1252 code page.
+fn cp1252(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '\u{20ac}', '\u{fffd}', '\u{201a}', '\u{0192}', '\u{201e}', '\u{2026}', '\u{2020}',
+        '\u{2021}', '\u{02c6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{fffd}',
+        '\u{017d}', '\u{fffd}', '\u{fffd}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}',
+        '\u{2022}', '\u{2013}', '\u{2014}', '\u{02dc}', '\u{2122}', '\u{0161}', '\u{203a}',
+        '\u{0153}', '\u{fffd}', '\u{017e}', '\u{0178}',
+    ];
+    match byte {
+        0x80..=0x9f => HIGH[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::html::Inline;
+
+    fn text(t: &str) -> Inline {
+        Inline::Text(t.to_string())
+    }
+
+    #[test]
+    fn test_scrivener_rtf() {
+        let rtf = r#"{\rtf1\ansi\ansicpg1252\cocoartf2639
+{\fonttbl\f0\fnil\fcharset0 Palatino-Roman;}
+{\colortbl;\red255\green255\blue255;}
+{\*\expandedcolortbl;;}
+\pard\tx360\fi360\sl264\slmult1\pardirnatural\partightenfactor0
+
+\f0\fs26 \cf0 It was a \b dark\b0  and \i stormy\i0  night.\
+She said \'93hello\'94 \endash  twice\line again.\par
+\pard \ul Under\ulnone  and \strike gone\strike0 . Caf\'e9 \'97 done \u-10179 ?\u-8704 ?.\par
+{\*\annotation A note}\par
+}"#;
+        let blocks = to_blocks(rtf);
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Paragraph(vec![
+                    text("It was a "),
+                    Inline::Strong(vec![text("dark")]),
+                    text(" and "),
+                    Inline::Emphasis(vec![text("stormy")]),
+                    text(" night."),
+                ]),
+                Block::Paragraph(vec![
+                    text("She said \u{201c}hello\u{201d} \u{2013} twice"),
+                    Inline::LineBreak,
+                    text("again."),
+                ]),
+                Block::Paragraph(vec![
+                    Inline::Underline(vec![text("Under")]),
+                    text(" and "),
+                    Inline::Strikethrough(vec![text("gone")]),
+                    text(". Caf\u{e9} \u{2014} done \u{1f600}."),
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_binary_data_never_splits_a_character() {
+        // A raw byte decoded as U+FFFD is three bytes long
+        let rtf = "{\\rtf1 One{\\bin1 \u{fffd}} two\\par}";
+        assert_eq!(
+            to_blocks(rtf),
+            vec![Block::Paragraph(vec![text("One two")])]
+        );
+    }
+
+    #[test]
+    fn test_broken_hex_escape_keeps_text() {
+        let rtf = "{\\rtf1 Caf\\'\u{e9} \\'\u{20ac}5 \\'e\u{20ac}\\par}";
+        assert_eq!(
+            to_blocks(rtf),
+            vec![Block::Paragraph(vec![text(
+                "Caf\u{e9} \u{20ac}5 e\u{20ac}"
+            )])]
+        );
+    }
+
+    #[test]
+    fn test_field_keeps_result() {
+        let rtf = r#"{\rtf1 See {\field{\*\fldinst{HYPERLINK "https://example.com"}}{\fldrslt the site}}.\par}"#;
+        assert_eq!(
+            to_blocks(rtf),
+            vec![Block::Paragraph(vec![text("See the site.")])]
+        );
+    }
+}
@@ -0,0 +1,429 @@
+//! Scrivener project reader.
+//!
+//! A `.scriv` bundle is a folder holding a `.scrivx` binder (XML) and one folder
+//! or file per document. Only the Draft folder is imported: its hierarchy and
+//! order become the manifest, each document's RTF becomes editor HTML, synopses
+//! are kept on the node and labels become plotlines. Scrivener 3 stores documents
+//! under `Files/Data/<UUID>/`, Scrivener 1 and 2 under `Files/Docs/<ID>.rtf`.
+
+use super::xml::{self, Element};
+use super::{rtf, ImportedNode, ImportedProject};
+use crate::errors::{Error, Result};
+use crate::export::html::render_editor_html;
+use crate::models::Plotline;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use uuid::Uuid;
+
+/// Scrivener's "No Label" entry.
+const NO_LABEL: &str = "-1";
+
+/// A binder entry from the Draft folder. Items whose id is not a UUID, or a
+/// number for older versions, are left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinderItem {
+    /// `UUID` (Scrivener 3) or numeric `ID` (older versions).
+    pub id: String,
+    pub legacy: bool,
+    pub title: String,
+    pub label_id: Option<String>,
+    pub children: Vec<BinderItem>,
+}
+
+/// Label definition: display name and colour as `#rrggbb`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub name: String,
+    pub color: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binder {
+    pub draft: Vec<BinderItem>,
+    pub labels: HashMap<String, Label>,
+}
+
+/// Files stored for one binder item.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Document {
+    pub rtf: Option<String>,
+    pub synopsis: Option<String>,
+}
+
+/// Reads a `.scriv` bundle. The project is named after the bundle folder.
+pub async fn read_bundle(bundle: &Path) -> Result<ImportedProject> {
+    let scrivx_path = find_scrivx(bundle).await?;
+    let binder = parse_binder(&tokio::fs::read_to_string(&scrivx_path).await?)?;
+
+    let mut documents = HashMap::new();
+    let mut pending: Vec<&BinderItem> = binder.draft.iter().collect();
+    while let Some(item) = pending.pop() {
+        let (rtf_path, synopsis_path) = if item.legacy {
+            let docs = bundle.join("Files").join("Docs");
+            (
+                docs.join(format!("{}.rtf", item.id)),
+                docs.join(format!("{}_synopsis.txt", item.id)),
+            )
+        } else {
+            let data = bundle.join("Files").join("Data").join(&item.id);
+            (data.join("content.rtf"), data.join("synopsis.txt"))
+        };
+        documents.insert(
+            item.id.clone(),
+            Document {
+                rtf: read_optional(&rtf_path).await?,
+                synopsis: read_optional(&synopsis_path).await?,
+            },
+        );
+        pending.extend(&item.children);
+    }
+
+    let title = bundle
+        .file_stem()
+        .map(|s| s.to_string_lossy().trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "Imported Project".to_string());
+
+    Ok(convert(title, &binder, &documents))
+}
+
+async fn find_scrivx(bundle: &Path) -> Result<std::path::PathBuf> {
+    let mut entries = tokio::fs::read_dir(bundle).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("scrivx"))
+        {
+            return Ok(path);
+        }
+    }
+    Err(Error::Import(format!(
+        "No .scrivx file found in {}",
+        bundle.display()
+    )))
+}
+
+/// Reads a document file, treating a missing file as no content. RTF is 7-bit,
+/// so a lossy decode never alters the text.
+async fn read_optional(path: &Path) -> Result<Option<String>> {
+    match tokio::fs::read(path).await {
+        Ok(bytes) => Ok(Some(String::from_utf8_lossy(&bytes).into_owned())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Parses the `.scrivx` XML into the Draft folder's items and the label table.
+pub fn parse_binder(scrivx: &str) -> Result<Binder> {
+    let root = xml::parse(scrivx)?;
+    let binder = root
+        .child("Binder")
+        .ok_or_else(|| Error::Import("The .scrivx file has no binder".to_string()))?;
+    let draft = binder
+        .children_named("BinderItem")
+        .find(|item| item.attr("Type") == Some("DraftFolder"))
+        .ok_or_else(|| Error::Import("The binder has no Draft folder".to_string()))?;
+
+    let labels = root
+        .find("LabelSettings")
+        .and_then(|settings| settings.child("Labels"))
+        .map(|labels| {
+            labels
+                .children_named("Label")
+                .filter_map(|label| {
+                    let id = label.attr("ID")?;
+                    Some((
+                        id.to_string(),
+                        Label {
+                            name: label.text().trim().to_string(),
+                            color: label.attr("Color").map(hex_color).unwrap_or_default(),
+                        },
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(Binder {
+        draft: binder_children(draft),
+        labels,
+    })
+}
+
+fn binder_children(item: &Element) -> Vec<BinderItem> {
+    let Some(children) = item.child("Children") else {
+        return Vec::new();
+    };
+    children
+        .children_named("BinderItem")
+        .filter_map(|child| {
+            let (id, legacy) = match (child.attr("UUID"), child.attr("ID")) {
+                (Some(uuid), _) => (uuid.to_string(), false),
+                (None, Some(id)) => (id.to_string(), true),
+                (None, None) => return None,
+            };
+            // The id names the item's files, so it must not be able to reach
+            // outside the bundle
+            let valid = if legacy {
+                !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit())
+            } else {
+                Uuid::try_parse(&id).is_ok()
+            };
+            if !valid {
+                log::warn!("Skipping binder item with invalid id `{}`", id);
+                return None;
+            }
+            Some(BinderItem {
+                id,
+                legacy,
+                title: child
+                    .child("Title")
+                    .map(|t| t.text().trim().to_string())
+                    .unwrap_or_default(),
+                label_id: child
+                    .child("MetaData")
+                    .and_then(|m| m.child("LabelID"))
+                    .map(|l| l.text().trim().to_string())
+                    .filter(|id| !id.is_empty() && id != NO_LABEL),
+                children: binder_children(child),
+            })
+        })
+        .collect()
+}
+
+/// Scrivener writes colours as three floats between 0 and 1.
+fn hex_color(value: &str) -> String {
+    let channels: Vec<u8> = value
+        .split_whitespace()
+        .filter_map(|c| c.parse::<f32>().ok())
+        .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+        .collect();
+    match channels.as_slice() {
+        [r, g, b, ..] => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        _ => String::new(),
+    }
+}
+
+/// Builds the import from a parsed binder and the documents keyed by item ID.
+pub fn convert(
+    title: String,
+    binder: &Binder,
+    documents: &HashMap<String, Document>,
+) -> ImportedProject {
+    let mut used_labels: Vec<&str> = Vec::new();
+    let nodes = convert_items(&binder.draft, binder, documents, &mut used_labels);
+
+    let mut seen = HashSet::new();
+    let plotlines = used_labels
+        .into_iter()
+        .filter(|id| seen.insert(*id))
+        .filter_map(|id| binder.labels.get(id))
+        .map(|label| Plotline {
+            id: label_tag(&label.name),
+            name: label.name.clone(),
+            color: label.color.clone(),
+        })
+        .collect();
+
+    ImportedProject {
+        title,
+        nodes,
+        plotlines,
+        ..Default::default()
+    }
+}
+
+fn convert_items<'a>(
+    items: &'a [BinderItem],
+    binder: &'a Binder,
+    documents: &HashMap<String, Document>,
+    used_labels: &mut Vec<&'a str>,
+) -> Vec<ImportedNode> {
+    items
+        .iter()
+        .map(|item| {
+            let document = documents.get(&item.id).cloned().unwrap_or_default();
+            let label = item
+                .label_id
+                .as_deref()
+                .and_then(|id| binder.labels.get_key_value(id));
+            if let Some((id, _)) = label {
+                used_labels.push(id.as_str());
+            }
+
+            ImportedNode {
+                title: if item.title.is_empty() {
+                    "Untitled".to_string()
+                } else {
+                    item.title.clone()
+                },
+                content: document
+                    .rtf
+                    .map(|rtf| render_editor_html(&rtf::to_blocks(&rtf)))
+                    .unwrap_or_default(),
+                synopsis: document
+                    .synopsis
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty()),
+                plotline_tag: label.map(|(_, label)| label_tag(&label.name)),
+                children: convert_items(&item.children, binder, documents, used_labels),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Plotline ID for a label: its name, lowercased, with runs of other characters
+/// turned into dashes.
+fn label_tag(name: &str) -> String {
+    let mut tag = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            tag.extend(c.to_lowercase());
+        } else if !tag.is_empty() && !tag.ends_with('-') {
+            tag.push('-');
+        }
+    }
+    let tag = tag.trim_end_matches('-');
+    if tag.is_empty() {
+        "label".to_string()
+    } else {
+        tag.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIVX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ScrivenerProject Version="2.0">
+  <Binder>
+    <BinderItem UUID="00000000-0000-4000-8000-0000000000D0" Type="DraftFolder">
+      <Title>Manuscript</Title>
+      <Children>
+        <BinderItem UUID="00000000-0000-4000-8000-0000000000A0" Type="Folder">
+          <Title>Chapter One</Title>
+          <MetaData><LabelID>1</LabelID></MetaData>
+          <Children>
+            <BinderItem UUID="00000000-0000-4000-8000-0000000000A1" Type="Text"><Title>The Door</Title>
+              <MetaData><LabelID>1</LabelID></MetaData></BinderItem>
+            <BinderItem UUID="00000000-0000-4000-8000-0000000000A2" Type="Text"><Title></Title>
+              <MetaData><LabelID>-1</LabelID></MetaData></BinderItem>
+          </Children>
+        </BinderItem>
+        <BinderItem UUID="00000000-0000-4000-8000-0000000000B0" Type="Text"><Title>Epilogue</Title>
+          <MetaData><LabelID>2</LabelID></MetaData></BinderItem>
+      </Children>
+    </BinderItem>
+    <BinderItem UUID="00000000-0000-4000-8000-0000000000E0" Type="ResearchFolder"><Title>Research</Title>
+      <Children><BinderItem UUID="00000000-0000-4000-8000-0000000000E1" Type="Text"><Title>Notes</Title></BinderItem></Children>
+    </BinderItem>
+  </Binder>
+  <LabelSettings>
+    <Title>Label</Title>
+    <Labels>
+      <Label ID="-1">No Label</Label>
+      <Label ID="1" Color="1.0 0.5 0.0">Main Plot</Label>
+      <Label ID="2" Color="0.2 0.4 0.6">Flash &amp; Back</Label>
+    </Labels>
+  </LabelSettings>
+</ScrivenerProject>"#;
+
+    #[test]
+    fn test_parse_binder() {
+        let binder = parse_binder(SCRIVX).unwrap();
+
+        let titles: Vec<&str> = binder.draft.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, vec!["Chapter One", "Epilogue"]);
+        assert_eq!(binder.draft[0].children.len(), 2);
+        assert_eq!(binder.draft[0].children[1].label_id, None);
+        assert!(!binder.draft[0].legacy);
+        assert_eq!(
+            binder.labels["2"],
+            Label {
+                name: "Flash & Back".to_string(),
+                color: "#336699".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_convert_documents() {
+        let binder = parse_binder(SCRIVX).unwrap();
+        let documents = HashMap::from([
+            (
+                "00000000-0000-4000-8000-0000000000A1".to_string(),
+                Document {
+                    rtf: Some(r"{\rtf1\ansi It \i opens\i0 .\par}".to_string()),
+                    synopsis: Some("Alice finds the door.\n".to_string()),
+                },
+            ),
+            (
+                "00000000-0000-4000-8000-0000000000B0".to_string(),
+                Document {
+                    rtf: Some(r"{\rtf1 One.\par Two.}".to_string()),
+                    synopsis: Some("  ".to_string()),
+                },
+            ),
+        ]);
+
+        let project = convert("Tales".to_string(), &binder, &documents);
+        assert_eq!(project.title, "Tales");
+
+        let chapter = &project.nodes[0];
+        assert_eq!(chapter.content, "");
+        assert_eq!(chapter.plotline_tag.as_deref(), Some("main-plot"));
+
+        let door = &chapter.children[0];
+        assert_eq!(door.content, "<p>It <em>opens</em>.</p>\n");
+        assert_eq!(door.synopsis.as_deref(), Some("Alice finds the door."));
+        assert_eq!(chapter.children[1].title, "Untitled");
+
+        let epilogue = &project.nodes[1];
+        assert_eq!(epilogue.content, "<p>One.</p>\n<p>Two.</p>\n");
+        assert_eq!(epilogue.synopsis, None);
+        assert_eq!(epilogue.plotline_tag.as_deref(), Some("flash-back"));
+
+        assert_eq!(
+            project.plotlines,
+            vec![
+                Plotline {
+                    id: "main-plot".to_string(),
+                    name: "Main Plot".to_string(),
+                    color: "#ff8000".to_string(),
+                },
+                Plotline {
+                    id: "flash-back".to_string(),
+                    name: "Flash & Back".to_string(),
+                    color: "#336699".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_legacy_ids_and_missing_draft() {
+        let legacy = r#"<ScrivenerProject><Binder><BinderItem ID="0" Type="DraftFolder"><Children>
+<BinderItem ID="3" Type="Text"><Title>Old</Title></BinderItem></Children></BinderItem></Binder></ScrivenerProject>"#;
+        let binder = parse_binder(legacy).unwrap();
+        assert_eq!(binder.draft[0].id, "3");
+        assert!(binder.draft[0].legacy);
+
+        assert!(parse_binder("<ScrivenerProject><Binder/></ScrivenerProject>").is_err());
+    }
+
+    #[test]
+    fn test_items_with_path_like_ids_are_dropped() {
+        let scrivx = r#"<ScrivenerProject><Binder><BinderItem ID="0" Type="DraftFolder"><Children>
+<BinderItem ID="../../x" Type="Text"><Title>Escape</Title></BinderItem>
+<BinderItem UUID="../Data" Type="Text"><Title>Escape</Title></BinderItem>
+<BinderItem ID="" Type="Text"><Title>Empty</Title></BinderItem>
+<BinderItem ID="7" Type="Text"><Title>Kept</Title></BinderItem>
+</Children></BinderItem></Binder></ScrivenerProject>"#;
+        let binder = parse_binder(scrivx).unwrap();
+        let titles: Vec<&str> = binder.draft.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, vec!["Kept"]);
+    }
+}
@@ -0,0 +1,307 @@
+//! Single-file manuscript reader for `.txt` and `.md` files.
+//!
+//! Lines matching the chapter pattern start a new top-level node and lines
+//! matching the optional scene pattern start a child node. A matched line becomes
+//! the node's title; separator lines with no text in them (`***`) give numbered
+//! titles instead ("Chapter 3", "Scene 2"). Markdown files are converted with the
+//! Markdown reader, plain text gets one paragraph per blank-line-separated block.
+
+use super::markdown;
+use super::{ImportedNode, ImportedProject};
+use crate::errors::{Error, Result};
+use crate::export::html::{render_editor_html, Block, Inline};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// How to recognise the lines that start a node.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SplitPattern {
+    /// `Chapter 1`, `CHAPTER 12: The Storm`
+    ChapterHeading,
+    /// Markdown headings of any level (`# Title`)
+    MarkdownHeading,
+    /// Lines of three or more asterisks (`***`, `* * *`)
+    Separator,
+    /// Custom regular expression, matched against each line.
+    Regex(String),
+}
+
+impl SplitPattern {
+    fn regex(&self) -> Result<Regex> {
+        let pattern = match self {
+            SplitPattern::ChapterHeading => r"(?i)^\s*chapter\s+\d+\b",
+            SplitPattern::MarkdownHeading => r"^#{1,6}\s",
+            SplitPattern::Separator => r"^\s*(?:\*\s*){3,}$",
+            SplitPattern::Regex(pattern) => pattern,
+        };
+        Regex::new(pattern).map_err(|e| Error::Validation(format!("Invalid split pattern: {}", e)))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TextSplitOptions {
+    pub chapter_pattern: SplitPattern,
+    #[serde(default)]
+    pub scene_pattern: Option<SplitPattern>,
+}
+
+/// Reads and splits a `.txt` or `.md` file. The project is named after the file.
+pub async fn read_text_file(path: &Path, options: &TextSplitOptions) -> Result<ImportedProject> {
+    let bytes = tokio::fs::read(path).await?;
+    let markdown = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .is_some_and(|ext| ext == "md" || ext == "markdown");
+    let title = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "Imported Project".to_string());
+
+    split_text(title, &String::from_utf8_lossy(&bytes), markdown, options)
+}
+
+/// Splits `text` into nodes. `markdown` selects Markdown conversion of the body.
+pub fn split_text(
+    title: String,
+    text: &str,
+    markdown: bool,
+    options: &TextSplitOptions,
+) -> Result<ImportedProject> {
+    let chapter_re = options.chapter_pattern.regex()?;
+    let scene_re = options
+        .scene_pattern
+        .as_ref()
+        .map(SplitPattern::regex)
+        .transpose()?;
+
+    let lines: Vec<&str> = text
+        .strip_prefix('\u{feff}')
+        .unwrap_or(text)
+        .lines()
+        .collect();
+    let (intro, chapters) = sections(&lines, &chapter_re, "Chapter");
+
+    let mut nodes = Vec::new();
+    if !is_blank(&intro) {
+        nodes.push(ImportedNode {
+            // Front matter before the first chapter, or the whole text if none matched
+            title: if chapters.is_empty() {
+                "Chapter 1".to_string()
+            } else {
+                "Untitled".to_string()
+            },
+            content: render(&intro, markdown),
+            ..Default::default()
+        });
+    }
+
+    for (chapter_title, chapter_lines) in chapters {
+        let node = match &scene_re {
+            Some(scene_re) => {
+                let (intro, scenes) = sections(&chapter_lines, scene_re, "Scene");
+                ImportedNode {
+                    title: chapter_title,
+                    content: render(&intro, markdown),
+                    children: scenes
+                        .into_iter()
+                        .map(|(title, scene_lines)| ImportedNode {
+                            title,
+                            content: render(&scene_lines, markdown),
+                            ..Default::default()
+                        })
+                        .collect(),
+                    ..Default::default()
+                }
+            }
+            None => ImportedNode {
+                title: chapter_title,
+                content: render(&chapter_lines, markdown),
+                ..Default::default()
+            },
+        };
+        nodes.push(node);
+    }
+
+    Ok(ImportedProject {
+        title,
+        nodes,
+        ..Default::default()
+    })
+}
+
+/// Splits lines at each match of `re`, returning the lines before the first match
+/// and one titled section per match. When the first match is a bare separator,
+/// the lines before it form the first section instead, as they open the text.
+fn sections<'a>(
+    lines: &[&'a str],
+    re: &Regex,
+    label: &str,
+) -> (Vec<&'a str>, Vec<(String, Vec<&'a str>)>) {
+    let mut intro = Vec::new();
+    let mut found: Vec<(Option<String>, Vec<&'a str>)> = Vec::new();
+
+    for &line in lines {
+        if re.is_match(line) {
+            found.push((heading_title(line), Vec::new()));
+        } else if let Some((_, section)) = found.last_mut() {
+            section.push(line);
+        } else {
+            intro.push(line);
+        }
+    }
+
+    let first_is_separator = found.first().is_some_and(|(title, _)| title.is_none());
+    if first_is_separator && !is_blank(&intro) {
+        found.insert(0, (None, std::mem::take(&mut intro)));
+    }
+
+    let sections = found
+        .into_iter()
+        .enumerate()
+        .map(|(index, (title, lines))| {
+            (
+                title.unwrap_or_else(|| format!("{} {}", label, index + 1)),
+                lines,
+            )
+        })
+        .collect();
+    (intro, sections)
+}
+
+/// Title of a matched line: the line without Markdown heading marks, if it has
+/// any letters or digits.
+fn heading_title(line: &str) -> Option<String> {
+    let title = line.trim().trim_start_matches('#').trim();
+    title
+        .chars()
+        .any(char::is_alphanumeric)
+        .then(|| title.to_string())
+}
+
+fn is_blank(lines: &[&str]) -> bool {
+    lines.iter().all(|line| line.trim().is_empty())
+}
+
+fn render(lines: &[&str], markdown: bool) -> String {
+    if markdown {
+        return markdown::to_editor_html(&lines.join("\n"));
+    }
+
+    // Blank lines separate paragraphs; without any, every line is one
+    let has_blank_lines = lines
+        .iter()
+        .skip_while(|line| line.trim().is_empty())
+        .any(|line| line.trim().is_empty());
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in lines.iter().map(|line| line.trim()) {
+        if !line.is_empty() {
+            paragraph.push(line);
+        }
+        if (line.is_empty() || !has_blank_lines) && !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(vec![Inline::Text(paragraph.join(" "))]));
+            paragraph.clear();
+        }
+    }
+    if !paragraph.is_empty() {
+        blocks.push(Block::Paragraph(vec![Inline::Text(paragraph.join(" "))]));
+    }
+    render_editor_html(&blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(nodes: &[ImportedNode]) -> Vec<&str> {
+        nodes.iter().map(|n| n.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_chapter_preset_with_separator_scenes() {
+        let text =
+            "Dedication.\n\nCHAPTER 1: Arrival\nThe train <stopped>.\nIt was late.\n\n* * *\n\
+                    Morning came.\nChapter 2\n\nShe left\nquietly.\n\nThe end.\n";
+        let options = TextSplitOptions {
+            chapter_pattern: SplitPattern::ChapterHeading,
+            scene_pattern: Some(SplitPattern::Separator),
+        };
+        let project = split_text("Book".to_string(), text, false, &options).unwrap();
+
+        assert_eq!(
+            titles(&project.nodes),
+            vec!["Untitled", "CHAPTER 1: Arrival", "Chapter 2"]
+        );
+        assert_eq!(project.nodes[0].content, "<p>Dedication.</p>\n");
+
+        let first = &project.nodes[1];
+        assert_eq!(first.content, "");
+        assert_eq!(titles(&first.children), vec!["Scene 1", "Scene 2"]);
+        assert_eq!(
+            first.children[0].content,
+            "<p>The train &lt;stopped&gt;. It was late.</p>\n"
+        );
+        assert_eq!(first.children[1].content, "<p>Morning came.</p>\n");
+
+        let second = &project.nodes[2];
+        assert!(second.children.is_empty());
+        assert_eq!(
+            second.content,
+            "<p>She left quietly.</p>\n<p>The end.</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_separator_chapters_are_numbered() {
+        let options = TextSplitOptions {
+            chapter_pattern: SplitPattern::Separator,
+            scene_pattern: None,
+        };
+        let project = split_text(
+            "Book".to_string(),
+            "One.\nTwo.\n***\nThree.\n",
+            false,
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(titles(&project.nodes), vec!["Chapter 1", "Chapter 2"]);
+        assert_eq!(project.nodes[0].content, "<p>One.</p>\n<p>Two.</p>\n");
+    }
+
+    #[test]
+    fn test_markdown_headings_and_custom_scene_pattern() {
+        let options = TextSplitOptions {
+            chapter_pattern: SplitPattern::MarkdownHeading,
+            scene_pattern: Some(SplitPattern::Regex(r"^Scene:".to_string())),
+        };
+        let project = split_text(
+            "Book".to_string(),
+            "# The *Start*\nIntro **bold**.\nScene: Dawn\nLight.\n",
+            true,
+            &options,
+        )
+        .unwrap();
+
+        let chapter = &project.nodes[0];
+        assert_eq!(chapter.title, "The *Start*");
+        assert_eq!(chapter.content, "<p>Intro <strong>bold</strong>.</p>\n");
+        assert_eq!(chapter.children[0].title, "Scene: Dawn");
+        assert_eq!(chapter.children[0].content, "<p>Light.</p>\n");
+    }
+
+    #[test]
+    fn test_invalid_regex() {
+        let options = TextSplitOptions {
+            chapter_pattern: SplitPattern::Regex("(".to_string()),
+            scene_pattern: None,
+        };
+        assert!(matches!(
+            split_text("Book".to_string(), "text", false, &options),
+            Err(Error::Validation(_))
+        ));
+    }
+}
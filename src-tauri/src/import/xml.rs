@@ -0,0 +1,227 @@
+//! Small XML tree for the importers.
+//!
+//! Scrivener binders, Word documents and EPUB packages are small enough to be held
+//! in memory, and walking a tree is far easier than driving a pull parser in every
+//! importer. Names are stored without their namespace prefix (`w:p` becomes `p`),
+//! which is enough to tell the vocabularies used by each format apart.
+
+use crate::errors::{Error, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum XmlNode {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<XmlNode>,
+}
+
+impl Element {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Child elements, in document order.
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            XmlNode::Element(e) => Some(e),
+            XmlNode::Text(_) => None,
+        })
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.elements().find(|e| e.name == name)
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.elements().filter(move |e| e.name == name)
+    }
+
+    /// First element named `name` at any depth below this one, depth-first.
+    pub fn find(&self, name: &str) -> Option<&Element> {
+        self.elements().find_map(|e| {
+            if e.name == name {
+                Some(e)
+            } else {
+                e.find(name)
+            }
+        })
+    }
+
+    /// Concatenated text of every descendant.
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        self.collect_text(&mut out);
+        out
+    }
+
+    fn collect_text(&self, out: &mut String) {
+        for node in &self.children {
+            match node {
+                XmlNode::Text(t) => out.push_str(t),
+                XmlNode::Element(e) => e.collect_text(out),
+            }
+        }
+    }
+}
+
+/// Parses a document and returns its root element.
+pub fn parse(xml: &str) -> Result<Element> {
+    let mut reader = Reader::from_str(xml);
+    // Parents of the element being filled; the bottom entry collects the root
+    let mut stack: Vec<Element> = vec![Element::default()];
+
+    loop {
+        let event = reader.read_event().map_err(|e| {
+            Error::Import(format!(
+                "Malformed XML at byte {}: {}",
+                reader.buffer_position(),
+                e
+            ))
+        })?;
+        match event {
+            Event::Start(start) => stack.push(element(&start)?),
+            Event::Empty(start) => {
+                let e = element(&start)?;
+                push_node(&mut stack, XmlNode::Element(e));
+            }
+            Event::End(_) => {
+                if stack.len() < 2 {
+                    return Err(Error::Import("Unbalanced XML end tag".to_string()));
+                }
+                let finished = stack.pop().expect("stack holds at least two elements");
+                push_node(&mut stack, XmlNode::Element(finished));
+            }
+            Event::Text(text) => {
+                let text = text.decode().map_err(|e| Error::Import(e.to_string()))?;
+                push_text(&mut stack, &text);
+            }
+            Event::CData(data) => {
+                let text = data.decode().map_err(|e| Error::Import(e.to_string()))?;
+                push_text(&mut stack, &text);
+            }
+            Event::GeneralRef(reference) => {
+                let resolved = match reference
+                    .resolve_char_ref()
+                    .map_err(|e| Error::Import(e.to_string()))?
+                {
+                    Some(c) => c.to_string(),
+                    None => {
+                        let name = reference
+                            .decode()
+                            .map_err(|e| Error::Import(e.to_string()))?;
+                        predefined_entity(&name).to_string()
+                    }
+                };
+                push_text(&mut stack, &resolved);
+            }
+            Event::Eof => break,
+            // Declarations, comments, processing instructions and doctypes
+            _ => {}
+        }
+    }
+
+    let document = stack.pop().filter(|_| stack.is_empty()).ok_or_else(|| {
+        Error::Import("XML document ended before all elements were closed".to_string())
+    })?;
+    document
+        .children
+        .into_iter()
+        .find_map(|node| match node {
+            XmlNode::Element(e) => Some(e),
+            XmlNode::Text(_) => None,
+        })
+        .ok_or_else(|| Error::Import("XML document has no root element".to_string()))
+}
+
+fn element(start: &BytesStart) -> Result<Element> {
+    let mut attributes = Vec::new();
+    for attr in start.attributes() {
+        let attr = attr.map_err(|e| Error::Import(e.to_string()))?;
+        let key = local_name(attr.key.as_ref());
+        let value = attr
+            .unescape_value()
+            .map_err(|e| Error::Import(e.to_string()))?;
+        attributes.push((key, value.into_owned()));
+    }
+    Ok(Element {
+        name: local_name(start.name().as_ref()),
+        attributes,
+        children: Vec::new(),
+    })
+}
+
+fn local_name(qualified: &[u8]) -> String {
+    let name = String::from_utf8_lossy(qualified);
+    match name.rsplit_once(':') {
+        Some((_, local)) => local.to_string(),
+        None => name.into_owned(),
+    }
+}
+
+/// Unknown entities (XHTML's `&nbsp;` without a DTD) are kept as a space.
+fn predefined_entity(name: &str) -> &'static str {
+    match name {
+        "amp" => "&",
+        "lt" => "<",
+        "gt" => ">",
+        "quot" => "\"",
+        "apos" => "'",
+        "nbsp" => "\u{a0}",
+        _ => " ",
+    }
+}
+
+fn push_node(stack: &mut [Element], node: XmlNode) {
+    if let Some(parent) = stack.last_mut() {
+        parent.children.push(node);
+    }
+}
+
+fn push_text(stack: &mut [Element], text: &str) {
+    if let Some(parent) = stack.last_mut() {
+        if let Some(XmlNode::Text(prev)) = parent.children.last_mut() {
+            prev.push_str(text);
+        } else {
+            parent.children.push(XmlNode::Text(text.to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tree() {
+        let root = parse(
+            r#"<?xml version="1.0"?>
+<w:document xmlns:w="ns"><w:body><w:p w:val="a &amp; b"><w:t>Tom &amp; Jerry&#8217;s</w:t><w:br/></w:p></w:body></w:document>"#,
+        )
+        .unwrap();
+
+        assert_eq!(root.name, "document");
+        let p = root.find("p").unwrap();
+        assert_eq!(p.attr("val"), Some("a & b"));
+        assert_eq!(p.text(), "Tom & Jerry\u{2019}s");
+        assert_eq!(
+            p.elements().map(|e| e.name.as_str()).collect::<Vec<_>>(),
+            vec!["t", "br"]
+        );
+    }
+
+    #[test]
+    fn test_rejects_unclosed_document() {
+        assert!(parse("<a><b></b>").is_err());
+        assert!(parse("").is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::intelligence::graph::build_character_graph_cached;
+use crate::intelligence::graph::{build_character_graph_cached, CachedMentions};
 use crate::intelligence::models::CharacterGraphPayload;
 use crate::intelligence::scanner::CharacterScanner;
 use crate::models::utils::WordIndexer;
@@ -66,7 +66,13 @@ impl IntelligenceCoordinator {
 
         // 3. Process Chapters
         let chapter_mentions = self
-            .process_chapters(root_path, metadata, scanner_arc, scanner_hash, &options)
+            .process_chapters(
+                root_path,
+                metadata,
+                scanner_arc,
+                scanner_hash,
+                options.chapter_filter.as_ref(),
+            )
             .await?;
 
         // 4. Build Graph
@@ -79,6 +85,24 @@ impl IntelligenceCoordinator {
         )
     }
 
+    /// Scan every chapter of the manifest and return the mentions per chapter ID,
+    /// sorted by character offset. Shares the scanner and content caches with
+    /// `analyze_project`.
+    pub async fn chapter_mentions(
+        &self,
+        project_id: Uuid,
+        root_path: &std::path::Path,
+        metadata: &ProjectMetadata,
+    ) -> crate::errors::Result<HashMap<String, CachedMentions>> {
+        if metadata.characters.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let (scanner_arc, scanner_hash) = self.get_or_create_scanner(project_id, metadata).await?;
+        self.process_chapters(root_path, metadata, scanner_arc, scanner_hash, None)
+            .await
+    }
+
     async fn get_or_create_scanner(
         &self,
         project_id: Uuid,
@@ -135,14 +159,14 @@ impl IntelligenceCoordinator {
         metadata: &ProjectMetadata,
         scanner: Arc<CharacterScanner>,
         scanner_hash: u64,
-        options: &AnalysisOptions,
+        chapter_filter: Option<&HashSet<String>>,
     ) -> crate::errors::Result<HashMap<String, Arc<Vec<(usize, usize, Uuid)>>>> {
         let repo = LocalFileRepository;
         let mut tasks = Vec::new();
 
         // 1. Identify valid chapters
         for chapter in &metadata.manifest.chapters {
-            if let Some(ref filter) = options.chapter_filter {
+            if let Some(filter) = chapter_filter {
                 if !filter.contains(chapter.id.as_str()) {
                     continue;
                 }
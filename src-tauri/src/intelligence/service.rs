@@ -1,6 +1,7 @@
 use crate::intelligence::coordinator::{
     ChapterContentCache, IntelligenceCache, IntelligenceCoordinator,
 };
+use crate::intelligence::graph::CachedMentions;
 use crate::intelligence::models::CharacterGraphPayload;
 use crate::models::ProjectMetadata;
 use std::collections::HashMap;
@@ -37,6 +38,22 @@ impl IntelligenceService {
             .analyze_project(project_id, root_path, metadata, options)
             .await
     }
+
+    pub async fn chapter_mentions(
+        &self,
+        project_id: Uuid,
+        root_path: &std::path::Path,
+        metadata: &ProjectMetadata,
+    ) -> crate::errors::Result<HashMap<String, CachedMentions>> {
+        let coordinator = IntelligenceCoordinator::new(
+            self.intelligence_cache.clone(),
+            self.chapter_content_cache.clone(),
+        );
+
+        coordinator
+            .chapter_mentions(project_id, root_path, metadata)
+            .await
+    }
 }
 
 impl Default for IntelligenceService {
@@ -1,5 +1,7 @@
 pub mod commands;
 pub mod errors;
+pub mod export;
+pub mod import;
 pub mod models;
 pub mod validation;
 
@@ -11,6 +13,7 @@ pub mod intelligence;
 pub mod project;
 
 use crate::project::manager::ProjectManager;
+use tauri::Manager;
 
 pub struct AppState {
     pub projects: ProjectManager,
@@ -44,14 +47,18 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             commands::create_project,
             commands::load_project,
+            commands::close_project,
             commands::update_manifest,
             commands::load_chapter_content,
             commands::save_chapter,
             commands::delete_node,
             commands::save_character,
             commands::delete_character,
+            commands::export_characters,
+            commands::import_characters,
             commands::update_project_settings,
             commands::update_plotlines,
+            commands::update_compile_presets,
             commands::create_node,
             commands::update_node_metadata,
             commands::get_research_artifacts,
@@ -65,14 +72,36 @@ pub fn run() {
             commands::create_snapshot,
             commands::restore_snapshot,
             commands::branch_snapshot,
+            commands::create_milestone,
+            commands::list_milestones,
+            commands::restore_milestone,
+            commands::restore_milestone_chapter,
             commands::analyze_character_graph,
+            commands::compile_project,
+            commands::export_character_bible,
+            commands::export_timeline,
+            commands::export_outline,
+            commands::import_scrivener,
+            commands::import_docx,
+            commands::import_markdown_folder,
+            commands::preview_text_import,
+            commands::import_text,
+            commands::import_epub,
             #[cfg(debug_assertions)]
             commands::seed_demo_project
         ])
-        .run(tauri::generate_context!())
+        .build(tauri::generate_context!())
         .unwrap_or_else(|e| {
             log::error!("Fatal: Failed to start application: {}", e);
             eprintln!("Fatal: Failed to start application: {}", e);
             std::process::exit(1);
+        })
+        .run(|app, event| {
+            // Metadata changes are written with a delay; save what is still pending.
+            // Failures are logged by `flush_all`.
+            if let tauri::RunEvent::Exit = event {
+                let state = app.state::<AppState>();
+                let _ = tauri::async_runtime::block_on(state.projects.flush_all());
+            }
         });
 }
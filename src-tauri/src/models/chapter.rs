@@ -28,6 +28,9 @@ pub struct Chapter {
     /// POV character ID (for simultaneous-scene paradox detection)
     #[serde(default)]
     pub pov_character_id: Option<String>,
+    /// Short summary of the node, e.g. brought over from a Scrivener synopsis
+    #[serde(default)]
+    pub synopsis: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -0,0 +1,294 @@
+use serde::{Deserialize, Serialize};
+
+/// Named set of compile options, stored in `project.json` so the same book can be
+/// exported for different audiences without touching the manifest.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct CompilePreset {
+    pub id: String,
+    pub name: String,
+    /// Nodes to compile, each with its whole subtree. Empty means the whole manifest.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Subtrees left out of the compile (notes, cut scenes...).
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Heading level (1-6) for each manifest depth, relative to the compiled roots.
+    /// `null` renders the node untitled, joined to the previous one by the scene
+    /// separator. Depths past the end of the list reuse the last entry.
+    #[serde(default = "default_heading_levels")]
+    pub heading_levels: Vec<Option<u8>>,
+    /// Numbering applied to level-1 headings.
+    #[serde(default)]
+    pub numbering: ChapterNumbering,
+    /// Word placed before the number, e.g. "Chapter". May be empty.
+    #[serde(default = "default_chapter_label")]
+    pub chapter_label: String,
+    #[serde(default = "default_scene_separator")]
+    pub scene_separator: String,
+    /// Title page (and contact block for manuscript formats).
+    #[serde(default = "default_true")]
+    pub front_matter: bool,
+    /// Closing "The End" page or marker.
+    #[serde(default)]
+    pub back_matter: bool,
+    /// Typeface for manuscript formats (DOCX).
+    #[serde(default)]
+    pub manuscript_font: ManuscriptFont,
+    #[serde(default)]
+    pub pdf: PdfLayout,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ChapterNumbering {
+    #[default]
+    None,
+    Arabic,
+    Roman,
+    SpelledOut,
+}
+
+/// Typeface accepted by agents and editors for manuscript submissions.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ManuscriptFont {
+    #[default]
+    Courier,
+    Times,
+}
+
+/// Page setup for PDF proofs. Lengths are in millimetres, font size in points.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case", default)]
+pub struct PdfLayout {
+    pub page_width: f64,
+    pub page_height: f64,
+    pub margin_top: f64,
+    pub margin_bottom: f64,
+    /// Binding-side margin, mirrored on facing pages.
+    pub margin_inner: f64,
+    pub margin_outer: f64,
+    pub font: PdfFont,
+    pub font_size: f64,
+    /// Line height as a multiple of the font size.
+    pub line_spacing: f64,
+    /// Book title on even pages, chapter title on odd pages.
+    pub running_headers: bool,
+}
+
+/// Standard PDF fonts, available in every reader without embedding.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PdfFont {
+    #[default]
+    Times,
+    Helvetica,
+    Courier,
+}
+
+impl Default for PdfLayout {
+    /// US trade paperback (6 x 9 in).
+    fn default() -> Self {
+        Self {
+            page_width: 152.4,
+            page_height: 228.6,
+            margin_top: 18.0,
+            margin_bottom: 20.0,
+            margin_inner: 20.0,
+            margin_outer: 15.0,
+            font: PdfFont::Times,
+            font_size: 11.0,
+            line_spacing: 1.35,
+            running_headers: true,
+        }
+    }
+}
+
+fn default_heading_levels() -> Vec<Option<u8>> {
+    vec![Some(1), Some(2), Some(3)]
+}
+
+fn default_chapter_label() -> String {
+    "Chapter".to_string()
+}
+
+fn default_scene_separator() -> String {
+    "* * *".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for CompilePreset {
+    fn default() -> Self {
+        Self {
+            id: "default".to_string(),
+            name: "Default".to_string(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            heading_levels: default_heading_levels(),
+            numbering: ChapterNumbering::None,
+            chapter_label: default_chapter_label(),
+            scene_separator: default_scene_separator(),
+            front_matter: true,
+            back_matter: false,
+            manuscript_font: ManuscriptFont::Courier,
+            pdf: PdfLayout::default(),
+        }
+    }
+}
+
+impl CompilePreset {
+    /// Presets created with every new project (and added to older ones on load).
+    pub fn builtin() -> Vec<CompilePreset> {
+        vec![
+            CompilePreset {
+                id: "submission".to_string(),
+                name: "Submission".to_string(),
+                heading_levels: vec![Some(1), None],
+                numbering: ChapterNumbering::SpelledOut,
+                scene_separator: "#".to_string(),
+                back_matter: true,
+                ..Default::default()
+            },
+            CompilePreset {
+                id: "beta-reader".to_string(),
+                name: "Beta Reader".to_string(),
+                numbering: ChapterNumbering::Arabic,
+                ..Default::default()
+            },
+        ]
+    }
+
+    /// Heading level for a node at `depth`, `None` when the node is untitled.
+    pub fn heading_level(&self, depth: usize) -> Option<u8> {
+        let level = self
+            .heading_levels
+            .get(depth)
+            .or(self.heading_levels.last())
+            .copied()
+            .unwrap_or(Some(1))?;
+        Some(level.clamp(1, 6))
+    }
+
+    /// Title of the `number`-th (1-based) level-1 heading.
+    pub fn chapter_title(&self, number: u32, title: &str) -> String {
+        let numeral = match self.numbering {
+            ChapterNumbering::None => return title.to_string(),
+            ChapterNumbering::Arabic => number.to_string(),
+            ChapterNumbering::Roman => to_roman(number),
+            ChapterNumbering::SpelledOut => spell_out(number),
+        };
+        let label = format!("{} {}", self.chapter_label.trim(), numeral)
+            .trim()
+            .to_string();
+        if title.trim().is_empty() {
+            label
+        } else {
+            format!("{}: {}", label, title)
+        }
+    }
+}
+
+fn to_roman(mut n: u32) -> String {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut out = String::new();
+    for (value, numeral) in NUMERALS {
+        while n >= value {
+            out.push_str(numeral);
+            n -= value;
+        }
+    }
+    out
+}
+
+fn spell_out(n: u32) -> String {
+    const ONES: [&str; 20] = [
+        "Zero",
+        "One",
+        "Two",
+        "Three",
+        "Four",
+        "Five",
+        "Six",
+        "Seven",
+        "Eight",
+        "Nine",
+        "Ten",
+        "Eleven",
+        "Twelve",
+        "Thirteen",
+        "Fourteen",
+        "Fifteen",
+        "Sixteen",
+        "Seventeen",
+        "Eighteen",
+        "Nineteen",
+    ];
+    const TENS: [&str; 10] = [
+        "", "", "Twenty", "Thirty", "Forty", "Fifty", "Sixty", "Seventy", "Eighty", "Ninety",
+    ];
+
+    match n {
+        0..=19 => ONES[n as usize].to_string(),
+        20..=99 => {
+            let tens = TENS[(n / 10) as usize];
+            match n % 10 {
+                0 => tens.to_string(),
+                ones => format!("{}-{}", tens, ONES[ones as usize]),
+            }
+        }
+        100..=999 => match n % 100 {
+            0 => format!("{} Hundred", ONES[(n / 100) as usize]),
+            rest => format!("{} Hundred {}", ONES[(n / 100) as usize], spell_out(rest)),
+        },
+        _ => n.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chapter_numbering() {
+        let mut preset = CompilePreset {
+            numbering: ChapterNumbering::Roman,
+            ..Default::default()
+        };
+        assert_eq!(preset.chapter_title(14, "Storm"), "Chapter XIV: Storm");
+
+        preset.numbering = ChapterNumbering::SpelledOut;
+        assert_eq!(preset.chapter_title(42, ""), "Chapter Forty-Two");
+        assert_eq!(spell_out(115), "One Hundred Fifteen");
+
+        preset.chapter_label = String::new();
+        preset.numbering = ChapterNumbering::Arabic;
+        assert_eq!(preset.chapter_title(3, "Dawn"), "3: Dawn");
+    }
+
+    #[test]
+    fn test_heading_levels_reuse_last_entry() {
+        let preset = &CompilePreset::builtin()[0];
+        assert_eq!(preset.heading_level(0), Some(1));
+        assert_eq!(preset.heading_level(1), None);
+        assert_eq!(preset.heading_level(4), None);
+        assert_eq!(CompilePreset::default().heading_level(7), Some(3));
+    }
+}
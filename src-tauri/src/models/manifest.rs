@@ -1,14 +1,72 @@
 use super::chapter::Chapter;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
-#[serde(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case", from = "ManifestFile")]
 pub struct Manifest {
     pub chapters: Vec<Chapter>,
+    /// Position of each chapter in `chapters`, by id. The methods below keep it
+    /// current; code that edits `chapters` directly should call `reindex`.
+    /// Lookups check the entry they find, so a stale index is only slower.
+    #[serde(skip)]
+    index: HashMap<String, usize>,
+}
+
+/// On-disk shape of `Manifest`, so deserializing builds the index.
+#[derive(Deserialize)]
+struct ManifestFile {
+    chapters: Vec<Chapter>,
+}
+
+impl From<ManifestFile> for Manifest {
+    fn from(file: ManifestFile) -> Self {
+        Self::new(file.chapters)
+    }
 }
 
 impl Manifest {
+    pub fn new(chapters: Vec<Chapter>) -> Self {
+        let mut manifest = Self {
+            chapters,
+            index: HashMap::new(),
+        };
+        manifest.reindex();
+        manifest
+    }
+
+    pub fn reindex(&mut self) {
+        self.index = self
+            .chapters
+            .iter()
+            .enumerate()
+            .map(|(position, c)| (c.id.clone(), position))
+            .collect();
+    }
+
+    fn position(&self, id: &str) -> Option<usize> {
+        self.index
+            .get(id)
+            .copied()
+            .filter(|&position| self.chapters.get(position).is_some_and(|c| c.id == id))
+            .or_else(|| self.chapters.iter().position(|c| c.id == id))
+    }
+
+    pub fn chapter(&self, id: &str) -> Option<&Chapter> {
+        self.position(id).map(|position| &self.chapters[position])
+    }
+
+    pub fn chapter_mut(&mut self, id: &str) -> Option<&mut Chapter> {
+        self.position(id)
+            .map(|position| &mut self.chapters[position])
+    }
+
+    pub fn push(&mut self, chapter: Chapter) {
+        self.index.insert(chapter.id.clone(), self.chapters.len());
+        self.chapters.push(chapter);
+    }
+
     pub fn create_chapter(&self, parent_id: Option<String>, title: String) -> Chapter {
         let new_id = format!("chapter-{}", Uuid::new_v4());
         let filename = format!("{}.md", new_id);
@@ -39,6 +97,7 @@ impl Manifest {
             plotline_tag: None,
             depends_on: None,
             pov_character_id: None,
+            synopsis: None,
         }
     }
 
@@ -75,6 +134,7 @@ impl Manifest {
                 true
             }
         });
+        self.reindex();
 
         filenames
     }
@@ -98,6 +158,7 @@ mod tests {
             plotline_tag: None,
             depends_on: None,
             pov_character_id: None,
+            synopsis: None,
         }
     }
 
@@ -111,15 +172,9 @@ mod tests {
         //     - c1_1 (c1_1.md)
         //  - c2 (c2.md)
 
-        manifest
-            .chapters
-            .push(create_dummy_chapter("c1", None, "c1.md"));
-        manifest
-            .chapters
-            .push(create_dummy_chapter("c1_1", Some("c1"), "c1_1.md"));
-        manifest
-            .chapters
-            .push(create_dummy_chapter("c2", None, "c2.md"));
+        manifest.push(create_dummy_chapter("c1", None, "c1.md"));
+        manifest.push(create_dummy_chapter("c1_1", Some("c1"), "c1_1.md"));
+        manifest.push(create_dummy_chapter("c2", None, "c2.md"));
 
         let removed_files = manifest.remove_node_recursively("c1".to_string());
 
@@ -130,5 +185,31 @@ mod tests {
 
         assert_eq!(manifest.chapters.len(), 1);
         assert_eq!(manifest.chapters[0].id, "c2");
+        assert_eq!(
+            manifest.chapter("c2").map(|c| c.filename.as_str()),
+            Some("c2.md")
+        );
+        assert!(manifest.chapter("c1_1").is_none());
+    }
+
+    #[test]
+    fn test_lookup_survives_direct_edits() {
+        let mut manifest: Manifest =
+            serde_json::from_str(r#"{ "chapters": [] }"#).expect("valid manifest");
+        manifest.push(create_dummy_chapter("a", None, "a.md"));
+        manifest.push(create_dummy_chapter("b", None, "b.md"));
+
+        // Reordering without reindexing leaves the index stale
+        manifest.chapters.swap(0, 1);
+        assert_eq!(
+            manifest.chapter("a").map(|c| c.filename.as_str()),
+            Some("a.md")
+        );
+
+        manifest
+            .chapter_mut("b")
+            .expect("chapter exists")
+            .word_count = 3;
+        assert_eq!(manifest.chapters[0].word_count, 3);
     }
 }
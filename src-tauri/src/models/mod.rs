@@ -1,12 +1,16 @@
 pub mod chapter;
 pub mod character;
+pub mod compile;
 pub mod manifest;
 pub mod project;
 pub mod utils;
 
 pub use chapter::{Chapter, NodeMetadataUpdate};
 pub use character::{Character, CharacterEngine, CharacterRole};
+pub use compile::{ChapterNumbering, CompilePreset, ManuscriptFont, PdfFont, PdfLayout};
 pub use manifest::Manifest;
-pub use project::{Plotline, ProjectMetadata, ProjectSettings};
+pub use project::{
+    Plotline, ProjectMetadata, ProjectSettings, SnapshotPolicy, CURRENT_SCHEMA_VERSION,
+};
 pub use utils::count_words;
 pub mod research;
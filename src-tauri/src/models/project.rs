@@ -1,12 +1,21 @@
 use super::character::Character;
+use super::compile::CompilePreset;
 use super::manifest::Manifest;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
+/// Version of the `project.json` layout written by this build. Bump it together
+/// with a new entry in `storage::migrations`.
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ProjectMetadata {
+    /// Files written before versioning have no field and count as version 0.
+    #[serde(default)]
+    pub schema_version: u32,
     pub id: Uuid,
     pub title: String,
     pub author: String,
@@ -18,6 +27,31 @@ pub struct ProjectMetadata {
     pub characters: Vec<Character>,
     #[serde(default)]
     pub plotlines: Vec<Plotline>,
+    #[serde(default = "default_compile_presets")]
+    pub compile_presets: Vec<CompilePreset>,
+    /// Position of each character in `characters`, by id. Like the manifest's
+    /// index it is checked on use; call `reindex` after editing `characters`
+    /// directly.
+    #[serde(skip)]
+    character_index: HashMap<Uuid, usize>,
+}
+
+/// What `project.json` holds: the metadata without `characters`, which are
+/// stored one file each in `characters/`, but with their order.
+#[derive(Serialize)]
+struct ProjectFile<'a> {
+    schema_version: u32,
+    id: &'a Uuid,
+    title: &'a str,
+    author: &'a str,
+    created_at: &'a DateTime<Utc>,
+    updated_at: &'a DateTime<Utc>,
+    settings: &'a ProjectSettings,
+    manifest: &'a Manifest,
+    /// Character ids in the order of `characters`.
+    character_order: Vec<Uuid>,
+    plotlines: &'a [Plotline],
+    compile_presets: &'a [CompilePreset],
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -26,13 +60,49 @@ pub struct ProjectSettings {
     pub daily_target: u32,
     #[serde(default = "default_word_target")]
     pub word_target: u32,
+    #[serde(default)]
+    pub snapshots: SnapshotPolicy,
+}
+
+/// When saving a chapter takes a snapshot, and which snapshots are kept as
+/// they age.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case", default)]
+pub struct SnapshotPolicy {
+    /// Every snapshot younger than this is kept.
+    pub keep_all_minutes: u32,
+    /// Past that, the latest snapshot of each hour is kept up to this age.
+    pub hourly_for_days: u32,
+    /// Then the latest of each day up to this age, and of each week after.
+    pub daily_for_days: u32,
+    /// A save takes a snapshot once this long has passed since the latest
+    /// one...
+    pub min_interval_seconds: u32,
+    /// ...or once this many characters have been added or removed since.
+    pub min_change_chars: u32,
+}
+
+impl Default for SnapshotPolicy {
+    fn default() -> Self {
+        Self {
+            keep_all_minutes: 60,
+            hourly_for_days: 1,
+            daily_for_days: 30,
+            min_interval_seconds: 300,
+            min_change_chars: 500,
+        }
+    }
 }
 
 fn default_word_target() -> u32 {
     50000
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+fn default_compile_presets() -> Vec<CompilePreset> {
+    CompilePreset::builtin()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct Plotline {
     pub id: String,
@@ -45,6 +115,7 @@ impl Default for ProjectSettings {
         Self {
             daily_target: 2000,
             word_target: default_word_target(),
+            snapshots: SnapshotPolicy::default(),
         }
     }
 }
@@ -53,6 +124,7 @@ impl ProjectMetadata {
     pub fn new(title: String, author: String) -> Self {
         let now = Utc::now();
         Self {
+            schema_version: CURRENT_SCHEMA_VERSION,
             id: Uuid::new_v4(),
             title,
             author,
@@ -66,25 +138,98 @@ impl ProjectMetadata {
                 name: "Main Plot".to_string(),
                 color: "#3b82f6".to_string(),
             }],
+            compile_presets: default_compile_presets(),
+            character_index: HashMap::new(),
         }
     }
 
+    /// Serializes the contents of `project.json`.
+    pub fn to_project_file(&self) -> serde_json::Result<String> {
+        // No `..`: a new field fails to compile here until it is given a place
+        let ProjectMetadata {
+            schema_version,
+            id,
+            title,
+            author,
+            created_at,
+            updated_at,
+            settings,
+            manifest,
+            characters,
+            plotlines,
+            compile_presets,
+            character_index: _,
+        } = self;
+        serde_json::to_string_pretty(&ProjectFile {
+            schema_version: *schema_version,
+            id,
+            title,
+            author,
+            created_at,
+            updated_at,
+            settings,
+            manifest,
+            character_order: characters.iter().map(|c| c.id).collect(),
+            plotlines,
+            compile_presets,
+        })
+    }
+
+    /// Rebuilds the chapter and character indexes.
+    pub fn reindex(&mut self) {
+        self.manifest.reindex();
+        self.reindex_characters();
+    }
+
+    fn reindex_characters(&mut self) {
+        self.character_index = self
+            .characters
+            .iter()
+            .enumerate()
+            .map(|(position, c)| (c.id, position))
+            .collect();
+    }
+
+    fn character_position(&self, id: Uuid) -> Option<usize> {
+        self.character_index
+            .get(&id)
+            .copied()
+            .filter(|&position| self.characters.get(position).is_some_and(|c| c.id == id))
+            .or_else(|| self.characters.iter().position(|c| c.id == id))
+    }
+
     pub fn add_or_update_character(&mut self, character: Character) {
-        if let Some(idx) = self.characters.iter().position(|c| c.id == character.id) {
+        if let Some(idx) = self.character_position(character.id) {
             self.characters[idx] = character;
         } else {
+            self.character_index
+                .insert(character.id, self.characters.len());
             self.characters.push(character);
         }
     }
 
-    pub fn remove_character(&mut self, character_id: Uuid) -> Result<(), String> {
-        let initial_len = self.characters.len();
-        self.characters.retain(|c| c.id != character_id);
+    pub fn compile_preset(&self, preset_id: &str) -> Option<&CompilePreset> {
+        self.compile_presets.iter().find(|p| p.id == preset_id)
+    }
 
-        if self.characters.len() == initial_len {
-            Err("Character not found".to_string())
-        } else {
-            Ok(())
-        }
+    pub fn plotline(&self, plotline_id: &str) -> Option<&Plotline> {
+        self.plotlines.iter().find(|p| p.id == plotline_id)
+    }
+
+    /// Looks up a character from an ID stored as a string, such as a node's
+    /// `pov_character_id`.
+    pub fn character(&self, character_id: &str) -> Option<&Character> {
+        let id = Uuid::parse_str(character_id).ok()?;
+        self.character_position(id)
+            .map(|position| &self.characters[position])
+    }
+
+    pub fn remove_character(&mut self, character_id: Uuid) -> Result<(), String> {
+        let position = self
+            .character_position(character_id)
+            .ok_or_else(|| "Character not found".to_string())?;
+        self.characters.remove(position);
+        self.reindex_characters();
+        Ok(())
     }
 }
@@ -2,13 +2,95 @@ use crate::models;
 use crate::storage;
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{Mutex, RwLock}; // RwLock added
 use uuid::Uuid;
 
+/// How long `mutate_project` waits before writing `project.json`. Mutations made
+/// in the meantime are saved by the same write.
+const FLUSH_DELAY: Duration = Duration::from_millis(500);
+/// How long a failed background write waits before it is tried again.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Clone)]
 pub struct ProjectContext {
     pub path: PathBuf,
     pub metadata: Arc<Mutex<models::ProjectMetadata>>,
+    persistence: Arc<Persistence>,
+}
+
+struct Persistence {
+    /// The in-memory metadata has changes the files don't.
+    dirty: AtomicBool,
+    /// A delayed flush is pending.
+    scheduled: AtomicBool,
+    /// The last background flush failed and nothing has been written since.
+    failed: AtomicBool,
+    /// Character files as last written, so a flush rewrites only changed ones.
+    characters: Mutex<storage::SavedCharacters>,
+}
+
+impl ProjectContext {
+    /// `metadata` must match what is on disk.
+    fn new(path: PathBuf, metadata: models::ProjectMetadata) -> Self {
+        let persistence = Persistence {
+            dirty: AtomicBool::new(false),
+            scheduled: AtomicBool::new(false),
+            failed: AtomicBool::new(false),
+            characters: Mutex::new(storage::saved_characters(&metadata.characters)),
+        };
+        Self {
+            path,
+            metadata: Arc::new(Mutex::new(metadata)),
+            persistence: Arc::new(persistence),
+        }
+    }
+
+    /// Writes changed character files and `project.json` if there are unsaved
+    /// mutations. The metadata lock is held throughout, so a mutation is either
+    /// in this write or marks the project dirty again after it.
+    async fn flush(&self) -> crate::errors::Result<()> {
+        let metadata = self.metadata.lock().await;
+        if !self.persistence.dirty.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let mut saved = self.persistence.characters.lock().await;
+        let result =
+            match storage::save_characters(&self.path, &metadata.characters, &mut saved).await {
+                Ok(()) => storage::save_project_metadata(&self.path, &metadata).await,
+                Err(e) => Err(e),
+            };
+        match result {
+            Ok(()) => self.persistence.failed.store(false, Ordering::SeqCst),
+            Err(_) => self.persistence.dirty.store(true, Ordering::SeqCst),
+        }
+        result
+    }
+
+    /// Flushes after `delay`, and again every RETRY_DELAY for as long as the
+    /// write fails.
+    fn schedule_flush(&self, delay: Duration) {
+        if self.persistence.scheduled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let context = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            context.persistence.scheduled.store(false, Ordering::SeqCst);
+            if let Err(e) = context.flush().await {
+                log::error!(
+                    "Failed to save project metadata to {}: {}",
+                    context.path.display(),
+                    e
+                );
+                context.persistence.failed.store(true, Ordering::SeqCst);
+                context.schedule_flush(RETRY_DELAY);
+            }
+        });
+    }
 }
 
 /// Manages lifecycle of loaded projects.
@@ -17,6 +99,14 @@ pub struct ProjectContext {
 /// Uses `RwLock` for the project registry to allow concurrent reads (getting context)
 /// while ensuring exclusive access for writes (loading/unloading projects).
 /// Each project's metadata is protected by its own `Mutex`.
+///
+/// # Persistence
+/// `mutate_project` updates the metadata in memory and writes `project.json` and
+/// the changed character files a moment later, so a burst of edits costs one
+/// write. A write that fails is retried in the background, and the next
+/// mutation of the project writes first and returns the error if it fails
+/// again. `unload_project` and `flush_all` write anything still pending and
+/// must run before the app exits.
 pub struct ProjectManager {
     pub projects: RwLock<HashMap<Uuid, ProjectContext>>,
 }
@@ -32,18 +122,24 @@ impl ProjectManager {
         &self,
         project_id: Uuid,
     ) -> crate::errors::Result<(PathBuf, Arc<Mutex<models::ProjectMetadata>>)> {
-        let projects = self.projects.read().await;
-        let context =
-            projects
-                .get(&project_id)
-                .ok_or_else(|| crate::errors::Error::InvalidStructure {
-                    path: PathBuf::new(),
-                    reason: "Project not loaded".to_string(),
-                })?;
+        let context = self.context(project_id).await?;
+        Ok((context.path, context.metadata))
+    }
 
-        Ok((context.path.clone(), context.metadata.clone()))
+    async fn context(&self, project_id: Uuid) -> crate::errors::Result<ProjectContext> {
+        let projects = self.projects.read().await;
+        projects
+            .get(&project_id)
+            .cloned()
+            .ok_or_else(|| crate::errors::Error::InvalidStructure {
+                path: PathBuf::new(),
+                reason: "Project not loaded".to_string(),
+            })
     }
 
+    /// Applies `mutation` and schedules a write of `project.json`. If the last
+    /// write failed, it is tried again first and its error returned, with
+    /// `mutation` not applied.
     pub async fn mutate_project<F>(
         &self,
         project_id: Uuid,
@@ -52,19 +148,49 @@ impl ProjectManager {
     where
         F: FnOnce(&mut models::ProjectMetadata) -> crate::errors::Result<()> + Send,
     {
-        let (root_path, metadata_arc) = self.get_context(project_id).await?;
+        let context = self.context(project_id).await?;
+        if context.persistence.failed.load(Ordering::SeqCst) {
+            context.flush().await?;
+        }
 
-        let mut metadata = metadata_arc.lock().await;
+        let mut metadata = context.metadata.lock().await;
 
         mutation(&mut metadata)?;
 
         metadata.updated_at = chrono::Utc::now();
 
-        storage::save_project_metadata(&root_path, &metadata).await?;
+        context.persistence.dirty.store(true, Ordering::SeqCst);
+        context.schedule_flush(FLUSH_DELAY);
 
         Ok(metadata.clone())
     }
 
+    /// Writes pending mutations of one project now.
+    pub async fn flush(&self, project_id: Uuid) -> crate::errors::Result<()> {
+        self.context(project_id).await?.flush().await
+    }
+
+    /// Writes pending mutations of every loaded project, returning the first
+    /// error after trying them all.
+    pub async fn flush_all(&self) -> crate::errors::Result<()> {
+        let contexts: Vec<ProjectContext> = self.projects.read().await.values().cloned().collect();
+
+        let mut result = Ok(());
+        for context in contexts {
+            if let Err(e) = context.flush().await {
+                log::error!(
+                    "Failed to save project metadata to {}: {}",
+                    context.path.display(),
+                    e
+                );
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+
     pub async fn create_project(
         &self,
         path: PathBuf,
@@ -92,12 +218,43 @@ impl ProjectManager {
         &self,
         path: PathBuf,
     ) -> crate::errors::Result<models::ProjectMetadata> {
+        // Reloading an open project reads the file, so write pending changes first
+        let open: Vec<ProjectContext> = self
+            .projects
+            .read()
+            .await
+            .values()
+            .filter(|context| context.path == path)
+            .cloned()
+            .collect();
+        for context in open {
+            context.flush().await?;
+        }
+
         let metadata = storage::load_project_metadata(&path).await?;
         self.register_project(metadata.id, path.clone(), metadata.clone())
             .await;
         Ok(metadata)
     }
 
+    /// Replaces `metadata`, the locked metadata of `project_id`, with what is on
+    /// disk, dropping unsaved mutations. For commands that rewrite the project
+    /// files themselves.
+    pub async fn reload_metadata(
+        &self,
+        project_id: Uuid,
+        metadata: &mut models::ProjectMetadata,
+    ) -> crate::errors::Result<()> {
+        let context = self.context(project_id).await?;
+        let reloaded = storage::load_project_metadata(&context.path).await?;
+
+        *context.persistence.characters.lock().await =
+            storage::saved_characters(&reloaded.characters);
+        context.persistence.dirty.store(false, Ordering::SeqCst);
+        *metadata = reloaded;
+        Ok(())
+    }
+
     pub async fn register_project(
         &self,
         id: Uuid,
@@ -105,18 +262,19 @@ impl ProjectManager {
         metadata: models::ProjectMetadata,
     ) {
         let mut projects = self.projects.write().await;
-        projects.insert(
-            id,
-            ProjectContext {
-                path,
-                metadata: Arc::new(Mutex::new(metadata)),
-            },
-        );
+        projects.insert(id, ProjectContext::new(path, metadata));
     }
 
-    pub async fn unload_project(&self, project_id: Uuid) {
+    /// Removes a project after writing its pending mutations.
+    pub async fn unload_project(&self, project_id: Uuid) -> crate::errors::Result<()> {
         let mut projects = self.projects.write().await;
-        projects.remove(&project_id);
+        let context = projects.remove(&project_id);
+        drop(projects);
+
+        match context {
+            Some(context) => context.flush().await,
+            None => Ok(()),
+        }
     }
 
     pub async fn is_loaded(&self, project_id: Uuid) -> bool {
@@ -167,4 +325,104 @@ mod tests {
         let result = manager.get_context(project_id).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_mutations_are_coalesced_and_flushed_on_unload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("book");
+        let manager = ProjectManager::new();
+        let project_id = manager
+            .create_project(path.clone(), "Book".to_string(), "Tester".to_string())
+            .await
+            .unwrap()
+            .id;
+
+        for target in [100, 200] {
+            manager
+                .mutate_project(project_id, |metadata| {
+                    metadata.settings.daily_target = target;
+                    Ok(())
+                })
+                .await
+                .unwrap();
+        }
+
+        // Not written yet
+        let on_disk = storage::load_project_metadata(&path).await.unwrap();
+        assert_eq!(on_disk.settings.daily_target, 2000);
+
+        manager.unload_project(project_id).await.unwrap();
+        let on_disk = storage::load_project_metadata(&path).await.unwrap();
+        assert_eq!(on_disk.settings.daily_target, 200);
+        assert!(!manager.is_loaded(project_id).await);
+    }
+
+    #[tokio::test]
+    async fn test_mutation_is_written_after_delay() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("book");
+        let manager = ProjectManager::new();
+        let project_id = manager
+            .create_project(path.clone(), "Book".to_string(), "Tester".to_string())
+            .await
+            .unwrap()
+            .id;
+
+        manager
+            .mutate_project(project_id, |metadata| {
+                metadata.title = "Renamed".to_string();
+                Ok(())
+            })
+            .await
+            .unwrap();
+
+        tokio::time::sleep(FLUSH_DELAY).await;
+        for _ in 0..50 {
+            if storage::load_project_metadata(&path).await.unwrap().title == "Renamed" {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("mutation was never written");
+    }
+
+    #[tokio::test]
+    async fn test_failed_write_is_reported_by_next_mutation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("book");
+        let manager = ProjectManager::new();
+        let project_id = manager
+            .create_project(path.clone(), "Book".to_string(), "Tester".to_string())
+            .await
+            .unwrap()
+            .id;
+        let set_target = |target| {
+            manager.mutate_project(project_id, move |metadata| {
+                metadata.settings.daily_target = target;
+                Ok(())
+            })
+        };
+
+        // The folder is gone by the time the change is written
+        std::fs::remove_dir_all(&path).unwrap();
+        set_target(100).await.unwrap();
+        let context = manager.context(project_id).await.unwrap();
+        tokio::time::sleep(FLUSH_DELAY).await;
+        for _ in 0..50 {
+            if context.persistence.failed.load(Ordering::SeqCst) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(set_target(200).await.is_err());
+
+        // Back again: the pending change is written before the next one
+        std::fs::create_dir(&path).unwrap();
+        set_target(300).await.unwrap();
+        let on_disk = storage::load_project_metadata(&path).await.unwrap();
+        assert_eq!(on_disk.settings.daily_target, 100);
+        manager.unload_project(project_id).await.unwrap();
+        let on_disk = storage::load_project_metadata(&path).await.unwrap();
+        assert_eq!(on_disk.settings.daily_target, 300);
+    }
 }
@@ -127,4 +127,21 @@ mod tests {
         let artifact2 = inner.artifacts.get(id2).expect("Artifact2 should exist");
         assert_eq!(artifact2.path, path2);
     }
+
+    #[tokio::test]
+    async fn test_stop_watching_leaves_other_projects_alone() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("research");
+        let state = ResearchState::new();
+        state
+            .initialize(path.clone())
+            .await
+            .expect("Failed to initialize state");
+
+        state.stop_watching(&dir.path().join("other")).await;
+        assert_eq!(state.get_root_path_safe().await.ok(), Some(path.clone()));
+
+        state.stop_watching(&path).await;
+        assert!(state.get_root_path_safe().await.is_err());
+    }
 }
@@ -1,7 +1,7 @@
 use crate::models::research::ResearchArtifact;
 use notify::RecommendedWatcher;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::sync::Mutex;
 
 use std::sync::Arc;
@@ -90,6 +90,16 @@ impl ResearchState {
         inner.watcher = None; // Dropping the watcher stops the underlying implementation
         inner.root_path = None;
     }
+    /// Stops the watcher if it is watching `path`, leaving another project's
+    /// alone.
+    pub async fn stop_watching(&self, path: &Path) {
+        let _lock = self.init_lock.lock().await;
+        let mut inner = self.inner.lock().await;
+        if inner.root_path.as_deref() == Some(path) {
+            inner.watcher = None;
+            inner.root_path = None;
+        }
+    }
     pub async fn get_root_path_safe(&self) -> crate::errors::Result<PathBuf> {
         let inner = self.inner.lock().await;
         inner
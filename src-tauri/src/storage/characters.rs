@@ -0,0 +1,214 @@
+//! Character storage: one JSON file per character in `characters/`, named by
+//! id, so editing one character touches one file when the project is synced.
+
+use super::consts::CHARACTERS_DIR;
+use super::fs_repo::LocalFileRepository;
+use super::journal;
+use super::traits::FileRepository;
+use crate::errors::Result;
+use crate::models::Character;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Last content written for each character file, by id. Lets
+/// `save_characters` skip characters that haven't changed.
+pub type SavedCharacters = HashMap<Uuid, String>;
+
+pub fn character_path(root_path: &Path, id: Uuid) -> PathBuf {
+    root_path.join(CHARACTERS_DIR).join(format!("{}.json", id))
+}
+
+pub(super) fn character_json(character: &Character) -> Result<String> {
+    Ok(serde_json::to_string_pretty(character)?)
+}
+
+/// Contents of the files as `save_characters` would write them, for characters
+/// known to be on disk already.
+pub fn saved_characters(characters: &[Character]) -> SavedCharacters {
+    characters
+        .iter()
+        .filter_map(|c| character_json(c).ok().map(|json| (c.id, json)))
+        .collect()
+}
+
+/// Reads every character file, in `order`, the ids as `project.json` lists
+/// them. Characters it doesn't list, such as ones added on another machine,
+/// follow sorted by name. Files not named by a character id, such as sync
+/// conflict copies, and files that don't parse are skipped.
+pub async fn load_characters(root_path: &Path, order: &[Uuid]) -> Result<Vec<Character>> {
+    let repo = LocalFileRepository;
+    let dir = root_path.join(CHARACTERS_DIR);
+    if !repo.exists(&dir).await? {
+        return Ok(Vec::new());
+    }
+
+    let mut characters = Vec::new();
+    for path in repo.read_dir(&dir).await? {
+        let named_by_id = path.extension().is_some_and(|ext| ext == "json")
+            && path
+                .file_stem()
+                .is_some_and(|stem| Uuid::parse_str(&stem.to_string_lossy()).is_ok());
+        if !named_by_id {
+            log::warn!("Skipping unexpected file {}", path.display());
+            continue;
+        }
+
+        let content = repo.read_file(&path).await?;
+        match serde_json::from_str::<Character>(&content) {
+            Ok(character) => characters.push(character),
+            // Left with conflict markers by a merge, say; the rest still loads
+            Err(e) => log::warn!("Skipping invalid character file {}: {}", path.display(), e),
+        }
+    }
+
+    let positions: HashMap<Uuid, usize> = order
+        .iter()
+        .enumerate()
+        .map(|(position, id)| (*id, position))
+        .collect();
+    characters.sort_by_cached_key(|c| {
+        (
+            positions.get(&c.id).copied().unwrap_or(usize::MAX),
+            c.name.to_lowercase(),
+            c.id,
+        )
+    });
+    Ok(characters)
+}
+
+/// Writes the files of characters that changed since `saved` and deletes those
+/// of characters that are gone, updating `saved` as it goes.
+pub async fn save_characters(
+    root_path: &Path,
+    characters: &[Character],
+    saved: &mut SavedCharacters,
+) -> Result<()> {
+    let repo = LocalFileRepository;
+    let dir = root_path.join(CHARACTERS_DIR);
+
+    for character in characters {
+        let json = character_json(character)?;
+        if saved.get(&character.id) == Some(&json) {
+            continue;
+        }
+        if !repo.exists(&dir).await? {
+            repo.create_dir_all(&dir).await?;
+        }
+        journal::write_atomic(&character_path(root_path, character.id), json.as_bytes()).await?;
+        saved.insert(character.id, json);
+    }
+
+    let present: HashSet<Uuid> = characters.iter().map(|c| c.id).collect();
+    let removed: Vec<Uuid> = saved
+        .keys()
+        .filter(|id| !present.contains(id))
+        .copied()
+        .collect();
+    for id in removed {
+        repo.delete(&character_path(root_path, id)).await?;
+        saved.remove(&id);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CharacterRole;
+
+    fn character(id: u128, name: &str) -> Character {
+        Character {
+            id: Uuid::from_u128(id),
+            name: name.to_string(),
+            role: CharacterRole::Secondary,
+            archetype: String::new(),
+            description: String::new(),
+            engine: Default::default(),
+            physical_features: String::new(),
+            traits: Vec::new(),
+            arc: String::new(),
+            notes: String::new(),
+            aliases: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_changed_files_are_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let mut characters = vec![character(1, "bob"), character(2, "Alice")];
+        let mut saved = SavedCharacters::new();
+        save_characters(root, &characters, &mut saved)
+            .await
+            .unwrap();
+
+        // An unchanged character's file is left alone, so this edit survives
+        let bob_path = character_path(root, Uuid::from_u128(1));
+        let edited = std::fs::read_to_string(&bob_path)
+            .unwrap()
+            .replace("bob", "Bob");
+        std::fs::write(&bob_path, &edited).unwrap();
+
+        characters[1].notes = "Curious".to_string();
+        save_characters(root, &characters, &mut saved)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&bob_path).unwrap(), edited);
+
+        let loaded = load_characters(root, &[]).await.unwrap();
+        let names: Vec<&str> = loaded.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Alice", "Bob"]);
+        assert_eq!(loaded[0].notes, "Curious");
+    }
+
+    #[tokio::test]
+    async fn test_removed_characters_are_deleted() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let mut saved = SavedCharacters::new();
+        save_characters(root, &[character(1, "A"), character(2, "B")], &mut saved)
+            .await
+            .unwrap();
+        std::fs::write(
+            root.join(CHARACTERS_DIR)
+                .join("notes (conflicted copy).json"),
+            "{}",
+        )
+        .unwrap();
+
+        save_characters(root, &[character(2, "B")], &mut saved)
+            .await
+            .unwrap();
+        assert!(!character_path(root, Uuid::from_u128(1)).exists());
+
+        let loaded = load_characters(root, &[]).await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].id, Uuid::from_u128(2));
+    }
+
+    #[tokio::test]
+    async fn test_load_keeps_order_and_skips_invalid_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let characters = [character(1, "Zed"), character(2, "Amy"), character(3, "Bo")];
+        save_characters(root, &characters, &mut SavedCharacters::new())
+            .await
+            .unwrap();
+        // A merge left conflict markers in one file
+        std::fs::write(
+            character_path(root, Uuid::from_u128(3)),
+            "<<<<<<< HEAD\n{}\n=======\n{}\n>>>>>>> theirs\n",
+        )
+        .unwrap();
+        // Added on another machine, not yet in the order
+        save_characters(root, &[character(4, "Al")], &mut SavedCharacters::new())
+            .await
+            .unwrap();
+
+        let order = [Uuid::from_u128(1), Uuid::from_u128(3), Uuid::from_u128(2)];
+        let loaded = load_characters(root, &order).await.unwrap();
+        let names: Vec<&str> = loaded.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Zed", "Amy", "Al"]);
+    }
+}
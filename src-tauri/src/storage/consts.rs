@@ -3,3 +3,4 @@ pub const CHARACTERS_DIR: &str = "characters";
 pub const RESEARCH_DIR: &str = "research";
 pub const SNAPSHOTS_DIR: &str = ".snapshots";
 pub const METADATA_FILENAME: &str = "project.json";
+pub const TRANSACTION_DIR: &str = ".transaction";
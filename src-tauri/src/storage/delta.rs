@@ -0,0 +1,185 @@
+//! Text deltas between two versions of a chapter.
+//!
+//! Both texts are cut into segments ending at a newline or a `>`, so Markdown
+//! splits into lines and HTML into tags and paragraphs. A delta copies runs of
+//! segments from the base and inserts the rest literally:
+//!
+//! ```text
+//! c <first segment> <count>\n
+//! i <byte length>\n<text>
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// How many earlier occurrences of a segment are tried when looking for a run
+/// to copy. Common segments like `<p>` occur everywhere.
+const MAX_CANDIDATES: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeltaOp {
+    Copy { start: usize, len: usize },
+    Insert(String),
+}
+
+fn segments(text: &str) -> Vec<&str> {
+    text.split_inclusive(['\n', '>']).collect()
+}
+
+/// Ops that turn `base` into `target`.
+pub fn diff(base: &str, target: &str) -> Vec<DeltaOp> {
+    let base = segments(base);
+    let target = segments(target);
+    let mut positions: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, segment) in base.iter().enumerate() {
+        positions.entry(segment).or_default().push(i);
+    }
+
+    let mut ops = Vec::new();
+    // Base position after the last copy; edits rarely move text far from it
+    let mut cursor = 0;
+    let mut j = 0;
+    while j < target.len() {
+        let run = |i: usize| {
+            base[i..]
+                .iter()
+                .zip(&target[j..])
+                .take_while(|(a, b)| a == b)
+                .count()
+        };
+        let candidates = positions.get(target[j]).map(Vec::as_slice).unwrap_or(&[]);
+        let nearby = candidates.partition_point(|&i| i < cursor);
+        let (start, len) = candidates[nearby..]
+            .iter()
+            .chain(&candidates[..nearby])
+            .take(MAX_CANDIDATES)
+            .map(|&i| (i, run(i)))
+            .fold(
+                (0, 0),
+                |best, found| if found.1 > best.1 { found } else { best },
+            );
+
+        if len == 0 {
+            match ops.last_mut() {
+                Some(DeltaOp::Insert(text)) => text.push_str(target[j]),
+                _ => ops.push(DeltaOp::Insert(target[j].to_string())),
+            }
+            j += 1;
+            continue;
+        }
+        match ops.last_mut() {
+            Some(DeltaOp::Copy {
+                start: last_start,
+                len: last_len,
+            }) if *last_start + *last_len == start => *last_len += len,
+            _ => ops.push(DeltaOp::Copy { start, len }),
+        }
+        cursor = start + len;
+        j += len;
+    }
+    ops
+}
+
+/// Characters added or removed going from `base` to `target`.
+pub fn changed_chars(base: &str, target: &str) -> usize {
+    let segments = segments(base);
+    let mut copied = 0;
+    let mut inserted = 0;
+    for op in diff(base, target) {
+        match op {
+            DeltaOp::Copy { start, len } => {
+                copied += segments[start..start + len]
+                    .iter()
+                    .map(|s| s.chars().count())
+                    .sum::<usize>();
+            }
+            DeltaOp::Insert(text) => inserted += text.chars().count(),
+        }
+    }
+    inserted + base.chars().count().saturating_sub(copied)
+}
+
+/// Rebuilds the target from `base`. None if an op reaches past the end of it.
+pub fn apply(base: &str, ops: &[DeltaOp]) -> Option<String> {
+    let base = segments(base);
+    let mut text = String::new();
+    for op in ops {
+        match op {
+            DeltaOp::Copy { start, len } => {
+                text.extend(base.get(*start..start.checked_add(*len)?)?.iter().copied());
+            }
+            DeltaOp::Insert(inserted) => text.push_str(inserted),
+        }
+    }
+    Some(text)
+}
+
+pub fn encode(ops: &[DeltaOp]) -> String {
+    let mut encoded = String::new();
+    for op in ops {
+        // Writing to a String cannot fail
+        let _ = match op {
+            DeltaOp::Copy { start, len } => writeln!(encoded, "c {} {}", start, len),
+            DeltaOp::Insert(text) => write!(encoded, "i {}\n{}", text.len(), text),
+        };
+    }
+    encoded
+}
+
+/// None if `encoded` is not a valid delta.
+pub fn decode(mut encoded: &str) -> Option<Vec<DeltaOp>> {
+    let mut ops = Vec::new();
+    while !encoded.is_empty() {
+        let (line, rest) = encoded.split_once('\n')?;
+        match line.split_once(' ')? {
+            ("c", range) => {
+                let (start, len) = range.split_once(' ')?;
+                ops.push(DeltaOp::Copy {
+                    start: start.parse().ok()?,
+                    len: len.parse().ok()?,
+                });
+                encoded = rest;
+            }
+            ("i", len) => {
+                let len: usize = len.parse().ok()?;
+                ops.push(DeltaOp::Insert(rest.get(..len)?.to_string()));
+                encoded = &rest[len..];
+            }
+            _ => return None,
+        }
+    }
+    Some(ops)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let base = "<h1>One</h1><p>First paragraph.</p><p>Second.</p><p>Third.</p>";
+        let target = "<h1>One</h1><p>First paragraph, edited.</p><p>Second.</p><p>Third.</p><p>New\nline</p>";
+
+        let ops = diff(base, target);
+        let encoded = encode(&ops);
+        assert_eq!(decode(&encoded).unwrap(), ops);
+        assert_eq!(apply(base, &ops).unwrap(), target);
+        // Unchanged paragraphs are copied, not stored
+        assert!(!encoded.contains("Second") && !encoded.contains("Third"));
+    }
+
+    #[test]
+    fn test_changed_chars() {
+        let base = "<p>One.</p><p>Two.</p>";
+        assert_eq!(changed_chars(base, base), 0);
+        // "Two.</p>" replaced by "Three.</p>"
+        assert_eq!(changed_chars(base, "<p>One.</p><p>Three.</p>"), 8 + 10);
+    }
+
+    #[test]
+    fn test_rejects_malformed_delta() {
+        assert!(decode("c 1\n").is_none());
+        assert!(decode("i 10\nshort").is_none());
+        assert!(apply("<p>a</p>", &[DeltaOp::Copy { start: 1, len: 5 }]).is_none());
+    }
+}
@@ -36,9 +36,7 @@ pub fn resolve_chapter_path<P: AsRef<Path>>(
 ) -> Result<PathBuf> {
     let filename = metadata
         .manifest
-        .chapters
-        .iter()
-        .find(|c| c.id == chapter_id)
+        .chapter(chapter_id)
         .map(|c| c.filename.clone());
 
     if let Some(fname) = filename {
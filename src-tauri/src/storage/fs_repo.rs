@@ -17,6 +17,15 @@ impl FileRepository for LocalFileRepository {
         Ok(())
     }
 
+    async fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        Ok(tokio::fs::read(path).await?)
+    }
+
+    async fn write_bytes(&self, path: &Path, content: &[u8]) -> Result<()> {
+        tokio::fs::write(path, content).await?;
+        Ok(())
+    }
+
     async fn exists(&self, path: &Path) -> Result<bool> {
         Ok(tokio::fs::try_exists(path).await?)
     }
@@ -28,6 +37,11 @@ impl FileRepository for LocalFileRepository {
         Ok(())
     }
 
+    async fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        tokio::fs::rename(from, to).await?;
+        Ok(())
+    }
+
     async fn create_dir_all(&self, path: &Path) -> Result<()> {
         tokio::fs::create_dir_all(path).await?;
         Ok(())
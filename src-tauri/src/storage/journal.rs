@@ -0,0 +1,252 @@
+//! Write-ahead journal for saves that touch several files.
+//!
+//! A `Transaction` stages every write as an fsynced temp file in
+//! `.transaction/`, then records the list of writes in `journal.json`. The rename
+//! that puts the journal in place is the commit point: after it the staged files
+//! are renamed over their targets and the folder is removed. `recover` runs when
+//! a project is loaded and finishes whatever a crash interrupted, rolling forward
+//! if the journal made it to disk and discarding the staged files otherwise.
+
+use super::consts::TRANSACTION_DIR;
+use crate::errors::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+const JOURNAL_FILENAME: &str = "journal.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Journal {
+    writes: Vec<JournalEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalEntry {
+    /// File name inside the transaction folder.
+    staged: String,
+    /// Destination, relative to the project root.
+    target: PathBuf,
+}
+
+/// A set of file writes that land together or not at all.
+pub struct Transaction {
+    root: PathBuf,
+    writes: Vec<(PathBuf, Vec<u8>)>,
+}
+
+impl Transaction {
+    pub fn new(root_path: &Path) -> Self {
+        Self {
+            root: root_path.to_path_buf(),
+            writes: Vec::new(),
+        }
+    }
+
+    /// Queues a write. `path` must be inside the project root.
+    pub fn write(&mut self, path: impl Into<PathBuf>, content: impl Into<Vec<u8>>) {
+        self.writes.push((path.into(), content.into()));
+    }
+
+    pub async fn commit(self) -> Result<()> {
+        if self.writes.is_empty() {
+            return Ok(());
+        }
+        // Settle anything an earlier failed commit left behind
+        recover(&self.root).await?;
+
+        let journal = self.prepare().await?;
+        apply(&self.root, &journal).await
+    }
+
+    /// Stages every write and puts the journal in place. Once this returns the
+    /// transaction survives a crash.
+    async fn prepare(&self) -> Result<Journal> {
+        let targets = self
+            .writes
+            .iter()
+            .map(|(path, _)| {
+                path.strip_prefix(&self.root)
+                    .map(Path::to_path_buf)
+                    .map_err(|_| Error::InvalidStructure {
+                        path: path.clone(),
+                        reason: "Transaction target is outside the project".to_string(),
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let dir = self.root.join(TRANSACTION_DIR);
+        tokio::fs::create_dir_all(&dir).await?;
+
+        let mut journal = Journal { writes: Vec::new() };
+        for (index, ((_, content), target)) in self.writes.iter().zip(targets).enumerate() {
+            let staged = format!("{}.tmp", index);
+            write_synced(&dir.join(&staged), content).await?;
+            journal.writes.push(JournalEntry { staged, target });
+        }
+
+        write_atomic(
+            &dir.join(JOURNAL_FILENAME),
+            serde_json::to_string(&journal)?.as_bytes(),
+        )
+        .await?;
+        Ok(journal)
+    }
+}
+
+/// Completes or discards a transaction interrupted by a crash.
+pub async fn recover(root_path: &Path) -> Result<()> {
+    let dir = root_path.join(TRANSACTION_DIR);
+    if !tokio::fs::try_exists(&dir).await? {
+        return Ok(());
+    }
+
+    let journal = match tokio::fs::read_to_string(dir.join(JOURNAL_FILENAME)).await {
+        Ok(text) => serde_json::from_str::<Journal>(&text).ok(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+
+    match journal {
+        Some(journal) => apply(root_path, &journal).await,
+        None => {
+            tokio::fs::remove_dir_all(&dir).await?;
+            Ok(())
+        }
+    }
+}
+
+/// Moves staged files over their targets. Entries already moved by an earlier
+/// attempt are skipped, so this can run again after a crash.
+async fn apply(root_path: &Path, journal: &Journal) -> Result<()> {
+    let dir = root_path.join(TRANSACTION_DIR);
+    let mut touched_dirs: Vec<PathBuf> = Vec::new();
+
+    for entry in &journal.writes {
+        let staged = dir.join(&entry.staged);
+        if !tokio::fs::try_exists(&staged).await? {
+            continue;
+        }
+        let target = root_path.join(&entry.target);
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+            if !touched_dirs.iter().any(|d| d == parent) {
+                touched_dirs.push(parent.to_path_buf());
+            }
+        }
+        tokio::fs::rename(&staged, &target).await?;
+    }
+
+    for parent in &touched_dirs {
+        sync_dir(parent).await;
+    }
+    tokio::fs::remove_dir_all(&dir).await?;
+    sync_dir(root_path).await;
+    Ok(())
+}
+
+/// Replaces `path` through an fsynced sibling temp file, so readers see either
+/// the old content or the new, never a partial write.
+pub async fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp = path.with_file_name(format!(".{}.tmp", name));
+
+    write_synced(&temp, content).await?;
+    tokio::fs::rename(&temp, path).await?;
+    if let Some(parent) = path.parent() {
+        sync_dir(parent).await;
+    }
+    Ok(())
+}
+
+async fn write_synced(path: &Path, content: &[u8]) -> Result<()> {
+    let mut file = tokio::fs::File::create(path).await?;
+    file.write_all(content).await?;
+    file.sync_all().await?;
+    Ok(())
+}
+
+/// Flushes a directory entry so renames inside it survive power loss. Not all
+/// platforms can open directories (Windows), so failures are ignored.
+async fn sync_dir(path: &Path) {
+    if let Ok(dir) = tokio::fs::File::open(path).await {
+        let _ = dir.sync_all().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(path: &Path) -> String {
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_commit_writes_all_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("project.json"), "old").unwrap();
+
+        let mut transaction = Transaction::new(root);
+        transaction.write(root.join("manuscript/one.md"), "chapter");
+        transaction.write(root.join("project.json"), "new");
+        transaction.commit().await.unwrap();
+
+        assert_eq!(read(&root.join("manuscript/one.md")), "chapter");
+        assert_eq!(read(&root.join("project.json")), "new");
+        assert!(!root.join(TRANSACTION_DIR).exists());
+    }
+
+    #[tokio::test]
+    async fn test_recover_rolls_forward_after_commit_point() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("project.json"), "old").unwrap();
+
+        let mut transaction = Transaction::new(root);
+        transaction.write(root.join("project.json"), "new");
+        transaction.write(root.join("manuscript/one.md"), "chapter");
+        let journal = transaction.prepare().await.unwrap();
+
+        // Crash after the first rename
+        let staging = root.join(TRANSACTION_DIR);
+        std::fs::rename(
+            staging.join(&journal.writes[0].staged),
+            root.join("project.json"),
+        )
+        .unwrap();
+
+        recover(root).await.unwrap();
+        assert_eq!(read(&root.join("project.json")), "new");
+        assert_eq!(read(&root.join("manuscript/one.md")), "chapter");
+        assert!(!staging.exists());
+    }
+
+    #[tokio::test]
+    async fn test_recover_rolls_back_without_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("project.json"), "old").unwrap();
+
+        // Crash while staging: the journal was never written
+        let staging = root.join(TRANSACTION_DIR);
+        std::fs::create_dir_all(&staging).unwrap();
+        std::fs::write(staging.join("0.tmp"), "half").unwrap();
+        std::fs::write(staging.join(".journal.json.tmp"), "{\"wri").unwrap();
+
+        recover(root).await.unwrap();
+        assert_eq!(read(&root.join("project.json")), "old");
+        assert!(!staging.exists());
+    }
+
+    #[tokio::test]
+    async fn test_target_outside_project_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut transaction = Transaction::new(&dir.path().join("project"));
+        transaction.write(dir.path().join("elsewhere.md"), "x");
+        assert!(transaction.commit().await.is_err());
+    }
+}
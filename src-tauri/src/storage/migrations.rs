@@ -0,0 +1,148 @@
+//! Upgrades of the `project.json` layout.
+//!
+//! `MIGRATIONS[n]` turns a version `n` document into version `n + 1`. They work
+//! on raw JSON so an old layout never has to be expressible in the current
+//! structs. A migration that moves data out of the document returns the files
+//! to write instead of writing them, so the chain stays free of file access.
+//! `load_project_metadata` runs the chain, keeping a backup of the file as it
+//! was, and refuses documents newer than `CURRENT_SCHEMA_VERSION` rather than
+//! dropping the fields it doesn't know about.
+
+use super::characters::character_json;
+use super::consts::CHARACTERS_DIR;
+use crate::errors::{Error, Result};
+use crate::models::{Character, CURRENT_SCHEMA_VERSION};
+use serde_json::Value;
+use std::path::PathBuf;
+
+type Migration = fn(&mut Value, &mut Vec<(PathBuf, String)>) -> Result<()>;
+
+const MIGRATIONS: &[Migration] = &[v0_add_schema_version, v1_character_files];
+
+/// Outcome of `migrate`.
+pub struct Migrated {
+    /// Version the document was written with.
+    pub found: u64,
+    /// Files moved out of the document, by path relative to the project root.
+    /// They must be written before the upgraded document.
+    pub files: Vec<(PathBuf, String)>,
+}
+
+/// Version 0 is every file written before versioning. Its fields are the same
+/// as version 1, so stamping the version is all there is to do.
+fn v0_add_schema_version(_document: &mut Value, _files: &mut Vec<(PathBuf, String)>) -> Result<()> {
+    Ok(())
+}
+
+/// Version 2 moves each character from the `characters` array to its own file
+/// in `characters/`, and keeps their order in `character_order`.
+fn v1_character_files(document: &mut Value, files: &mut Vec<(PathBuf, String)>) -> Result<()> {
+    let characters = match document
+        .as_object_mut()
+        .and_then(|d| d.remove("characters"))
+    {
+        Some(characters) => serde_json::from_value::<Vec<Character>>(characters)
+            .map_err(|e| Error::Validation(format!("Invalid characters: {}", e)))?,
+        None => Vec::new(),
+    };
+
+    let mut order = Vec::new();
+    for character in &characters {
+        files.push((
+            PathBuf::from(CHARACTERS_DIR).join(format!("{}.json", character.id)),
+            character_json(character)?,
+        ));
+        order.push(Value::from(character.id.to_string()));
+    }
+    document["character_order"] = Value::Array(order);
+    Ok(())
+}
+
+/// Version a document was written with. Missing means version 0; anything but
+/// a non-negative integer is an error.
+pub fn schema_version(document: &Value) -> Result<u64> {
+    match document.get("schema_version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| Error::Validation(format!("Invalid schema_version `{}`", version))),
+    }
+}
+
+/// Brings `document` up to `CURRENT_SCHEMA_VERSION`.
+pub fn migrate(document: &mut Value) -> Result<Migrated> {
+    if !document.is_object() {
+        return Err(Error::Validation(
+            "project.json must contain an object".to_string(),
+        ));
+    }
+    let found = schema_version(document)?;
+    if found > u64::from(CURRENT_SCHEMA_VERSION) {
+        return Err(Error::UnsupportedSchema {
+            found,
+            supported: u64::from(CURRENT_SCHEMA_VERSION),
+        });
+    }
+
+    let mut files = Vec::new();
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(found as usize) {
+        migration(document, &mut files)?;
+        document["schema_version"] = Value::from(version + 1);
+    }
+    Ok(Migrated { found, files })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_chain_covers_every_version() {
+        assert_eq!(MIGRATIONS.len(), CURRENT_SCHEMA_VERSION as usize);
+    }
+
+    #[test]
+    fn test_migrates_unversioned_document() {
+        let mut document = json!({ "title": "Tales" });
+        let migrated = migrate(&mut document).unwrap();
+        assert_eq!(migrated.found, 0);
+        assert!(migrated.files.is_empty());
+        assert_eq!(
+            schema_version(&document).unwrap(),
+            u64::from(CURRENT_SCHEMA_VERSION)
+        );
+        assert_eq!(document["title"], "Tales");
+    }
+
+    #[test]
+    fn test_moves_characters_to_files() {
+        let id = "00000000-0000-0000-0000-000000000001";
+        let mut document = json!({
+            "schema_version": 1,
+            "characters": [{ "id": id, "name": "Amy", "role": "extra", "description": "" }]
+        });
+        let migrated = migrate(&mut document).unwrap();
+
+        assert!(document.get("characters").is_none());
+        assert_eq!(document["character_order"], json!([id]));
+        assert_eq!(migrated.files.len(), 1);
+        let (path, content) = &migrated.files[0];
+        assert_eq!(
+            path,
+            &PathBuf::from(CHARACTERS_DIR).join(format!("{}.json", id))
+        );
+        assert!(content.contains("\"Amy\""));
+    }
+
+    #[test]
+    fn test_refuses_newer_or_malformed_version() {
+        let mut document = json!({ "schema_version": CURRENT_SCHEMA_VERSION + 1 });
+        assert!(matches!(
+            migrate(&mut document),
+            Err(Error::UnsupportedSchema { .. })
+        ));
+        let mut document = json!({ "schema_version": "2" });
+        assert!(matches!(migrate(&mut document), Err(Error::Validation(_))));
+    }
+}
@@ -0,0 +1,424 @@
+//! Milestones: named snapshots of the whole project.
+//!
+//! A milestone records every file that makes up the project at one moment:
+//! `project.json`, the chapter files, the character files and, if asked for,
+//! the research index. The contents go into the snapshot object store, so text
+//! a milestone shares with chapter snapshots or other milestones is stored
+//! once. Each milestone is `manuscript/.snapshots/milestones/<id>.json`.
+
+use super::characters::{character_json, character_path};
+use super::consts::{CHARACTERS_DIR, METADATA_FILENAME, RESEARCH_DIR};
+use super::files::resolve_chapter_path;
+use super::journal::{write_atomic, Transaction};
+use super::migrations;
+use super::snapshots::{content_hash, read_object_text, snapshots_root, store_object};
+use super::traits::FileRepository;
+use crate::errors::{Error, Result};
+use crate::models::{Chapter, ProjectMetadata};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use uuid::Uuid;
+
+const MILESTONES_DIR: &str = "milestones";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Milestone {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    /// Content hash of each file, by path relative to the project root with
+    /// `/` separators.
+    pub files: BTreeMap<String, String>,
+}
+
+fn milestones_dir(root_path: &Path) -> PathBuf {
+    snapshots_root(root_path).join(MILESTONES_DIR)
+}
+
+fn milestone_path(root_path: &Path, id: Uuid) -> PathBuf {
+    milestones_dir(root_path).join(format!("{}.json", id))
+}
+
+/// `path` relative to the project root, as used for `Milestone::files`.
+fn file_key(root_path: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root_path).unwrap_or(path);
+    relative
+        .iter()
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Fails if any path in `milestone.files` could reach outside the project:
+/// the JSON may have been edited, or come from elsewhere.
+fn check_file_keys(root_path: &Path, milestone: &Milestone) -> Result<()> {
+    for key in milestone.files.keys() {
+        let escapes = Path::new(key)
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            return Err(Error::InvalidStructure {
+                path: milestone_path(root_path, milestone.id),
+                reason: format!("Milestone file `{}` is outside the project", key),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn research_index_path(root_path: &Path) -> PathBuf {
+    root_path
+        .join(RESEARCH_DIR)
+        .join(super::research::INDEX_FILENAME)
+}
+
+/// Captures the project as `metadata` describes it. Metadata and characters
+/// are taken from memory, so changes not yet flushed are included; chapter
+/// text is read from disk, where saves write it straight away.
+pub async fn create_milestone<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    metadata: &ProjectMetadata,
+    name: &str,
+    include_research: bool,
+) -> Result<Milestone> {
+    let mut contents = vec![(
+        root_path.join(METADATA_FILENAME),
+        metadata.to_project_file()?,
+    )];
+    for character in &metadata.characters {
+        contents.push((
+            character_path(root_path, character.id),
+            character_json(character)?,
+        ));
+    }
+    for chapter in &metadata.manifest.chapters {
+        let path = resolve_chapter_path(root_path, metadata, &chapter.id)?;
+        // Chapters never saved have no file yet
+        if repo.exists(&path).await? {
+            let content = repo.read_file(&path).await?;
+            contents.push((path, content));
+        }
+    }
+    let research_index = research_index_path(root_path);
+    if include_research && repo.exists(&research_index).await? {
+        let content = repo.read_file(&research_index).await?;
+        contents.push((research_index, content));
+    }
+
+    let mut files = BTreeMap::new();
+    for (path, content) in contents {
+        let hash = content_hash(&content);
+        store_object(repo, root_path, &hash, &content).await?;
+        files.insert(file_key(root_path, &path), hash);
+    }
+
+    let milestone = Milestone {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        created_at: Utc::now(),
+        files,
+    };
+    repo.create_dir_all(&milestones_dir(root_path)).await?;
+    write_atomic(
+        &milestone_path(root_path, milestone.id),
+        serde_json::to_string_pretty(&milestone)?.as_bytes(),
+    )
+    .await?;
+    Ok(milestone)
+}
+
+/// Every milestone of the project, oldest first. Files that cannot be read are
+/// left out, so one damaged milestone doesn't hide the others.
+pub async fn list_milestones<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+) -> Result<Vec<Milestone>> {
+    let dir = milestones_dir(root_path);
+    if !repo.exists(&dir).await? {
+        return Ok(Vec::new());
+    }
+
+    let mut milestones = Vec::new();
+    for path in repo.read_dir(&dir).await? {
+        if path.extension().is_some_and(|ext| ext == "json") {
+            match serde_json::from_slice::<Milestone>(&repo.read_bytes(&path).await?) {
+                Ok(milestone) => milestones.push(milestone),
+                Err(e) => log::warn!("Skipping damaged milestone {}: {}", path.display(), e),
+            }
+        }
+    }
+    milestones.sort_by_key(|m| m.created_at);
+    Ok(milestones)
+}
+
+pub async fn load_milestone<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    id: Uuid,
+) -> Result<Milestone> {
+    let path = milestone_path(root_path, id);
+    if !repo.exists(&path).await? {
+        return Err(Error::MilestoneNotFound { id });
+    }
+    Ok(serde_json::from_str(&repo.read_file(&path).await?)?)
+}
+
+/// The project metadata a milestone recorded, brought up to the current
+/// layout. Its `characters` are empty; they are stored as separate files.
+async fn milestone_metadata<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    milestone: &Milestone,
+) -> Result<ProjectMetadata> {
+    let hash = milestone
+        .files
+        .get(METADATA_FILENAME)
+        .ok_or_else(|| Error::InvalidStructure {
+            path: milestone_path(root_path, milestone.id),
+            reason: "Milestone has no project.json".to_string(),
+        })?;
+    let mut document: serde_json::Value =
+        serde_json::from_str(&read_object_text(repo, root_path, hash).await?)?;
+    // Milestones are newer than the character files, so nothing is moved out
+    migrations::migrate(&mut document)?;
+    let mut metadata: ProjectMetadata = serde_json::from_value(document)?;
+    metadata.reindex();
+    Ok(metadata)
+}
+
+/// Puts every file of the milestone back in one transaction, then deletes the
+/// character files and the chapter files of `current` that the milestone
+/// doesn't have. The project must be loaded again afterwards.
+pub async fn restore_milestone<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    current: &ProjectMetadata,
+    milestone: &Milestone,
+) -> Result<()> {
+    check_file_keys(root_path, milestone)?;
+
+    // Read everything first, so a damaged object fails before any file changes
+    let mut transaction = Transaction::new(root_path);
+    for (key, hash) in &milestone.files {
+        let content = read_object_text(repo, root_path, hash).await?;
+        transaction.write(root_path.join(key), content);
+    }
+    transaction.commit().await?;
+
+    let characters_dir = root_path.join(CHARACTERS_DIR);
+    let mut stale = Vec::new();
+    if repo.exists(&characters_dir).await? {
+        stale.extend(repo.read_dir(&characters_dir).await?);
+    }
+    for chapter in &current.manifest.chapters {
+        stale.push(resolve_chapter_path(root_path, current, &chapter.id)?);
+    }
+    for path in stale {
+        if !milestone.files.contains_key(&file_key(root_path, &path)) {
+            repo.delete(&path).await?;
+        }
+    }
+    Ok(())
+}
+
+/// One chapter as the milestone recorded it, with its text.
+pub async fn read_milestone_chapter<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    milestone: &Milestone,
+    chapter_id: &str,
+) -> Result<(Chapter, String)> {
+    check_file_keys(root_path, milestone)?;
+    let metadata = milestone_metadata(repo, root_path, milestone).await?;
+    let chapter = metadata
+        .manifest
+        .chapter(chapter_id)
+        .cloned()
+        .ok_or_else(|| Error::ChapterNotFound {
+            id: chapter_id.to_string(),
+        })?;
+
+    let path = resolve_chapter_path(root_path, &metadata, chapter_id)?;
+    let content = match milestone.files.get(&file_key(root_path, &path)) {
+        Some(hash) => read_object_text(repo, root_path, hash).await?,
+        // The chapter had no file yet
+        None => String::new(),
+    };
+    Ok((chapter, content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Character, CharacterRole};
+    use crate::storage::{
+        load_project_metadata, save_characters, save_project_metadata, LocalFileRepository,
+        SavedCharacters,
+    };
+
+    fn character(name: &str) -> Character {
+        Character {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            role: CharacterRole::Secondary,
+            archetype: String::new(),
+            description: String::new(),
+            engine: Default::default(),
+            physical_features: String::new(),
+            traits: Vec::new(),
+            arc: String::new(),
+            notes: String::new(),
+            aliases: Vec::new(),
+        }
+    }
+
+    /// Adds a chapter with a file, as creating and saving it in the app would.
+    fn add_chapter(root: &Path, metadata: &mut ProjectMetadata, title: &str, text: &str) -> String {
+        let chapter = metadata.manifest.create_chapter(None, title.to_string());
+        let path = root
+            .join(crate::storage::MANUSCRIPT_DIR)
+            .join(&chapter.filename);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+        let id = chapter.id.clone();
+        metadata.manifest.push(chapter);
+        id
+    }
+
+    async fn save(root: &Path, metadata: &ProjectMetadata) {
+        save_characters(root, &metadata.characters, &mut SavedCharacters::new())
+            .await
+            .unwrap();
+        save_project_metadata(root, metadata).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_restore_brings_back_structure_and_characters() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let repo = LocalFileRepository;
+        let mut metadata = ProjectMetadata::new("Tales".to_string(), "Jane".to_string());
+        let first = add_chapter(root, &mut metadata, "One", "<p>Draft</p>");
+        metadata.add_or_update_character(character("Amy"));
+        save(root, &metadata).await;
+
+        let milestone = create_milestone(&repo, root, &metadata, "Draft 1", false)
+            .await
+            .unwrap();
+
+        // Later work: an edit, a new chapter, a character swapped for another
+        std::fs::write(
+            resolve_chapter_path(root, &metadata, &first).unwrap(),
+            "<p>Rewrite</p>",
+        )
+        .unwrap();
+        let second = add_chapter(root, &mut metadata, "Two", "<p>New</p>");
+        let amy = metadata.characters[0].id;
+        metadata.remove_character(amy).unwrap();
+        metadata.add_or_update_character(character("Zed"));
+        save(root, &metadata).await;
+        let second_path = resolve_chapter_path(root, &metadata, &second).unwrap();
+
+        restore_milestone(&repo, root, &metadata, &milestone)
+            .await
+            .unwrap();
+
+        let restored = load_project_metadata(root).await.unwrap();
+        assert_eq!(restored.manifest.chapters.len(), 1);
+        assert!(!second_path.exists());
+        let names: Vec<&str> = restored
+            .characters
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Amy"]);
+        assert_eq!(
+            std::fs::read_to_string(resolve_chapter_path(root, &restored, &first).unwrap())
+                .unwrap(),
+            "<p>Draft</p>"
+        );
+        assert_eq!(list_milestones(&repo, root).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reads_one_chapter_after_it_was_deleted() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let repo = LocalFileRepository;
+        let mut metadata = ProjectMetadata::new("Tales".to_string(), "Jane".to_string());
+        let id = add_chapter(root, &mut metadata, "One", "<p>Kept</p>");
+        save(root, &metadata).await;
+        let milestone = create_milestone(&repo, root, &metadata, "Draft 1", false)
+            .await
+            .unwrap();
+
+        metadata.manifest.remove_node_recursively(id.clone());
+        save(root, &metadata).await;
+
+        let loaded = load_milestone(&repo, root, milestone.id).await.unwrap();
+        let (chapter, content) = read_milestone_chapter(&repo, root, &loaded, &id)
+            .await
+            .unwrap();
+        assert_eq!(chapter.title, "One");
+        assert_eq!(content, "<p>Kept</p>");
+
+        assert!(matches!(
+            read_milestone_chapter(&repo, root, &loaded, "missing").await,
+            Err(Error::ChapterNotFound { .. })
+        ));
+        assert!(matches!(
+            load_milestone(&repo, root, Uuid::new_v4()).await,
+            Err(Error::MilestoneNotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_damaged_milestone_is_left_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let repo = LocalFileRepository;
+        let metadata = ProjectMetadata::new("Tales".to_string(), "Jane".to_string());
+        save(root, &metadata).await;
+        let kept = create_milestone(&repo, root, &metadata, "Draft 1", false)
+            .await
+            .unwrap();
+        // Cut short by a crash
+        std::fs::write(milestone_path(root, Uuid::new_v4()), "{\"id\":").unwrap();
+
+        let listed = list_milestones(&repo, root).await.unwrap();
+        let ids: Vec<Uuid> = listed.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![kept.id]);
+    }
+
+    #[tokio::test]
+    async fn test_refuses_paths_outside_the_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("Tales");
+        let repo = LocalFileRepository;
+        let metadata = ProjectMetadata::new("Tales".to_string(), "Jane".to_string());
+        std::fs::create_dir(&root).unwrap();
+        save(&root, &metadata).await;
+        let mut milestone = create_milestone(&repo, &root, &metadata, "Draft 1", false)
+            .await
+            .unwrap();
+        let hash = milestone.files[METADATA_FILENAME].clone();
+
+        for key in ["../escaped.json", "/tmp/escaped.json"] {
+            milestone.files.insert(key.to_string(), hash.clone());
+            assert!(matches!(
+                restore_milestone(&repo, &root, &metadata, &milestone).await,
+                Err(Error::InvalidStructure { .. })
+            ));
+            assert!(matches!(
+                read_milestone_chapter(&repo, &root, &milestone, "c1").await,
+                Err(Error::InvalidStructure { .. })
+            ));
+            milestone.files.remove(key);
+        }
+        assert!(!dir.path().join("escaped.json").exists());
+    }
+}
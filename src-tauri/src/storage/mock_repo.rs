@@ -7,7 +7,7 @@ use std::sync::{Arc, Mutex};
 
 #[derive(Default, Clone)]
 pub struct MockFileRepository {
-    files: Arc<Mutex<HashMap<PathBuf, String>>>,
+    files: Arc<Mutex<HashMap<PathBuf, Vec<u8>>>>,
     exists_override: Arc<Mutex<HashMap<PathBuf, bool>>>,
 }
 
@@ -20,7 +20,7 @@ impl MockFileRepository {
         self.files
             .lock()
             .expect("mutex poisoned")
-            .insert(path, content);
+            .insert(path, content.into_bytes());
     }
 
     pub fn get_content(&self, path: &Path) -> Option<String> {
@@ -28,7 +28,7 @@ impl MockFileRepository {
             .lock()
             .expect("mutex poisoned")
             .get(path)
-            .cloned()
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
     }
 
     pub fn set_exists(&self, path: PathBuf, exists: bool) {
@@ -42,6 +42,17 @@ impl MockFileRepository {
 #[async_trait]
 impl FileRepository for MockFileRepository {
     async fn read_file(&self, path: &Path) -> Result<String> {
+        let bytes = self.read_bytes(path).await?;
+        String::from_utf8(bytes).map_err(|e| {
+            crate::errors::Error::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        })
+    }
+
+    async fn write_file(&self, path: &Path, content: &str) -> Result<()> {
+        self.write_bytes(path, content.as_bytes()).await
+    }
+
+    async fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
         self.files
             .lock()
             .expect("mutex poisoned")
@@ -55,11 +66,11 @@ impl FileRepository for MockFileRepository {
             })
     }
 
-    async fn write_file(&self, path: &Path, content: &str) -> Result<()> {
+    async fn write_bytes(&self, path: &Path, content: &[u8]) -> Result<()> {
         self.files
             .lock()
             .expect("mutex poisoned")
-            .insert(path.to_path_buf(), content.to_string());
+            .insert(path.to_path_buf(), content.to_vec());
         Ok(())
     }
 
@@ -84,6 +95,18 @@ impl FileRepository for MockFileRepository {
         Ok(())
     }
 
+    async fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        let mut files = self.files.lock().expect("mutex poisoned");
+        let content = files.remove(from).ok_or_else(|| {
+            crate::errors::Error::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "File not found in mock",
+            ))
+        })?;
+        files.insert(to.to_path_buf(), content);
+        Ok(())
+    }
+
     async fn create_dir_all(&self, _path: &Path) -> Result<()> {
         Ok(())
     }
@@ -109,7 +132,13 @@ impl FileRepository for MockFileRepository {
 
         // Return dummy metadata since our mock doesn't track modification time/size strictly
         // or we could calculate size from content.
-        let len = self.get_content(path).map(|c| c.len() as u64).unwrap_or(0);
+        let len = self
+            .files
+            .lock()
+            .expect("mutex poisoned")
+            .get(path)
+            .map(|c| c.len() as u64)
+            .unwrap_or(0);
         Ok(FileMetadata {
             len,
             modified: 1000,
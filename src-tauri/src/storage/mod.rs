@@ -1,6 +1,11 @@
+pub mod characters;
 pub mod consts;
+pub mod delta;
 pub mod files;
 pub mod fs_repo;
+pub mod journal;
+pub mod migrations;
+pub mod milestones;
 #[cfg(test)]
 pub mod mock_repo;
 pub mod project;
@@ -8,9 +13,12 @@ pub mod research;
 pub mod snapshots;
 pub mod traits;
 
+pub use characters::*;
 pub use consts::*;
 pub use files::*;
 pub use fs_repo::*;
+pub use journal::*;
+pub use milestones::*;
 #[cfg(test)]
 pub use mock_repo::*;
 pub use project::*;
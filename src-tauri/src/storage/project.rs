@@ -1,8 +1,12 @@
+use super::characters;
 use super::consts::METADATA_FILENAME;
-use crate::models::ProjectMetadata;
+use super::journal;
+use super::migrations;
+use crate::models::{ProjectMetadata, CURRENT_SCHEMA_VERSION};
 use crate::storage::fs_repo::LocalFileRepository;
 use crate::storage::traits::FileRepository;
 use std::path::Path;
+use uuid::Uuid;
 
 pub async fn create_project_structure(
     root_path: &Path,
@@ -31,6 +35,9 @@ pub async fn load_project_metadata(root_path: &Path) -> crate::errors::Result<Pr
     let repo = LocalFileRepository;
     let file_path = root_path.join(METADATA_FILENAME);
 
+    // Finish or discard a save interrupted by a crash before reading anything
+    journal::recover(root_path).await?;
+
     if !repo.exists(&file_path).await? {
         return Err(crate::errors::Error::InvalidStructure {
             path: root_path.to_path_buf(),
@@ -39,19 +46,151 @@ pub async fn load_project_metadata(root_path: &Path) -> crate::errors::Result<Pr
     }
 
     let content = repo.read_file(&file_path).await?;
-    let metadata: ProjectMetadata = serde_json::from_str(&content)?;
+    let mut document: serde_json::Value = serde_json::from_str(&content)?;
+    let migrated = migrations::migrate(&mut document)?;
+    // Not part of the metadata: `characters` itself is in this order
+    let character_order: Vec<Uuid> = document
+        .get("character_order")
+        .and_then(|order| serde_json::from_value(order.clone()).ok())
+        .unwrap_or_default();
+    let mut metadata: ProjectMetadata = serde_json::from_value(document)?;
+
+    let upgraded = migrated.found < u64::from(CURRENT_SCHEMA_VERSION);
+    if upgraded {
+        // Keep the file as it was before rewriting it in the new layout
+        let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H%M%S");
+        let backup_path = root_path.join(format!(
+            "{}.v{}-{}.bak",
+            METADATA_FILENAME, migrated.found, timestamp
+        ));
+        repo.write_file(&backup_path, &content).await?;
+
+        // Until project.json is rewritten below, a crash just repeats this
+        let mut transaction = journal::Transaction::new(root_path);
+        for (path, file) in migrated.files {
+            transaction.write(root_path.join(path), file);
+        }
+        transaction.commit().await?;
+    }
+
+    metadata.characters = characters::load_characters(root_path, &character_order).await?;
+    metadata.reindex();
+    if upgraded {
+        save_project_metadata(root_path, &metadata).await?;
+    }
 
     Ok(metadata)
 }
 
+/// Writes `project.json`. Characters are stored separately by
+/// `save_characters`.
 pub async fn save_project_metadata(
     root_path: &Path,
     metadata: &ProjectMetadata,
 ) -> crate::errors::Result<()> {
-    let repo = LocalFileRepository;
     let file_path = root_path.join(METADATA_FILENAME);
-    let content = serde_json::to_string_pretty(metadata)?;
+    let content = metadata.to_project_file()?;
+
+    journal::write_atomic(&file_path, content.as_bytes()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_load_upgrades_unversioned_project_with_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let original = r#"{
+            "id": "00000000-0000-0000-0000-000000000001",
+            "title": "Tales",
+            "author": "Jane",
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z",
+            "settings": { "daily_target": 500 },
+            "manifest": { "chapters": [] }
+        }"#;
+        std::fs::write(root.join(METADATA_FILENAME), original).unwrap();
+
+        let metadata = load_project_metadata(root).await.unwrap();
+        assert_eq!(metadata.schema_version, CURRENT_SCHEMA_VERSION);
 
-    repo.write_file(&file_path, &content).await?;
-    Ok(())
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(root.join(METADATA_FILENAME)).unwrap())
+                .unwrap();
+        assert_eq!(
+            migrations::schema_version(&saved).unwrap(),
+            u64::from(CURRENT_SCHEMA_VERSION)
+        );
+
+        let backups: Vec<_> = std::fs::read_dir(root)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.starts_with("project.json.v0-") && name.ends_with(".bak"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(root.join(&backups[0])).unwrap(),
+            original
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_refuses_newer_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = format!(
+            r#"{{ "schema_version": {}, "future": true }}"#,
+            CURRENT_SCHEMA_VERSION + 1
+        );
+        std::fs::write(dir.path().join(METADATA_FILENAME), &content).unwrap();
+
+        assert!(matches!(
+            load_project_metadata(dir.path()).await,
+            Err(crate::errors::Error::UnsupportedSchema { .. })
+        ));
+        // Left untouched
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(METADATA_FILENAME)).unwrap(),
+            content
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_moves_characters_to_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let mut metadata = ProjectMetadata::new("Tales".to_string(), "Jane".to_string());
+        let records = crate::import::characters::parse_records(
+            "name\nZed\nAmy\n",
+            crate::export::characters::CharacterFormat::Csv,
+        )
+        .unwrap();
+        crate::import::characters::apply_records(&mut metadata, records, false).unwrap();
+
+        // A version 1 file, with the characters inline
+        let mut document = serde_json::to_value(&metadata).unwrap();
+        document["schema_version"] = serde_json::json!(1);
+        std::fs::write(root.join(METADATA_FILENAME), document.to_string()).unwrap();
+
+        let loaded = load_project_metadata(root).await.unwrap();
+        assert_eq!(loaded.characters.len(), 2);
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(root.join(METADATA_FILENAME)).unwrap())
+                .unwrap();
+        assert!(saved.get("characters").is_none());
+
+        // Loaded again from the character files, in the order they had
+        let reloaded = load_project_metadata(root).await.unwrap();
+        let names: Vec<&str> = reloaded
+            .characters
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Zed", "Amy"]);
+        assert!(reloaded
+            .character(&loaded.characters[0].id.to_string())
+            .is_some());
+    }
 }
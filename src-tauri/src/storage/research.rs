@@ -3,7 +3,7 @@ use crate::models::research::ResearchArtifact;
 use std::collections::HashMap;
 use std::path::Path;
 
-const INDEX_FILENAME: &str = ".research-index.json";
+pub(super) const INDEX_FILENAME: &str = ".research-index.json";
 
 pub async fn load_index<P: AsRef<Path>>(path: P) -> HashMap<String, ResearchArtifact> {
     let index_path = path.as_ref().join(INDEX_FILENAME);
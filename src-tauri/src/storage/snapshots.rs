@@ -1,32 +1,415 @@
+//! Chapter snapshots, kept in a content-addressed store.
+//!
+//! Layout under `manuscript/.snapshots/`:
+//! - `objects/ab/cdef…`: zlib-compressed content, named by the SHA-256 of the
+//!   text it holds. Identical text is stored once, whichever chapter or branch
+//!   it came from.
+//! - `<chapter_id>/index.json`: the chapter's snapshots, oldest first, each
+//!   with its timestamp, content hash and optional label.
+//!
+//! An object holds either the full text (a keyframe) or a delta from the
+//! previous snapshot's object, so a chain of revisions costs little more than
+//! its edits. Every `KEYFRAME_INTERVAL` revisions a chain starts over with a
+//! keyframe, which bounds how many objects a read has to apply.
+//!
+//! Which snapshots are taken and kept is up to the project's `SnapshotPolicy`:
+//! saves are throttled, and older history is thinned to one snapshot per hour,
+//! day and week as it ages.
+//!
+//! Snapshots from before the store (`<chapter_id>/<timestamp>_<hash>.md` files)
+//! are moved into it the first time their chapter's index is read.
+
+use super::delta;
+use super::journal::{self, Transaction};
 use super::traits::FileRepository;
-use crate::errors::Result;
+use crate::errors::{Error, Result};
+use crate::models::SnapshotPolicy;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::path::Path;
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 
 const SNAPSHOTS_DIR: &str = ".snapshots";
-/// Maximum number of snapshots to keep per chapter
-const MAX_SNAPSHOTS_PER_CHAPTER: usize = 50;
+const OBJECTS_DIR: &str = "objects";
+const INDEX_FILENAME: &str = "index.json";
+/// A chapter's history is compacted at most this often.
+const COMPACTION_INTERVAL: Duration = Duration::hours(1);
+/// Longest chain of objects a snapshot is rebuilt from: a keyframe and the
+/// deltas on top of it.
+const KEYFRAME_INTERVAL: usize = 32;
+/// Starts an object stored as a delta. Chapter text never contains NUL.
+const DELTA_MAGIC: &str = "\0delta ";
+/// Timestamp format of snapshot ids, as used by the old snapshot filenames.
+const ID_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H%M%S";
 
-/// Cleans up old snapshots, keeping only the most recent MAX_SNAPSHOTS_PER_CHAPTER.
-/// This prevents disk space exhaustion over time.
-async fn cleanup_old_snapshots<R: FileRepository>(
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SnapshotEntry {
+    /// `<timestamp>_<short hash>`, unique within the chapter.
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    /// Full SHA-256 of the content, naming its object.
+    pub hash: String,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct SnapshotIndex {
+    /// Oldest first.
+    snapshots: Vec<SnapshotEntry>,
+    #[serde(default)]
+    compacted_at: Option<DateTime<Utc>>,
+}
+
+/// An object as stored: the full text, or encoded ops to apply to `base`.
+enum StoredObject {
+    Full(String),
+    Delta { base: String, ops: String },
+}
+
+/// An object's text, with the objects it was rebuilt from: itself first, its
+/// keyframe last.
+struct Resolved {
+    content: String,
+    chain: Vec<String>,
+}
+
+/// A snapshot that has not been written yet: the files to write, in order.
+pub struct PlannedSnapshot {
+    pub entry: SnapshotEntry,
+    pub writes: Vec<(PathBuf, Vec<u8>)>,
+}
+
+pub(super) fn snapshots_root(root_path: &Path) -> PathBuf {
+    root_path
+        .join(super::consts::MANUSCRIPT_DIR)
+        .join(SNAPSHOTS_DIR)
+}
+
+fn chapter_dir(root_path: &Path, chapter_id: &str) -> PathBuf {
+    snapshots_root(root_path).join(chapter_id)
+}
+
+fn index_path(root_path: &Path, chapter_id: &str) -> PathBuf {
+    chapter_dir(root_path, chapter_id).join(INDEX_FILENAME)
+}
+
+fn object_path(root_path: &Path, hash: &str) -> PathBuf {
+    let (fan_out, rest) = hash.split_at(2.min(hash.len()));
+    snapshots_root(root_path)
+        .join(OBJECTS_DIR)
+        .join(fan_out)
+        .join(rest)
+}
+
+pub fn content_hash(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+fn compress(content: &str) -> Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(content.as_bytes())?;
+    Ok(encoder.finish()?)
+}
+
+fn corrupt_object(path: PathBuf) -> Error {
+    Error::InvalidStructure {
+        path,
+        reason: "Snapshot object is corrupt".to_string(),
+    }
+}
+
+async fn read_object<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    hash: &str,
+) -> Result<StoredObject> {
+    let path = object_path(root_path, hash);
+    let bytes = repo.read_bytes(&path).await?;
+    let mut payload = String::new();
+    ZlibDecoder::new(bytes.as_slice())
+        .read_to_string(&mut payload)
+        .map_err(|_| corrupt_object(path))?;
+
+    let delta = payload
+        .strip_prefix(DELTA_MAGIC)
+        .and_then(|rest| rest.split_once('\n'));
+    Ok(match delta {
+        Some((base, ops)) => StoredObject::Delta {
+            base: base.to_string(),
+            ops: ops.to_string(),
+        },
+        None => StoredObject::Full(payload),
+    })
+}
+
+/// Rebuilds an object's text from its keyframe, checking every version on the
+/// way against its name so a damaged file is reported instead of restored.
+async fn resolve<R: FileRepository>(repo: &R, root_path: &Path, hash: &str) -> Result<Resolved> {
+    let corrupt = || corrupt_object(object_path(root_path, hash));
+    let mut chain = Vec::new();
+    let mut deltas = Vec::new();
+    let mut current = hash.to_string();
+    let mut content = loop {
+        let stored = read_object(repo, root_path, &current).await?;
+        chain.push(current);
+        match stored {
+            StoredObject::Full(text) => break text,
+            StoredObject::Delta { base, ops } => {
+                if chain.contains(&base) {
+                    return Err(corrupt());
+                }
+                deltas.push(ops);
+                current = base;
+            }
+        }
+    };
+
+    if content_hash(&content) != chain[deltas.len()] {
+        return Err(corrupt());
+    }
+    for (i, ops) in deltas.iter().enumerate().rev() {
+        let ops = delta::decode(ops).ok_or_else(corrupt)?;
+        content = delta::apply(&content, &ops).ok_or_else(corrupt)?;
+        if content_hash(&content) != chain[i] {
+            return Err(corrupt());
+        }
+    }
+    Ok(Resolved { content, chain })
+}
+
+/// The text stored under `hash`.
+pub(super) async fn read_object_text<R: FileRepository>(
     repo: &R,
-    _snapshots_dir: &Path, // Kept for future logging if needed
-    entries: &[std::path::PathBuf],
+    root_path: &Path,
+    hash: &str,
+) -> Result<String> {
+    Ok(resolve(repo, root_path, hash).await?.content)
+}
+
+/// `content` stored as a delta from `previous`, if that keeps the chain within
+/// KEYFRAME_INTERVAL and is smaller than the text itself.
+fn delta_object(content: &str, previous: &Resolved) -> Option<String> {
+    if previous.chain.len() >= KEYFRAME_INTERVAL {
+        return None;
+    }
+    let ops = delta::encode(&delta::diff(&previous.content, content));
+    (ops.len() < content.len()).then(|| format!("{}{}\n{}", DELTA_MAGIC, previous.chain[0], ops))
+}
+
+fn snapshot_id(timestamp: DateTime<Utc>, hash: &str, index: &SnapshotIndex) -> String {
+    let base = format!("{}_{}", timestamp.format(ID_TIMESTAMP_FORMAT), &hash[..8]);
+    let taken = |id: &str| index.snapshots.iter().any(|e| e.id == id);
+    if !taken(&base) {
+        return base;
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|id| !taken(id))
+        .unwrap_or(base)
+}
+
+async fn load_index<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    chapter_id: &str,
+) -> Result<SnapshotIndex> {
+    let path = index_path(root_path, chapter_id);
+    if !repo.exists(&path).await? {
+        return migrate_legacy_snapshots(repo, root_path, chapter_id).await;
+    }
+    match serde_json::from_slice(&repo.read_bytes(&path).await?) {
+        Ok(index) => Ok(index),
+        Err(e) => {
+            // A damaged index would block every save of the chapter. Its
+            // history is lost, but the file is kept for recovery by hand and
+            // the chapter starts a new one.
+            log::warn!(
+                "Snapshot index {} is damaged, starting a new one: {}",
+                path.display(),
+                e
+            );
+            repo.rename(&path, &path.with_extension("json.damaged"))
+                .await?;
+            Ok(SnapshotIndex::default())
+        }
+    }
+}
+
+async fn save_index<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    chapter_id: &str,
+    index: &SnapshotIndex,
+) -> Result<()> {
+    let dir = chapter_dir(root_path, chapter_id);
+    if !repo.exists(&dir).await? {
+        repo.create_dir_all(&dir).await?;
+    }
+    // Not pretty-printed: a long history has thousands of entries
+    journal::write_atomic(
+        &index_path(root_path, chapter_id),
+        serde_json::to_string(index)?.as_bytes(),
+    )
+    .await
+}
+
+/// Moves `<timestamp>_<hash>.md` snapshot files into the store, keeping their
+/// names as ids. Returns the resulting index.
+async fn migrate_legacy_snapshots<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    chapter_id: &str,
+) -> Result<SnapshotIndex> {
+    let mut index = SnapshotIndex::default();
+    let dir = chapter_dir(root_path, chapter_id);
+    if !repo.exists(&dir).await? {
+        return Ok(index);
+    }
+
+    let mut legacy = repo.read_dir(&dir).await?;
+    legacy.retain(|e| e.extension().is_some_and(|ext| ext == "md"));
+    if legacy.is_empty() {
+        return Ok(index);
+    }
+    // Timestamps (ISO) are sortable strings.
+    legacy.sort();
+
+    for path in &legacy {
+        let content = repo.read_file(path).await?;
+        let hash = content_hash(&content);
+        store_object(repo, root_path, &hash, &content).await?;
+
+        let id = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let timestamp = match id
+            .get(..17)
+            .and_then(|t| NaiveDateTime::parse_from_str(t, ID_TIMESTAMP_FORMAT).ok())
+        {
+            Some(t) => t.and_utc(),
+            None => {
+                let modified = repo.get_metadata(path).await?.modified;
+                DateTime::from_timestamp(modified as i64, 0).unwrap_or_default()
+            }
+        };
+        index.snapshots.push(SnapshotEntry {
+            id,
+            timestamp,
+            hash,
+            label: None,
+        });
+    }
+
+    // The index goes first so a failure part way leaves the files to retry
+    save_index(repo, root_path, chapter_id, &index).await?;
+    for path in &legacy {
+        repo.delete(path).await?;
+    }
+    Ok(index)
+}
+
+pub(super) async fn store_object<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    hash: &str,
+    content: &str,
 ) -> Result<()> {
-    if entries.len() > MAX_SNAPSHOTS_PER_CHAPTER {
-        let to_delete = entries.len() - MAX_SNAPSHOTS_PER_CHAPTER;
-        // Entries are sorted oldest first, so delete from the beginning
-        for path in entries.iter().take(to_delete) {
-            // Ignore errors during deletion - best effort cleanup
-            let _ = repo.delete(path).await;
+    let path = object_path(root_path, hash);
+    if repo.exists(&path).await? {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        repo.create_dir_all(parent).await?;
+    }
+    write_object_file(repo, &path, &compress(content)?).await
+}
+
+/// Writes an object through a staged copy, so it is never half written: an
+/// object that exists is taken to be complete and never written again.
+async fn write_object_file<R: FileRepository>(repo: &R, path: &Path, bytes: &[u8]) -> Result<()> {
+    let staged = path.with_extension("tmp");
+    repo.write_bytes(&staged, bytes).await?;
+    repo.rename(&staged, path).await
+}
+
+/// Plans a snapshot of `content` without writing it, so callers can write it as
+/// part of a larger transaction. Returns None if the content is identical to
+/// the latest snapshot (deduplicated) and there is no `label`, or, given a
+/// `throttle`, if the latest snapshot is both too recent and too similar.
+pub async fn plan_snapshot<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    chapter_id: &str,
+    content: &str,
+    label: Option<&str>,
+    throttle: Option<&SnapshotPolicy>,
+) -> Result<Option<PlannedSnapshot>> {
+    let mut index = load_index(repo, root_path, chapter_id).await?;
+    let hash = content_hash(content);
+
+    // A label marks this version even if it was snapshotted before
+    if label.is_none() && index.snapshots.last().is_some_and(|e| e.hash == hash) {
+        return Ok(None);
+    }
+
+    // A previous snapshot that cannot be read starts a new chain, and doesn't
+    // hold back the save
+    let previous = match index.snapshots.last() {
+        Some(last) => resolve(repo, root_path, &last.hash)
+            .await
+            .ok()
+            .map(|resolved| (last.timestamp, resolved)),
+        None => None,
+    };
+    let timestamp = Utc::now();
+    if let (Some(policy), Some((taken_at, previous))) = (throttle, &previous) {
+        let recent = timestamp - *taken_at < Duration::seconds(policy.min_interval_seconds.into());
+        if recent
+            && delta::changed_chars(&previous.content, content) < policy.min_change_chars as usize
+        {
+            return Ok(None);
         }
     }
-    Ok(())
+
+    let entry = SnapshotEntry {
+        id: snapshot_id(timestamp, &hash, &index),
+        timestamp,
+        hash: hash.clone(),
+        label: label.map(str::to_string),
+    };
+
+    let mut writes = Vec::new();
+    // Identical text saved before, by any chapter, is already stored
+    let object = object_path(root_path, &hash);
+    if !repo.exists(&object).await? {
+        let payload = previous
+            .and_then(|(_, previous)| delta_object(content, &previous))
+            .unwrap_or_else(|| content.to_string());
+        writes.push((object, compress(&payload)?));
+    }
+    index.snapshots.push(entry.clone());
+    writes.push((
+        index_path(root_path, chapter_id),
+        serde_json::to_string(&index)?.into_bytes(),
+    ));
+
+    Ok(Some(PlannedSnapshot { entry, writes }))
 }
 
-/// Creates a snapshot of the given content for a chapter.
-/// Returns Ok(Some(snapshot_path)) if a snapshot was created,
+/// Creates a snapshot of the given content for a chapter, whenever it differs
+/// from the latest one or has a `label`. Labelled snapshots are kept for good;
+/// `policy` decides which other older snapshots are kept.
+/// Returns Ok(Some(entry)) if a snapshot was created,
 /// Ok(None) if the content is identical to the latest snapshot (deduplicated),
 /// or Err if an IO error occurred.
 pub async fn create_snapshot<R: FileRepository>(
@@ -34,129 +417,556 @@ pub async fn create_snapshot<R: FileRepository>(
     root_path: &Path,
     chapter_id: &str,
     content: &str,
-) -> Result<Option<String>> {
-    // 1. Setup paths
-    // Structure: <root>/manuscript/.snapshots/<chapter_id>/
-    // Note: We use "manuscript" as base because chapters are there?
-    // Let's verify where chapters are. MANUSCRIPT_DIR.
-    // Ideally .snapshots should be adjacent or inside.
-    // Let's go with <root>/manuscript/<chapter_id>/.snapshots is not possible because chapter is a file.
-    // So <root>/manuscript/.snapshots/<chapter_id>/
-    let snapshots_dir = root_path
-        .join(super::consts::MANUSCRIPT_DIR)
-        .join(SNAPSHOTS_DIR)
-        .join(chapter_id);
+    label: Option<&str>,
+    policy: &SnapshotPolicy,
+) -> Result<Option<SnapshotEntry>> {
+    let Some(planned) = plan_snapshot(repo, root_path, chapter_id, content, label, None).await?
+    else {
+        return Ok(None);
+    };
 
-    if !repo.exists(&snapshots_dir).await? {
-        repo.create_dir_all(&snapshots_dir).await?;
+    let mut transaction = Transaction::new(root_path);
+    for (path, bytes) in &planned.writes {
+        transaction.write(path, bytes.clone());
     }
-
-    // 2. Compute hash of current content
-    let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
-    let hash = hex::encode(hasher.finalize());
-    let short_hash = &hash[0..8];
-
-    // 3. Check latest snapshot to deduplicate
-    // We list files, sort by name (timestamp should ensure order), and check last.
-    // But reading file content is slow?
-    // Optimization: Store hash in filename? <timestamp>_<hash>.md
-    // Then we just check if the last file has the same hash suffix.
-
-    let mut entries = repo.read_dir(&snapshots_dir).await?;
-    // Filter for .md files
-    entries.retain(|e| e.extension().is_some_and(|ext| ext == "md"));
-    // Sort determines order. Timestamps (ISO) are sortable strings.
-    entries.sort();
-
-    if let Some(last_entry) = entries.last() {
-        if let Some(filename) = last_entry.file_name() {
-            let filename_str = filename.to_string_lossy();
-            // Check if filename ends with hash
-            if filename_str.contains(&format!("_{}.md", short_hash)) {
-                // Content matches, dedupe
-                return Ok(None);
-            }
-        }
+    transaction.commit().await?;
+    // The snapshot is written; a failed compaction is retried after the next one
+    if let Err(e) = prune_snapshots(repo, root_path, chapter_id, policy).await {
+        log::warn!("Failed to compact snapshots of {}: {}", chapter_id, e);
     }
 
-    // 4. Create new snapshot
-    let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H%M%S");
-    let filename = format!("{}_{}.md", timestamp, short_hash);
-    let path = snapshots_dir.join(&filename);
+    Ok(Some(planned.entry))
+}
 
-    repo.write_file(&path, content).await?;
+/// Compacts the chapter's history if it hasn't been for COMPACTION_INTERVAL.
+/// This prevents disk space exhaustion over time.
+pub async fn prune_snapshots<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    chapter_id: &str,
+    policy: &SnapshotPolicy,
+) -> Result<()> {
+    let index = load_index(repo, root_path, chapter_id).await?;
+    let due = index
+        .compacted_at
+        .is_none_or(|at| Utc::now() - at >= COMPACTION_INTERVAL);
+    if due {
+        compact_snapshots(repo, root_path, chapter_id, policy).await?;
+    }
+    Ok(())
+}
 
-    // 5. Cleanup old snapshots to prevent disk exhaustion
-    // Re-read entries after adding the new one
-    let mut updated_entries = repo.read_dir(&snapshots_dir).await?;
-    updated_entries.retain(|e| e.extension().is_some_and(|ext| ext == "md"));
-    updated_entries.sort();
-    cleanup_old_snapshots(repo, &snapshots_dir, &updated_entries).await?;
+/// Thins a history, oldest first, as `policy` asks: every snapshot from the
+/// last `keep_all_minutes`, then the latest of each hour, of each day and,
+/// past `daily_for_days`, of each week. Labelled snapshots are always kept.
+fn apply_retention(
+    snapshots: &mut Vec<SnapshotEntry>,
+    policy: &SnapshotPolicy,
+    now: DateTime<Utc>,
+) {
+    let keep_all = Duration::minutes(policy.keep_all_minutes.into());
+    let hourly = Duration::days(policy.hourly_for_days.into());
+    let daily = Duration::days(policy.daily_for_days.into());
+    // Buckets are fixed spans of time, so a kept snapshot stays kept as it ages
+    let bucket = |entry: &SnapshotEntry| {
+        let age = now - entry.timestamp;
+        let seconds = entry.timestamp.timestamp();
+        if entry.label.is_some() || age < keep_all {
+            None
+        } else if age < hourly {
+            Some((0, seconds.div_euclid(3600)))
+        } else if age < daily {
+            Some((1, seconds.div_euclid(86_400)))
+        } else {
+            Some((2, seconds.div_euclid(7 * 86_400)))
+        }
+    };
 
-    Ok(Some(filename))
+    // Newest first, so the first snapshot seen in a bucket is its latest
+    let mut seen = HashSet::new();
+    let mut keep: Vec<bool> = snapshots
+        .iter()
+        .rev()
+        .map(|entry| bucket(entry).is_none_or(|b| seen.insert(b)))
+        .collect();
+    keep.reverse();
+    let mut keep = keep.into_iter();
+    snapshots.retain(|_| keep.next().unwrap_or(true));
 }
 
-pub async fn list_snapshots<R: FileRepository>(
+/// Applies the retention policy to a chapter's history and stores every kept
+/// snapshot as a delta from the one before it where that is smaller, as
+/// `plan_snapshot` would have. This covers snapshots migrated from before
+/// deltas, and deltas whose base was dropped. Objects nothing needs any more
+/// are then deleted.
+pub async fn compact_snapshots<R: FileRepository>(
     repo: &R,
     root_path: &Path,
     chapter_id: &str,
-) -> Result<Vec<String>> {
-    let snapshots_dir = root_path
-        .join(super::consts::MANUSCRIPT_DIR)
-        .join(SNAPSHOTS_DIR)
-        .join(chapter_id);
+    policy: &SnapshotPolicy,
+) -> Result<()> {
+    let mut index = load_index(repo, root_path, chapter_id).await?;
+    let count = index.snapshots.len();
+    let now = Utc::now();
+    apply_retention(&mut index.snapshots, policy, now);
+    let dropped = count - index.snapshots.len();
+    index.compacted_at = Some(now);
+    save_index(repo, root_path, chapter_id, &index).await?;
 
-    if !repo.exists(&snapshots_dir).await? {
-        return Ok(Vec::new());
+    let mut previous: Option<&str> = None;
+    for entry in &index.snapshots {
+        let hash = entry.hash.as_str();
+        let stale = match read_object(repo, root_path, hash).await {
+            Ok(StoredObject::Full(_)) => previous.is_some(),
+            Ok(StoredObject::Delta { base, .. }) => Some(base.as_str()) != previous,
+            // Damaged objects are left as they are
+            Err(_) => false,
+        };
+        if stale {
+            reencode_object(repo, root_path, hash, previous).await?;
+        }
+        previous = Some(hash);
     }
 
-    let mut entries = repo.read_dir(&snapshots_dir).await?;
-    entries.retain(|e| e.extension().is_some_and(|ext| ext == "md"));
-    entries.sort();
+    if dropped > 0 {
+        collect_garbage(repo, root_path).await?;
+    }
+    Ok(())
+}
 
-    // Return filenames
-    Ok(entries
-        .into_iter()
-        .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
-        .collect())
+/// Stores an object as a delta from `previous` where that is smaller, and in
+/// full otherwise.
+async fn reencode_object<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    hash: &str,
+    previous: Option<&str>,
+) -> Result<()> {
+    let Ok(current) = resolve(repo, root_path, hash).await else {
+        return Ok(());
+    };
+    let base = match previous {
+        Some(previous) => resolve(repo, root_path, previous).await.ok(),
+        None => None,
+    };
+    // Never base an object on one that is rebuilt from it
+    let payload = base
+        .filter(|base| !base.chain.iter().any(|h| h == hash))
+        .and_then(|base| delta_object(&current.content, &base));
+    match payload {
+        Some(payload) => replace_object(repo, root_path, hash, &payload).await,
+        None if current.chain.len() > 1 => {
+            replace_object(repo, root_path, hash, &current.content).await
+        }
+        None => Ok(()),
+    }
 }
 
-pub async fn read_snapshot_content<R: FileRepository>(
+async fn replace_object<R: FileRepository>(
     repo: &R,
     root_path: &Path,
-    chapter_id: &str,
-    filename: &str,
-) -> Result<String> {
-    let path = root_path
-        .join(super::consts::MANUSCRIPT_DIR)
-        .join(SNAPSHOTS_DIR)
-        .join(chapter_id)
-        .join(filename);
+    hash: &str,
+    payload: &str,
+) -> Result<()> {
+    let path = object_path(root_path, hash);
+    write_object_file(repo, &path, &compress(payload)?).await
+}
+
+/// Deletes the objects no snapshot needs: those no chapter's index refers to
+/// and no delta is based on.
+async fn collect_garbage<R: FileRepository>(repo: &R, root_path: &Path) -> Result<()> {
+    let mut live = HashSet::new();
+    for hash in referenced_hashes(repo, root_path).await? {
+        let mut current = hash;
+        while live.insert(current.clone()) {
+            match read_object(repo, root_path, &current).await {
+                Ok(StoredObject::Delta { base, .. }) => current = base,
+                _ => break,
+            }
+        }
+    }
 
-    repo.read_file(&path).await
+    let objects_dir = snapshots_root(root_path).join(OBJECTS_DIR);
+    if !repo.exists(&objects_dir).await? {
+        return Ok(());
+    }
+    for fan_out in repo.read_dir(&objects_dir).await? {
+        for path in repo.read_dir(&fan_out).await? {
+            let hash: String = path
+                .strip_prefix(&objects_dir)
+                .map(|name| name.iter().map(|part| part.to_string_lossy()).collect())
+                .unwrap_or_default();
+            if !live.contains(&hash) {
+                // Ignore errors during deletion - best effort cleanup
+                let _ = repo.delete(&path).await;
+            }
+        }
+    }
+    Ok(())
 }
 
-pub async fn restore_snapshot<R: FileRepository>(
+/// Hashes of every object some chapter's index or a milestone refers to.
+async fn referenced_hashes<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+) -> Result<HashSet<String>> {
+    let mut hashes = HashSet::new();
+    for dir in repo.read_dir(&snapshots_root(root_path)).await? {
+        let path = dir.join(INDEX_FILENAME);
+        if !repo.exists(&path).await? {
+            continue;
+        }
+        let index: SnapshotIndex = serde_json::from_str(&repo.read_file(&path).await?)?;
+        hashes.extend(index.snapshots.into_iter().map(|e| e.hash));
+    }
+    for milestone in super::milestones::list_milestones(repo, root_path).await? {
+        hashes.extend(milestone.files.into_values());
+    }
+    Ok(hashes)
+}
+
+/// A chapter's snapshots, oldest first.
+pub async fn list_snapshots<R: FileRepository>(
     repo: &R,
     root_path: &Path,
     chapter_id: &str,
-    snapshot_filename: &str,
-    current_content: &str,
-    chapter_filename: &str,
+) -> Result<Vec<SnapshotEntry>> {
+    Ok(load_index(repo, root_path, chapter_id).await?.snapshots)
+}
+
+pub async fn read_snapshot_content<R: FileRepository>(
+    repo: &R,
+    root_path: &Path,
+    chapter_id: &str,
+    snapshot_id: &str,
 ) -> Result<String> {
-    // 1. Create safety snapshot of current state
-    create_snapshot(repo, root_path, chapter_id, current_content).await?;
+    let index = load_index(repo, root_path, chapter_id).await?;
+    let entry = index
+        .snapshots
+        .iter()
+        .find(|e| e.id == snapshot_id)
+        .ok_or_else(|| Error::SnapshotNotFound {
+            id: snapshot_id.to_string(),
+        })?;
 
-    // 2. Read snapshot content
-    let content = read_snapshot_content(repo, root_path, chapter_id, snapshot_filename).await?;
+    Ok(resolve(repo, root_path, &entry.hash).await?.content)
+}
 
-    // 3. Write back to chapter file
-    let chapter_path = root_path
-        .join(super::consts::MANUSCRIPT_DIR)
-        .join(chapter_filename);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::LocalFileRepository;
+
+    fn object_count(root: &Path) -> usize {
+        let objects = snapshots_root(root).join(OBJECTS_DIR);
+        std::fs::read_dir(objects)
+            .map(|dirs| {
+                dirs.map(|d| std::fs::read_dir(d.unwrap().path()).unwrap().count())
+                    .sum()
+            })
+            .unwrap_or(0)
+    }
+
+    #[tokio::test]
+    async fn test_identical_content_is_stored_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let repo = LocalFileRepository;
+        let policy = SnapshotPolicy::default();
+
+        let first = create_snapshot(&repo, root, "c1", "<p>Once</p>", None, &policy)
+            .await
+            .unwrap()
+            .unwrap();
+        // Same as the latest: deduplicated
+        assert!(
+            create_snapshot(&repo, root, "c1", "<p>Once</p>", None, &policy)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        // Same text in another chapter shares the object
+        create_snapshot(&repo, root, "c2", "<p>Once</p>", None, &policy)
+            .await
+            .unwrap()
+            .unwrap();
+        create_snapshot(&repo, root, "c1", "<p>Twice</p>", None, &policy)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(object_count(root), 2);
+        assert_eq!(first.hash, content_hash("<p>Once</p>"));
+        let listed = list_snapshots(&repo, root, "c1").await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(
+            read_snapshot_content(&repo, root, "c1", &first.id)
+                .await
+                .unwrap(),
+            "<p>Once</p>"
+        );
+
+        // Stored compressed, not as plain text
+        let raw = std::fs::read(object_path(root, &first.hash)).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("Once"));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_snapshots_are_migrated() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let repo = LocalFileRepository;
+        let legacy = chapter_dir(root, "c1");
+        std::fs::create_dir_all(&legacy).unwrap();
+        std::fs::write(legacy.join("2024-01-02T030405_abcdef12.md"), "Old").unwrap();
+
+        let listed = list_snapshots(&repo, root, "c1").await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, "2024-01-02T030405_abcdef12");
+        assert_eq!(
+            listed[0].timestamp.to_rfc3339(),
+            "2024-01-02T03:04:05+00:00"
+        );
+        assert!(!legacy.join("2024-01-02T030405_abcdef12.md").exists());
+        assert_eq!(
+            read_snapshot_content(&repo, root, "c1", &listed[0].id)
+                .await
+                .unwrap(),
+            "Old"
+        );
+    }
+
+    /// A chapter that has grown to `n + 1` paragraphs.
+    fn revision(n: usize) -> String {
+        (0..=n)
+            .map(|i| format!("<p>Paragraph {} of a chapter that keeps growing.</p>", i))
+            .collect()
+    }
+
+    async fn full_objects(repo: &LocalFileRepository, root: &Path, chapter_id: &str) -> usize {
+        let mut count = 0;
+        for entry in list_snapshots(repo, root, chapter_id).await.unwrap() {
+            if let StoredObject::Full(_) = read_object(repo, root, &entry.hash).await.unwrap() {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    #[tokio::test]
+    async fn test_history_is_stored_as_keyframes_and_deltas() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let repo = LocalFileRepository;
+        let policy = SnapshotPolicy::default();
+
+        for n in 0..40 {
+            create_snapshot(&repo, root, "c1", &revision(n), None, &policy)
+                .await
+                .unwrap();
+        }
+
+        // A keyframe, 31 deltas, then a new keyframe
+        assert_eq!(full_objects(&repo, root, "c1").await, 2);
+        let listed = list_snapshots(&repo, root, "c1").await.unwrap();
+        for (n, entry) in listed.iter().enumerate() {
+            assert_eq!(
+                read_snapshot_content(&repo, root, "c1", &entry.id)
+                    .await
+                    .unwrap(),
+                revision(n)
+            );
+        }
+    }
+
+    #[test]
+    fn test_retention_thins_history_by_age() {
+        let now = DateTime::parse_from_rfc3339("2024-06-15T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let at = |minutes_ago: i64, label: Option<&str>| SnapshotEntry {
+            id: minutes_ago.to_string(),
+            timestamp: now - Duration::minutes(minutes_ago),
+            hash: String::new(),
+            label: label.map(str::to_string),
+        };
+        let day = 24 * 60;
+        let mut snapshots = vec![
+            // Weekly: 2024-04-16 11:00 and 12:00, a Tuesday
+            at(60 * day + 60, None),
+            at(60 * day + 30, Some("Draft 1")),
+            at(60 * day, None),
+            // Daily: 2024-06-12 10:00 and 11:00
+            at(3 * day + 120, None),
+            at(3 * day + 60, None),
+            // Hourly: 09:50 and 09:55
+            at(130, None),
+            at(125, None),
+            // Everything from the last hour
+            at(30, None),
+            at(10, None),
+        ];
+        apply_retention(&mut snapshots, &SnapshotPolicy::default(), now);
+
+        let kept: Vec<&str> = snapshots.iter().map(|e| e.id.as_str()).collect();
+        let expected = [60 * day + 30, 60 * day, 3 * day + 60, 125, 30, 10].map(|m| m.to_string());
+        assert_eq!(kept, expected);
+    }
+
+    #[tokio::test]
+    async fn test_autosaves_are_throttled() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let repo = LocalFileRepository;
+        let policy = SnapshotPolicy {
+            min_change_chars: 100,
+            ..SnapshotPolicy::default()
+        };
+        create_snapshot(&repo, root, "c1", &revision(0), None, &policy)
+            .await
+            .unwrap();
+
+        // A small edit right after the latest snapshot
+        let small = revision(0) + "<p>A few words.</p>";
+        assert!(
+            plan_snapshot(&repo, root, "c1", &small, None, Some(&policy))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        // Unthrottled, or with enough changed
+        assert!(plan_snapshot(&repo, root, "c1", &small, None, None)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(
+            plan_snapshot(&repo, root, "c1", &revision(3), None, Some(&policy))
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compaction_drops_old_snapshots_and_encodes_deltas() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let repo = LocalFileRepository;
+        let policy = SnapshotPolicy::default();
+        let legacy = chapter_dir(root, "c1");
+        std::fs::create_dir_all(&legacy).unwrap();
+        // The first three fall in the same week
+        let dates = ["01-01", "01-02", "01-03", "02-01", "03-01"];
+        for (n, date) in dates.iter().enumerate() {
+            let name = format!("2024-{}T000000_0000000{}.md", date, n);
+            std::fs::write(legacy.join(name), revision(n)).unwrap();
+        }
+        // Migrated snapshots are stored in full
+        assert_eq!(full_objects(&repo, root, "c1").await, 5);
+        // Another chapter shares the oldest text
+        create_snapshot(&repo, root, "c2", &revision(0), None, &policy)
+            .await
+            .unwrap();
+
+        compact_snapshots(&repo, root, "c1", &policy).await.unwrap();
 
-    repo.write_file(&chapter_path, &content).await?;
+        let listed = list_snapshots(&repo, root, "c1").await.unwrap();
+        assert_eq!(listed.len(), 3);
+        assert_eq!(full_objects(&repo, root, "c1").await, 1);
+        for (entry, n) in listed.iter().zip(2..) {
+            assert_eq!(
+                read_snapshot_content(&repo, root, "c1", &entry.id)
+                    .await
+                    .unwrap(),
+                revision(n)
+            );
+        }
+        // Revision 1 is gone; revision 0 stays for c2
+        assert_eq!(object_count(root), 4);
+    }
 
-    Ok(content)
+    #[tokio::test]
+    async fn test_labelled_snapshot_survives_compaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let repo = LocalFileRepository;
+        let policy = SnapshotPolicy::default();
+        create_snapshot(&repo, root, "c1", &revision(0), None, &policy)
+            .await
+            .unwrap();
+        // Labelling the latest text again records it
+        create_snapshot(&repo, root, "c1", &revision(0), Some("Sent"), &policy)
+            .await
+            .unwrap()
+            .unwrap();
+        create_snapshot(&repo, root, "c1", &revision(1), None, &policy)
+            .await
+            .unwrap();
+
+        // Age all three into the same week
+        let start = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .to_utc();
+        let mut index = load_index(&repo, root, "c1").await.unwrap();
+        for (entry, n) in index.snapshots.iter_mut().zip(0..) {
+            entry.timestamp = start + Duration::minutes(n);
+        }
+        save_index(&repo, root, "c1", &index).await.unwrap();
+
+        compact_snapshots(&repo, root, "c1", &policy).await.unwrap();
+
+        let listed = list_snapshots(&repo, root, "c1").await.unwrap();
+        let labels: Vec<Option<&str>> = listed.iter().map(|e| e.label.as_deref()).collect();
+        assert_eq!(labels, vec![Some("Sent"), None]);
+        assert_eq!(
+            read_snapshot_content(&repo, root, "c1", &listed[0].id)
+                .await
+                .unwrap(),
+            revision(0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_damaged_index_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let repo = LocalFileRepository;
+        let policy = SnapshotPolicy::default();
+        create_snapshot(&repo, root, "c1", "First", None, &policy)
+            .await
+            .unwrap();
+        // Cut short by a crash
+        let path = index_path(root, "c1");
+        std::fs::write(&path, "{\"snapshots\":[{\"id\":").unwrap();
+
+        let entry = create_snapshot(&repo, root, "c1", "Second", None, &policy)
+            .await
+            .unwrap()
+            .unwrap();
+        let listed = list_snapshots(&repo, root, "c1").await.unwrap();
+        assert_eq!(listed, vec![entry]);
+        assert!(path.with_extension("json.damaged").exists());
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_object_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let repo = LocalFileRepository;
+        let policy = SnapshotPolicy::default();
+        let entry = create_snapshot(&repo, root, "c1", "text", None, &policy)
+            .await
+            .unwrap()
+            .unwrap();
+        std::fs::write(object_path(root, &entry.hash), compress("other").unwrap()).unwrap();
+
+        assert!(matches!(
+            read_snapshot_content(&repo, root, "c1", &entry.id).await,
+            Err(Error::InvalidStructure { .. })
+        ));
+        assert!(matches!(
+            read_snapshot_content(&repo, root, "c1", "missing").await,
+            Err(Error::SnapshotNotFound { .. })
+        ));
+    }
 }
@@ -12,8 +12,11 @@ pub struct FileMetadata {
 pub trait FileRepository: Send + Sync {
     async fn read_file(&self, path: &Path) -> Result<String>;
     async fn write_file(&self, path: &Path, content: &str) -> Result<()>;
+    async fn read_bytes(&self, path: &Path) -> Result<Vec<u8>>;
+    async fn write_bytes(&self, path: &Path, content: &[u8]) -> Result<()>;
     async fn exists(&self, path: &Path) -> Result<bool>;
     async fn delete(&self, path: &Path) -> Result<()>;
+    async fn rename(&self, from: &Path, to: &Path) -> Result<()>;
     async fn create_dir_all(&self, path: &Path) -> Result<()>;
     async fn read_dir(&self, path: &Path) -> Result<Vec<std::path::PathBuf>>;
     async fn get_metadata(&self, path: &Path) -> Result<FileMetadata>;
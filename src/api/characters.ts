@@ -1,6 +1,8 @@
 import { invoke } from '@tauri-apps/api/core';
 import type { ProjectMetadata, Character } from '../types';
 
+export type CharacterFormat = 'csv' | 'json';
+
 export const charactersApi = {
   save: async (projectId: string, character: Character): Promise<ProjectMetadata> => {
     return invoke<ProjectMetadata>('save_character', { projectId, character });
@@ -12,4 +14,17 @@ export const charactersApi = {
       characterId,
     });
   },
+
+  export: async (projectId: string, format: CharacterFormat, outputPath: string): Promise<void> => {
+    return invoke('export_characters', { projectId, format, outputPath });
+  },
+
+  import: async (
+    projectId: string,
+    format: CharacterFormat,
+    sourcePath: string,
+    merge: boolean,
+  ): Promise<ProjectMetadata> => {
+    return invoke<ProjectMetadata>('import_characters', { projectId, format, sourcePath, merge });
+  },
 };
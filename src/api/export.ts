@@ -0,0 +1,52 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export type CompileFormat =
+  | 'epub'
+  | 'docx'
+  | 'markdown'
+  | 'latex'
+  | 'pdf'
+  | 'odt'
+  | 'html'
+  | 'html_site';
+
+export type BibleFormat = 'markdown' | 'html';
+
+export type TimelineFormat = 'ics' | 'csv';
+
+export type OutlineFormat = 'markdown' | 'csv';
+
+export const exportApi = {
+  compile: async (
+    projectId: string,
+    format: CompileFormat,
+    outputPath: string,
+    presetId?: string
+  ): Promise<void> => {
+    return invoke('compile_project', { projectId, format, outputPath, presetId });
+  },
+
+  exportCharacterBible: async (
+    projectId: string,
+    format: BibleFormat,
+    outputPath: string
+  ): Promise<void> => {
+    return invoke('export_character_bible', { projectId, format, outputPath });
+  },
+
+  exportTimeline: async (
+    projectId: string,
+    format: TimelineFormat,
+    outputPath: string
+  ): Promise<void> => {
+    return invoke('export_timeline', { projectId, format, outputPath });
+  },
+
+  exportOutline: async (
+    projectId: string,
+    format: OutlineFormat,
+    outputPath: string
+  ): Promise<void> => {
+    return invoke('export_outline', { projectId, format, outputPath });
+  },
+};
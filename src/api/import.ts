@@ -0,0 +1,56 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { ProjectMetadata } from '../types';
+
+export type SplitPattern = 'chapter_heading' | 'markdown_heading' | 'separator' | { regex: string };
+
+export interface TextSplitOptions {
+  chapter_pattern: SplitPattern;
+  scene_pattern?: SplitPattern;
+}
+
+export interface PreviewNode {
+  title: string;
+  word_count: number;
+  children: PreviewNode[];
+}
+
+export const importApi = {
+  scrivener: async (sourcePath: string, path: string, author: string): Promise<ProjectMetadata> => {
+    return invoke<ProjectMetadata>('import_scrivener', { sourcePath, path, author });
+  },
+
+  docx: async (sourcePath: string, path: string, author: string): Promise<ProjectMetadata> => {
+    return invoke<ProjectMetadata>('import_docx', { sourcePath, path, author });
+  },
+
+  markdownFolder: async (
+    sourcePath: string,
+    path: string,
+    author: string,
+    researchFolder?: string
+  ): Promise<ProjectMetadata> => {
+    return invoke<ProjectMetadata>('import_markdown_folder', {
+      sourcePath,
+      path,
+      author,
+      researchFolder,
+    });
+  },
+
+  previewText: async (sourcePath: string, options: TextSplitOptions): Promise<PreviewNode[]> => {
+    return invoke<PreviewNode[]>('preview_text_import', { sourcePath, options });
+  },
+
+  text: async (
+    sourcePath: string,
+    path: string,
+    author: string,
+    options: TextSplitOptions
+  ): Promise<ProjectMetadata> => {
+    return invoke<ProjectMetadata>('import_text', { sourcePath, path, author, options });
+  },
+
+  epub: async (sourcePath: string, path: string, author: string): Promise<ProjectMetadata> => {
+    return invoke<ProjectMetadata>('import_epub', { sourcePath, path, author });
+  },
+};
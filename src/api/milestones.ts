@@ -0,0 +1,28 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { Milestone, ProjectMetadata } from '../types';
+
+export const milestonesApi = {
+  create: async (projectId: string, name: string, includeResearch: boolean): Promise<Milestone> => {
+    return invoke<Milestone>('create_milestone', { projectId, name, includeResearch });
+  },
+
+  list: async (projectId: string): Promise<Milestone[]> => {
+    return invoke<Milestone[]>('list_milestones', { projectId });
+  },
+
+  restore: async (projectId: string, milestoneId: string): Promise<ProjectMetadata> => {
+    return invoke<ProjectMetadata>('restore_milestone', { projectId, milestoneId });
+  },
+
+  restoreChapter: async (
+    projectId: string,
+    milestoneId: string,
+    chapterId: string,
+  ): Promise<ProjectMetadata> => {
+    return invoke<ProjectMetadata>('restore_milestone_chapter', {
+      projectId,
+      milestoneId,
+      chapterId,
+    });
+  },
+};
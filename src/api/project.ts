@@ -1,5 +1,11 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { ProjectMetadata, Manifest, ProjectSettings, Plotline } from '../types';
+import type {
+  ProjectMetadata,
+  Manifest,
+  ProjectSettings,
+  Plotline,
+  CompilePreset,
+} from '../types';
 
 export const projectApi = {
   // Project Management
@@ -11,6 +17,11 @@ export const projectApi = {
     return invoke<ProjectMetadata>('load_project', { path });
   },
 
+  // Writes pending changes before the project is unloaded
+  close: async (projectId: string): Promise<void> => {
+    return invoke('close_project', { projectId });
+  },
+
   updateManifest: async (projectId: string, manifest: Manifest): Promise<ProjectMetadata> => {
     return invoke<ProjectMetadata>('update_manifest', { projectId, manifest });
   },
@@ -31,4 +42,14 @@ export const projectApi = {
       plotlines,
     });
   },
+
+  updateCompilePresets: async (
+    projectId: string,
+    presets: CompilePreset[]
+  ): Promise<ProjectMetadata> => {
+    return invoke<ProjectMetadata>('update_compile_presets', {
+      projectId,
+      presets,
+    });
+  },
 };
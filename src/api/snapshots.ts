@@ -0,0 +1,30 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { SnapshotEntry } from '../types';
+
+export const snapshotsApi = {
+  list: async (projectId: string, chapterId: string): Promise<SnapshotEntry[]> => {
+    return invoke<SnapshotEntry[]>('list_snapshots', { projectId, chapterId });
+  },
+
+  loadContent: async (projectId: string, chapterId: string, snapshotId: string): Promise<string> => {
+    return invoke<string>('load_snapshot_content', { projectId, chapterId, snapshotId });
+  },
+
+  // A labelled snapshot is kept however old it gets
+  create: async (
+    projectId: string,
+    chapterId: string,
+    content: string,
+    label?: string,
+  ): Promise<void> => {
+    return invoke('create_snapshot', { projectId, chapterId, content, label: label ?? null });
+  },
+
+  restore: async (projectId: string, chapterId: string, snapshotId: string): Promise<string> => {
+    return invoke<string>('restore_snapshot', { projectId, chapterId, snapshotId });
+  },
+
+  branch: async (projectId: string, chapterId: string, snapshotId: string): Promise<void> => {
+    return invoke('branch_snapshot', { projectId, snapshotChapterId: chapterId, snapshotId });
+  },
+};
@@ -105,10 +105,10 @@ export function useEditorSession(projectId: Ref<string>, chapterId: Ref<string>)
   };
 
   // Snapshot Restoration
-  const restoreSnapshot = async (filename: string) => {
+  const restoreSnapshot = async (snapshotId: string) => {
     if (!chapterId.value) return;
     try {
-      const newContent = await snapshotStore.restoreSnapshot(chapterId.value, filename);
+      const newContent = await snapshotStore.restoreSnapshot(chapterId.value, snapshotId);
       if (newContent !== undefined) {
         currentHtml.value = newContent;
         isDirty.value = false;
@@ -123,10 +123,10 @@ export function useEditorSession(projectId: Ref<string>, chapterId: Ref<string>)
     }
   };
 
-  const branchSnapshot = async (filename: string) => {
+  const branchSnapshot = async (snapshotId: string) => {
     if (!projectId.value || !chapterId.value) return;
     try {
-      await snapshotStore.branchSnapshot(chapterId.value, filename);
+      await snapshotStore.branchSnapshot(chapterId.value, snapshotId);
     } catch (e) {
       notifyError('Failed to branch snapshot', e);
       throw e;
@@ -1,5 +1,6 @@
 import { defineStore } from 'pinia';
 import { ref, shallowRef, computed, triggerRef } from 'vue';
+import { projectApi } from '../api/project';
 import type { FileNode, ProjectSettings, Character, Plotline } from '../types';
 
 export const useProjectStore = defineStore('project', () => {
@@ -112,7 +113,8 @@ export const useProjectStore = defineStore('project', () => {
     rebuildAll(newNodes);
   }
 
-  function closeProject() {
+  async function closeProject() {
+    const closingId = projectId.value;
     projectId.value = undefined;
     path.value = undefined;
     nodes.value = [];
@@ -126,6 +128,14 @@ export const useProjectStore = defineStore('project', () => {
 
     // Clear derived state
     rebuildAll([]);
+
+    if (closingId) {
+      try {
+        await projectApi.close(closingId);
+      } catch (e) {
+        console.error('Failed to close project', e);
+      }
+    }
   }
 
   // Granular Mutations (Optimized)
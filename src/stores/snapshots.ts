@@ -1,11 +1,12 @@
 import { defineStore } from 'pinia';
-import { invoke } from '@tauri-apps/api/core';
 import { ref } from 'vue';
 import { useProjectStore } from './project';
 import { useProjectLoader } from '../composables/domain/project/useProjectLoader';
+import { snapshotsApi } from '../api/snapshots';
+import type { SnapshotEntry } from '../types';
 
 export const useSnapshotStore = defineStore('snapshots', () => {
-  const snapshots = ref<string[]>([]);
+  const snapshots = ref<SnapshotEntry[]>([]);
   const loading = ref(false);
   const projectStore = useProjectStore();
   const { loadProject } = useProjectLoader();
@@ -14,12 +15,9 @@ export const useSnapshotStore = defineStore('snapshots', () => {
     if (!projectStore.projectId) return;
     loading.value = true;
     try {
-      const result = await invoke<string[]>('list_snapshots', {
-        projectId: projectStore.projectId,
-        chapterId,
-      });
-      // Sort descending (newest first)
-      snapshots.value = result.sort().reverse();
+      const result = await snapshotsApi.list(projectStore.projectId, chapterId);
+      // Listed oldest first; show newest first
+      snapshots.value = result.reverse();
     } catch (e) {
       console.error('Failed to fetch snapshots', e);
       snapshots.value = [];
@@ -28,28 +26,20 @@ export const useSnapshotStore = defineStore('snapshots', () => {
     }
   }
 
-  async function loadSnapshotContent(chapterId: string, filename: string): Promise<string> {
+  async function loadSnapshotContent(chapterId: string, snapshotId: string): Promise<string> {
     if (!projectStore.projectId) return '';
     try {
-      return await invoke<string>('load_snapshot_content', {
-        projectId: projectStore.projectId,
-        chapterId,
-        filename,
-      });
+      return await snapshotsApi.loadContent(projectStore.projectId, chapterId, snapshotId);
     } catch (e) {
       console.error('Failed to load snapshot content', e);
       throw e;
     }
   }
 
-  async function createSnapshot(chapterId: string, content: string) {
+  async function createSnapshot(chapterId: string, content: string, label?: string) {
     if (!projectStore.projectId) return;
     try {
-      await invoke('create_snapshot', {
-        projectId: projectStore.projectId,
-        chapterId,
-        content,
-      });
+      await snapshotsApi.create(projectStore.projectId, chapterId, content, label);
       await fetchSnapshots(chapterId);
     } catch (e) {
       console.error('Failed to create snapshot', e);
@@ -57,14 +47,14 @@ export const useSnapshotStore = defineStore('snapshots', () => {
     }
   }
 
-  async function restoreSnapshot(chapterId: string, snapshotFilename: string) {
+  async function restoreSnapshot(chapterId: string, snapshotId: string) {
     if (!projectStore.projectId) return;
     try {
-      const newContent = await invoke<string>('restore_snapshot', {
-        projectId: projectStore.projectId,
+      const newContent = await snapshotsApi.restore(
+        projectStore.projectId,
         chapterId,
-        snapshotFilename,
-      });
+        snapshotId,
+      );
       await fetchSnapshots(chapterId);
       return newContent;
     } catch (e) {
@@ -73,14 +63,10 @@ export const useSnapshotStore = defineStore('snapshots', () => {
     }
   }
 
-  async function branchSnapshot(chapterId: string, snapshotFilename: string) {
+  async function branchSnapshot(chapterId: string, snapshotId: string) {
     if (!projectStore.projectId) return;
     try {
-      await invoke('branch_snapshot', {
-        projectId: projectStore.projectId,
-        snapshotChapterId: chapterId,
-        snapshotFilename,
-      });
+      await snapshotsApi.branch(projectStore.projectId, chapterId, snapshotId);
       await loadProject(projectStore.projectId);
     } catch (e) {
       console.error('Failed to branch snapshot', e);
@@ -1,4 +1,5 @@
 export interface ProjectMetadata {
+  schema_version: number;
   id: string;
   title: string;
   author: string;
@@ -8,11 +9,21 @@ export interface ProjectMetadata {
   manifest: Manifest;
   characters: Character[];
   plotlines: Plotline[];
+  compile_presets: CompilePreset[];
 }
 
 export interface ProjectSettings {
   daily_target: number;
   word_target: number;
+  snapshots?: SnapshotPolicy;
+}
+
+export interface SnapshotPolicy {
+  keep_all_minutes: number;
+  hourly_for_days: number;
+  daily_for_days: number;
+  min_interval_seconds: number;
+  min_change_chars: number;
 }
 
 export interface Manifest {
@@ -38,6 +49,8 @@ export interface Chapter {
   depends_on?: string;
   /** POV character ID (for simultaneous-scene paradox detection) */
   pov_character_id?: string;
+  /** Short summary of the node, e.g. from a Scrivener synopsis */
+  synopsis?: string;
 }
 
 export interface Character {
@@ -83,6 +96,7 @@ export interface FileNode {
   plotline_tag?: string;
   depends_on?: string;
   pov_character_id?: string;
+  synopsis?: string;
 }
 
 export interface DailyStats {
@@ -111,6 +125,59 @@ export interface Plotline {
   color: string;
 }
 
+// Compile Types
+
+export type ChapterNumbering = 'none' | 'arabic' | 'roman' | 'spelled_out';
+
+export type ManuscriptFont = 'courier' | 'times';
+
+export type PdfFont = 'times' | 'helvetica' | 'courier';
+
+/** Page setup for PDF proofs; lengths in millimetres, font size in points */
+export interface PdfLayout {
+  page_width: number;
+  page_height: number;
+  margin_top: number;
+  margin_bottom: number;
+  margin_inner: number;
+  margin_outer: number;
+  font: PdfFont;
+  font_size: number;
+  line_spacing: number;
+  running_headers: boolean;
+}
+
+export interface CompilePreset {
+  id: string;
+  name: string;
+  /** Node ids compiled with their subtrees; empty means the whole manifest */
+  include: string[];
+  exclude: string[];
+  /** Heading level per manifest depth; null joins the node to the previous one */
+  heading_levels: (number | null)[];
+  numbering: ChapterNumbering;
+  chapter_label: string;
+  scene_separator: string;
+  front_matter: boolean;
+  back_matter: boolean;
+  manuscript_font: ManuscriptFont;
+  pdf: PdfLayout;
+}
+
+export interface SnapshotEntry {
+  id: string;
+  timestamp: string; // ISO 8601
+  hash: string; // SHA-256 of the content
+  label?: string | null;
+}
+
+export interface Milestone {
+  id: string;
+  name: string;
+  created_at: string; // ISO 8601
+  files: Record<string, string>; // Project-relative path -> content hash
+}
+
 export interface TimelineScene {
   chapter: Chapter;
   x: number; // Computed pixel position
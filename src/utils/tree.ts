@@ -95,6 +95,7 @@ export function reconstructHierarchy(chapters: Chapter[]): FileNode[] {
       plotline_tag: c.plotline_tag,
       depends_on: c.depends_on,
       pov_character_id: c.pov_character_id,
+      synopsis: c.synopsis,
       children: [],
     };
     nodeMap.set(c.id, node);
@@ -133,6 +134,7 @@ export function projectToManifest(nodes: FileNode[]): Manifest {
       plotline_tag: node.plotline_tag,
       depends_on: node.depends_on,
       pov_character_id: node.pov_character_id,
+      synopsis: node.synopsis,
     });
   });
 